@@ -27,10 +27,17 @@ pub use std::default::Default;
 pub use std::clone::Clone;
 pub use std::cmp::PartialEq;
 pub use std::ops::{ AddAssign, Add };
-pub use std::fmt::Debug;
+pub use std::fmt::{ Debug, Display };
+pub use std::str::FromStr;
 pub use std::hash::Hash;
 pub use std::iter::IntoIterator;
 pub use std::iter::Iterator;
+use std::iter::FromIterator;
+use std::collections::{ HashMap, HashSet, BTreeMap };
+use std::collections::hash_map::Entry;
+use bagformat::{ self, ParseBagError };
+use std::collections::BinaryHeap;
+use std::cmp::{ Ordering, Reverse };
 
 /// A container for inserting and removing given values.
 ///
@@ -41,11 +48,16 @@ pub use std::iter::Iterator;
 /// for a non-empty bag, the elements in the bag are stored in `data[ 0 ]`
 /// through `data[ used - 1 ]`, and we don't care what's in the
 /// rest of data.
+///
+/// 3. If `sorted` is true, `data[ 0 ]` through `data[ used - 1 ]` are
+/// in ascending order. It is set by sort( ) and kept while the bag is
+/// only erased from, and is always true for fewer than two elements.
 
 pub struct Bag<Type: PartialEq + Clone + Default>
 {
    data: Vec<Type>,
-   used: usize
+   used: usize,
+   sorted: bool
 }
 
 impl<Type: PartialEq + Clone + Default> Bag<Type>
@@ -68,7 +80,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
 
     pub fn new() -> Self
     {
-        Bag { data: vec![ Type::default(); 1 ], used: 0 }
+        Bag { data: vec![ Type::default(); 1 ], used: 0, sorted: true }
     }
 
     /// Initialize an empty bag having a capacity of `initial_capacity`.
@@ -91,6 +103,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// # Aborts
     /// OOM: Insufficient memory for allocating a new array.
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     pub fn with_capacity( initial_capacity: usize ) -> Self
     {
         if initial_capacity <= 0
@@ -99,7 +112,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
         }
         else
         {
-            Bag { data: vec![ Type::default(); initial_capacity ], used: 0 }
+            Bag { data: vec![ Type::default(); initial_capacity ], used: 0, sorted: true }
         }
     }
 
@@ -121,7 +134,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
 
     pub fn from_bag( source: &Bag<Type> ) -> Self
     {
-        Bag { data: source.data.clone() , used: source.used }
+        Bag { data: source.data.clone() , used: source.used, sorted: source.sorted }
     }
 
     /// Potentially increase capacity of this bag.
@@ -138,7 +151,8 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// # Postcondition
     /// The bag's capacity is at least `new_capacity`.  If the capacity
     /// was already at or greater than `new_capacity`, then the capacity
-    /// is left unchanged.
+    /// is left unchanged. Otherwise the array is grown to exactly
+    /// `new_capacity` elements.
     ///
     /// # Note
     /// Unlike the expected reserve trait that takes an argument
@@ -151,6 +165,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// # Aborts
     /// OOM: Insufficient memory for allocating a new array.
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     pub fn ensure_capacity( &mut self, mut new_capacity: usize )
     {
         if new_capacity <= 0
@@ -162,7 +177,8 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
             if self.data.len() < new_capacity
             {
                 new_capacity -= self.data.len();
-                self.data.extend( vec![ Type::default(); new_capacity ] );
+                self.data.reserve_exact( new_capacity );
+                self.data.resize( self.data.len() + new_capacity, Type::default() );
             }
         }
     }
@@ -195,6 +211,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// `target` have been removed and the method returns number of items removed.
     ///
     /// Used will change if one is found, but the capacity will not.
+    /// If the bag is sorted the other elements keep their order.
     ///
     /// # Return
     /// An unsigned integer value representing the number of items erased from bag.
@@ -218,6 +235,24 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
         let old_used = self.used;
         let mut index = 0;
 
+        if self.sorted
+        {
+            // the kept elements are moved down in order
+            let mut kept = 0;
+
+            for index in 0..self.used
+            {
+                if self.data[ index ] != target
+                {
+                    self.data.swap( kept, index );
+                    kept += 1;
+                }
+            }
+
+            self.used = kept;
+            return old_used - self.used;
+        }
+
         // loop will only iterate though the full
         //  range of 0 to this.used, which can vary though the loop
         while index < self.used
@@ -249,6 +284,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// Otherwise the bag remains unchanged and the method returns false.
     ///
     /// Used will change if one is found, but the capacity will not.
+    /// If the bag is sorted the other elements keep their order.
     ///
     /// # Return
     /// True or false depending on whether `target` exists in the bag.
@@ -269,6 +305,12 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
         match self.data[ ..self.used ].iter().position( | value | *value == target )
         {
             None    =>      false,
+            Some( index ) if self.sorted =>
+                {
+                    self.data[ index..self.used ].rotate_left( 1 );
+                    self.used -= 1;
+                    true
+                }
             Some( index ) =>  
                 {
                     self.used -= 1;
@@ -289,6 +331,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// A new copy of the element has been added to this bag.
     ///
     /// Used will increase by one, the capacity only change if needed.
+    /// A bag of more than one element is no longer marked sorted.
     ///
     /// # Panics
     /// If `self.data.len() * 2` causes an unsigned integer overflow.
@@ -319,6 +362,7 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
 
         self.data[ self.used ] = new_item;
         self.used += 1;
+        self.sorted = self.used == 1;
     }
 
     /// Determine the number of elements in this bag.
@@ -332,68 +376,683 @@ impl<Type: PartialEq + Clone + Default> Bag<Type>
     /// # Return
     /// The number of elements in this bag.
 
-    pub fn size( &self ) -> usize
+    pub fn size( &self ) -> usize
+    {
+        self.used
+    }
+
+    /// Accessor method to count the number of occurrences of a
+    /// particular element in this bag.
+    /// 
+    /// Implementation matches occurrences( target ).
+    ///
+    /// # Parameter: `target`
+    /// The element for which number of occurrences will be counted.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The number of times that `target` occurs in this bag.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    /// bag.insert( 4 );
+    /// bag.insert( 6 );
+    /// bag.insert( 6 );
+    /// bag.insert( 6 );
+    ///
+    /// assert_eq!( bag.occurrences( 4 ), 2 );
+    /// ```
+
+    pub fn occurrences( &self, target: Type ) -> usize
+    {
+        self.data[ ..self.used ].iter()
+                                .filter( | &value | *value == target )
+                                .count()
+    }
+
+    /// Reduces the capacity of this bag to current size if there is
+    /// excess capacity.
+    ///
+    /// # Note
+    /// This method has been deprecated in favor of shrink_to_fit().
+    ///
+    /// # Postcondition
+    /// Capacity of this bag is reduced to the current number
+    /// of items in bag or left unchanged if capacity equals to
+    /// number of items in bag but must be at least one.
+
+    pub fn trim_to_size( &mut self )
+    {
+        self.data.truncate(
+            if self.used <= 1
+            {
+                1
+            }
+            else
+            {
+                self.used
+            }
+        );
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> Bag<Type>
+{
+    /// Whether the bag is known to be in ascending order.
+    ///
+    /// # Return
+    /// True after sort( ) until an element is inserted or added, and
+    /// for a bag of fewer than two elements.
+
+    pub fn is_sorted( &self ) -> bool
+    {
+        self.sorted
+    }
+
+    /// Sort the elements of this bag in place by a comparison.
+    ///
+    /// # Parameter: `compare`
+    /// The order of two elements, which must be a total order.
+    ///
+    /// # Postcondition
+    /// The elements are ordered by `compare`, with equal elements in
+    /// their stored order. As the order need not be the ascending
+    /// order of the elements, the bag is not marked sorted.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 3 );
+    /// bag.insert( 2 );
+    /// bag.sort_by( | a, b | b.cmp( a ) );
+    ///
+    /// assert_eq!( bag.into_iter().collect::<Vec<&i32>>(), vec![ &3, &2, &1 ] );
+    /// ```
+
+    pub fn sort_by<Compare>( &mut self, compare: Compare )
+        where Compare: FnMut( &Type, &Type ) -> Ordering
+    {
+        self.data[ ..self.used ].sort_by( compare );
+        self.sorted = self.used <= 1;
+    }
+}
+
+// The steps by which a transaction of bagtransaction undoes its changes.
+impl<Type: PartialEq + Clone + Default> Bag<Type>
+{
+    // The index and a copy of the element that erase_one( target ) removes.
+    pub( crate ) fn find( &self, target: &Type ) -> Option<( usize, Type )>
+    {
+        self.data[ ..self.used ].iter().position( | value | value == target )
+                                .map( | index | ( index, self.data[ index ].clone() ) )
+    }
+
+    // Returns the bag to the length, capacity and sorted flag it had
+    // before elements were inserted or added to it.
+    pub( crate ) fn restore_len( &mut self, used: usize, capacity: usize, sorted: bool )
+    {
+        self.used = used;
+        self.data.truncate( capacity );
+        self.sorted = sorted;
+    }
+
+    // The index and a copy of each element that erase( target ) removes,
+    // in ascending order, and, unless the bag is sorted, copies of the
+    // last elements, which erase( _ ) moves into their places.
+    pub( crate ) fn find_all( &self, target: &Type ) -> ( Vec<( usize, Type )>, Vec<Type> )
+    {
+        let erased: Vec<( usize, Type )> = self.data[ ..self.used ].iter()
+                                                                   .enumerate()
+                                                                   .filter( | &( _, value ) | value == target )
+                                                                   .map( | ( index, value ) | ( index, value.clone() ) )
+                                                                   .collect();
+
+        let tail = if self.sorted { Vec::new() } else { self.data[ self.used - erased.len()..self.used ].to_vec() };
+
+        ( erased, tail )
+    }
+
+    // Puts back the elements that erase( _ ) removed, as found by
+    // find_all( _ ) before it, in their places and order.
+    pub( crate ) fn unerase_all( &mut self, erased: Vec<( usize, Type )>, tail: Vec<Type> )
+    {
+        let kept = self.used;
+        let used = kept + erased.len();
+
+        if self.sorted
+        {
+            // the kept elements are moved back up around the erased ones
+            let mut erased = erased.into_iter().rev().peekable();
+            let mut next_kept = kept;
+
+            for index in ( 0..used ).rev()
+            {
+                match erased.next_if( | &( position, _ ) | position == index )
+                {
+                    Some( ( _, value ) ) => self.data[ index ] = value,
+                    None =>
+                        {
+                            next_kept -= 1;
+                            self.data.swap( next_kept, index );
+                        }
+                }
+            }
+        }
+        else
+        {
+            // the last elements go back to the end, and the erased ones
+            // to the places the last elements were moved into
+            for ( offset, value ) in tail.into_iter().enumerate()
+            {
+                self.data[ kept + offset ] = value;
+            }
+
+            for ( index, value ) in erased
+            {
+                self.data[ index ] = value;
+            }
+        }
+
+        self.used = used;
+    }
+
+    // Puts `value` back at `index`, from where erase_one( _ ) removed it.
+    pub( crate ) fn unerase( &mut self, index: usize, value: Type )
+    {
+        if self.sorted
+        {
+            self.data[ self.used ] = value;
+            self.data[ index..=self.used ].rotate_right( 1 );
+        }
+        else
+        {
+            // the element moved into the gap goes back to the end
+            self.data[ self.used ] = self.data[ index ].clone();
+            self.data[ index ] = value;
+        }
+
+        self.used += 1;
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + Ord> Bag<Type>
+{
+    /// Sort the elements of this bag in place in ascending order.
+    ///
+    /// # Postcondition
+    /// The elements are in ascending order, with equal elements in their
+    /// stored order, and the bag is marked sorted so binary_search_count( )
+    /// and dedup_counts( ) need not sort it again.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 3 );
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.sort();
+    ///
+    /// assert!( bag.is_sorted() );
+    /// assert_eq!( bag.into_iter().collect::<Vec<&i32>>(), vec![ &1, &2, &3 ] );
+    /// ```
+
+    pub fn sort( &mut self )
+    {
+        if !self.sorted
+        {
+            self.data[ ..self.used ].sort();
+            self.sorted = true;
+        }
+    }
+
+    /// Iterate over the elements of this bag in ascending order.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning every element, with equal elements in
+    /// their stored order.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the sorted references.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 3 );
+    /// bag.insert( 1 );
+    ///
+    /// assert_eq!( bag.sorted_iter().collect::<Vec<&i32>>(), vec![ &1, &3 ] );
+    /// ```
+
+    pub fn sorted_iter( &self ) -> ::std::vec::IntoIter<&Type>
+    {
+        let mut sorted: Vec<&Type> = self.data[ ..self.used ].iter().collect();
+
+        if !self.sorted
+        {
+            sorted.sort();
+        }
+
+        sorted.into_iter()
+    }
+
+    /// Count the number of occurrences of `target` in this bag,
+    /// by binary search if the bag is sorted.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The number of times that `target` occurs in this bag, found in
+    /// logarithmic time if the bag is sorted and linear time if not.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.sort();
+    ///
+    /// assert_eq!( bag.binary_search_count( &2 ), 2 );
+    /// ```
+
+    pub fn binary_search_count( &self, target: &Type ) -> usize
+    {
+        let used = &self.data[ ..self.used ];
+
+        if !self.sorted
+        {
+            return used.iter().filter( | &value | value == target ).count();
+        }
+
+        let start = used.partition_point( | value | value < target );
+        let end = used.partition_point( | value | value <= target );
+
+        end - start
+    }
+
+    /// Sort this bag, if it is not sorted, and list each distinct
+    /// value with its multiplicity.
+    ///
+    /// # Postcondition
+    /// The bag is sorted as by sort( ).
+    ///
+    /// # Return
+    /// The `( value, multiplicity )` pairs in ascending order of the values.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the list.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.dedup_counts(), vec![ ( 2, 1 ), ( 4, 2 ) ] );
+    /// ```
+
+    pub fn dedup_counts( &mut self ) -> Vec<( Type, usize )>
+    {
+        self.sort();
+
+        let mut counts: Vec<( Type, usize )> = Vec::new();
+
+        for value in &self.data[ ..self.used ]
+        {
+            match counts.last_mut()
+            {
+                Some( &mut ( ref last, ref mut count ) ) if last == value => { *count += 1; }
+                _ => { counts.push( ( value.clone(), 1 ) ); }
+            }
+        }
+
+        counts
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + Hash + Eq> Bag<Type>
+{
+    /// Iterate over the distinct values in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning each value in the bag once, in the
+    /// order of their first occurrence.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.distinct().collect::<Vec<&i32>>(), vec![ &4, &2 ] );
+    /// ```
+
+    pub fn distinct( &self ) -> impl Iterator<Item = &Type> + '_
+    {
+        self.counts().map( | ( value, _ ) | value )
+    }
+
+    /// Count the distinct values in this bag exactly.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The number of values returned by distinct( ).
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the set of values.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.distinct_len(), 2 );
+    /// ```
+
+    pub fn distinct_len( &self ) -> usize
+    {
+        self.data[ ..self.used ].iter().collect::<HashSet<&Type>>().len()
+    }
+
+    /// Iterate over the distinct values in this bag with the
+    /// number of times each occurs.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning `( value, multiplicity )` for each value
+    /// in the bag, in the order of their first occurrence.
+    ///
+    /// # Note
+    /// The bag is read once, unlike calling occurrences( _ ) for each element.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.counts().collect::<Vec<( &i32, usize )>>(), vec![ ( &4, 2 ), ( &2, 1 ) ] );
+    /// ```
+
+    pub fn counts( &self ) -> ::std::vec::IntoIter<( &Type, usize )>
+    {
+        let mut positions: HashMap<&Type, usize> = HashMap::new();
+        let mut counts: Vec<( &Type, usize )> = Vec::new();
+
+        for value in &self.data[ ..self.used ]
+        {
+            match positions.entry( value )
+            {
+                Entry::Occupied( entry ) => { counts[ *entry.get() ].1 += 1; }
+                Entry::Vacant( entry ) =>
+                    {
+                        entry.insert( counts.len() );
+                        counts.push( ( value, 1 ) );
+                    }
+            }
+        }
+
+        counts.into_iter()
+    }
+
+    /// List the `k` values that occur most often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the highest
+    /// multiplicity to the lowest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// assert_eq!( bag.most_common( 2 ), vec![ ( &2, 2 ), ( &1, 1 ) ] );
+    /// ```
+
+    pub fn most_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( ::bag::first_counts( self.data[ ..self.used ].iter() ).into_iter(), k, false )
+    }
+
+    /// List the `k` values that occur least often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the lowest
+    /// multiplicity to the highest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// assert_eq!( bag.least_common( 2 ), vec![ ( &1, 1 ), ( &3, 1 ) ] );
+    /// ```
+
+    pub fn least_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( ::bag::first_counts( self.data[ ..self.used ].iter() ).into_iter(), k, true )
+    }
+
+    /// Convert this bag into a map from each value to its multiplicity.
+    ///
+    /// # Return
+    /// A `HashMap` holding the number of times each value occurs in the bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the map.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.into_counts()[ &4 ], 2 );
+    /// ```
+
+    pub fn into_counts( self ) -> HashMap<Type, usize>
+    {
+        let mut counts = HashMap::new();
+
+        for value in self.data.into_iter().take( self.used )
+        {
+            *counts.entry( value ).or_insert( 0 ) += 1;
+        }
+
+        counts
+    }
+
+    /// Convert this bag into a sorted map from each value to its multiplicity.
+    ///
+    /// # Return
+    /// A `BTreeMap` holding the number of times each value occurs in
+    /// the bag, iterated in ascending order of the values.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the map.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.into_sorted_counts().into_iter().collect::<Vec<( i32, usize )>>(), vec![ ( 2, 1 ), ( 4, 2 ) ] );
+    /// ```
+
+    pub fn into_sorted_counts( self ) -> BTreeMap<Type, usize>
+        where Type: Ord
+    {
+        let mut counts = BTreeMap::new();
+
+        for value in self.data.into_iter().take( self.used )
+        {
+            *counts.entry( value ).or_insert( 0 ) += 1;
+        }
+
+        counts
+    }
+}
+
+/// Count the distinct values of a list of values.
+///
+/// # Return
+/// A map from each value to the position of its first occurrence
+/// and its multiplicity, for select_common( _, _, _ ).
+///
+/// # Aborts
+/// OOM: Insufficient memory for allocating the map.
+
+pub fn first_counts<'a, Type, Values>( values: Values ) -> HashMap<&'a Type, ( usize, usize )>
+    where Type: 'a + Hash + Eq, Values: Iterator<Item = &'a Type>
+{
+    let mut counts: HashMap<&Type, ( usize, usize )> = HashMap::new();
+
+    for ( position, value ) in values.enumerate()
+    {
+        counts.entry( value ).or_insert( ( position, 0 ) ).1 += 1;
+    }
+
+    counts
+}
+
+/// Select the `k` most or least common of a list of counts.
+///
+/// # Parameter: `counts`
+/// `( value, ( position, multiplicity ) )` pairs in any order, where
+/// `position` orders the values of equal multiplicity, as given by
+/// first_counts( _ ).
+///
+/// # Parameter: `least`
+/// Whether to select the lowest multiplicities rather than the highest.
+///
+/// # Return
+/// Up to `k` of the `( value, multiplicity )` pairs, ordered by
+/// multiplicity, highest first or lowest first if `least`, and then
+/// by their positions.
+///
+/// # Note
+/// Only a heap of at most `k` pairs is kept, taking O(n log k) time
+/// and O(k) memory rather than sorting all of the pairs.
+
+pub fn select_common<Value, Counts>( counts: Counts, k: usize, least: bool ) -> Vec<( Value, usize )>
+    where Counts: Iterator<Item = ( Value, ( usize, usize ) )>
+{
+    // the top of the heap is the worst pair kept, so it is the one replaced
+    let mut heap = BinaryHeap::with_capacity( k.saturating_add( 1 ).min( 1024 ) );
+
+    for ( value, ( position, count ) ) in counts
+    {
+        let rank = if least { usize::MAX - count } else { count };
+
+        heap.push( Reverse( Selected { rank: ( rank, Reverse( position ) ), value, count } ) );
+
+        if heap.len() > k
+        {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map( | Reverse( selected ) | ( selected.value, selected.count ) )
+        .collect()
+}
+
+// A pair kept by select_common( _, _, _ ), ordered by its rank alone.
+struct Selected<Value>
+{
+    rank: ( usize, Reverse<usize> ),
+    value: Value,
+    count: usize
+}
+
+impl<Value> PartialEq for Selected<Value>
+{
+    fn eq( &self, other: &Self ) -> bool
     {
-        self.used
+        self.rank == other.rank
     }
+}
 
-    /// Accessor method to count the number of occurrences of a
-    /// particular element in this bag.
-    /// 
-    /// Implementation matches occurrences( target ).
-    ///
-    /// # Parameter: `target`
-    /// The element for which number of occurrences will be counted.
-    ///
-    /// # Postcondition
-    /// This method does not alter state of the bag.
-    ///
-    /// # Return
-    /// The number of times that `target` occurs in this bag.
-    ///
-    /// # Examples
-    /// ```
-    /// let mut bag = Bag::new();
-    /// bag.insert( 2 );
-    /// bag.insert( 4 );
-    /// bag.insert( 4 );
-    /// bag.insert( 6 );
-    /// bag.insert( 6 );
-    /// bag.insert( 6 );
-    ///
-    /// assert_eq!( bag.occurrences( 4 ), 2 );
-    /// ```
+impl<Value> Eq for Selected<Value> {}
 
-    pub fn occurrences( &self, target: Type ) -> usize
+impl<Value> PartialOrd for Selected<Value>
+{
+    fn partial_cmp( &self, other: &Self ) -> Option<Ordering>
     {
-        self.data[ ..self.used ].iter()
-                                .filter( | &value | *value == target )
-                                .count()
+        Some( self.cmp( other ) )
     }
+}
 
-    /// Reduces the capacity of this bag to current size if there is
-    /// excess capacity.
-    ///
-    /// # Note
-    /// This method has been deprecated in favor of shrink_to_fit().
-    ///
-    /// # Postcondition
-    /// Capacity of this bag is reduced to the current number
-    /// of items in bag or left unchanged if capacity equals to
-    /// number of items in bag but must be at least one.
-
-    pub fn trim_to_size( &mut self )
+impl<Value> Ord for Selected<Value>
+{
+    fn cmp( &self, other: &Self ) -> Ordering
     {
-        self.data.truncate(
-            if self.used <= 1
-            {
-                1
-            }
-            else
-            {
-                self.used
-            }
-        );
+        self.rank.cmp( &other.rank )
     }
 }
 
@@ -472,6 +1131,7 @@ impl<Type: PartialEq + Clone + Default> Clear for Bag<Type>
     fn clear(&mut self)
     {
         self.used = 0;
+        self.sorted = true;
     }
 }
 
@@ -537,6 +1197,7 @@ impl<Type: PartialEq + Clone + Default> WithCapacity for Bag<Type>
     /// bag.insert( 11 );
     /// ```
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     fn with_capacity( initial_capacity: usize ) -> Self
     {
         if initial_capacity <= 0
@@ -545,7 +1206,7 @@ impl<Type: PartialEq + Clone + Default> WithCapacity for Bag<Type>
         }
         else
         {
-            Bag { data: vec![ Type::default(); initial_capacity ], used: 0 }
+            Bag { data: vec![ Type::default(); initial_capacity ], used: 0, sorted: true }
         }
     }
 }
@@ -565,7 +1226,8 @@ impl<Type: PartialEq + Clone + Default> CapacityMut for Bag<Type>
     /// # Postcondition
     /// The bag's capacity is at least `new_capacity`.  If the capacity
     /// was already at or greater than `new_capacity`, then the capacity
-    /// is left unchanged.
+    /// is left unchanged. Otherwise the array is grown to exactly
+    /// `new_capacity` elements.
     ///
     /// # Note
     /// Unlike the expected reserve trait that takes an argument
@@ -586,6 +1248,7 @@ impl<Type: PartialEq + Clone + Default> CapacityMut for Bag<Type>
     /// assert_eq!( bag.capacity(), 10 );
     /// ```
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     fn reserve( &mut self, mut new_capacity: usize )
     {
         if new_capacity <= 0
@@ -597,7 +1260,8 @@ impl<Type: PartialEq + Clone + Default> CapacityMut for Bag<Type>
             if self.data.len() < new_capacity
             {
                 new_capacity -= self.data.len();
-                self.data.extend( vec![ Type::default(); new_capacity ] );
+                self.data.reserve_exact( new_capacity );
+                self.data.resize( self.data.len() + new_capacity, Type::default() );
             }
         }
     }
@@ -651,7 +1315,7 @@ impl<Type: PartialEq + Clone + Default> Default for Bag<Type>
 
     fn default() -> Self
     {
-        Bag { data: vec![ Type::default(); 1 ], used: 0 }
+        Bag { data: vec![ Type::default(); 1 ], used: 0, sorted: true }
     }
 }
 
@@ -681,7 +1345,7 @@ impl<Type: PartialEq + Clone + Default> Clone for Bag<Type>
 
     fn clone( &self ) -> Self
     {
-        Bag { data: self.data.clone() , used: self.used }
+        Bag { data: self.data.clone() , used: self.used, sorted: self.sorted }
     }
 
     /// Make a copy from a source.
@@ -703,6 +1367,7 @@ impl<Type: PartialEq + Clone + Default> Clone for Bag<Type>
     {
         self.data = source.data.clone();
         self.used = source.used;
+        self.sorted = source.sorted;
     }
 }
 
@@ -745,12 +1410,18 @@ impl<Type: PartialEq + Clone + Default> PartialEq for Bag<Type>
     /// # Return
     /// False if `self == other`.
 
+    #[ allow( clippy::partialeq_ne_impl ) ]
     fn ne( &self, other: &Bag<Type> ) -> bool
     {
         !( self == other )
     }
 }
 
+/// Bags of elements whose equality is an equivalence are equal
+/// to themselves, so they can be the keys of maps and sets.
+
+impl<Type: PartialEq + Clone + Default + Eq> Eq for Bag<Type> {}
+
 impl<Type: PartialEq + Clone + Default> AddAssign for Bag<Type>
 {
     /// Add the contents of another bag to this bag.
@@ -776,6 +1447,11 @@ impl<Type: PartialEq + Clone + Default> AddAssign for Bag<Type>
     {
         let mut old_capacity = self.data.len();
 
+        if other.used > 0
+        {
+            self.sorted = self.used == 0 && other.sorted;
+        }
+
         self.data.truncate( self.used );
         self.data.extend_from_slice( &other.data[ ..other.used ] );
         self.used += other.used;
@@ -817,7 +1493,67 @@ impl<Type: PartialEq + Clone + Default> Add for Bag<Type>
         Bag { data: [ &self.data[ ..self.used ]
                 , &other.data[ ..other.used ] ]
                 .concat()
-            , used: self.used + other.used }
+            , used: self.used + other.used
+            , sorted: ( self.sorted && other.used == 0 ) || ( other.sorted && self.used == 0 ) }
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> FromIterator<Type> for Bag<Type>
+{
+    /// Create a bag of the values of an iterator.
+    ///
+    /// # Return
+    /// A bag holding each value in the order it is returned.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: Bag<i32> = vec![ 1, 2, 2 ].into_iter().collect();
+    ///
+    /// assert_eq!( bag.occurrences( 2 ), 2 );
+    /// ```
+
+    fn from_iter<Values: IntoIterator<Item = Type>>( values: Values ) -> Self
+    {
+        let mut bag = Bag::new();
+
+        bag.extend( values );
+        bag
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> Extend<Type> for Bag<Type>
+{
+    /// Insert each value of an iterator into this bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+
+    fn extend<Values: IntoIterator<Item = Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert( value );
+        }
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> Extend<&'a Type> for Bag<Type>
+{
+    /// Insert a copy of each value of a bag, or other iterator of
+    /// references, into this bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+
+    fn extend<Values: IntoIterator<Item = &'a Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert( value.clone() );
+        }
     }
 }
 
@@ -833,8 +1569,8 @@ impl<Type: PartialEq + Clone + Default + Debug> Debug for Bag<Type>
     /// # Postcondition
     /// The bag is not altered by this method.
     ///
-    /// # Panics
-    /// If the iterated write! returns an Err.
+    /// # Return
+    /// An Err if writing to `fmt` failed.
 
     fn fmt( &self, fmt: &mut ::std::fmt::Formatter ) -> ::std::fmt::Result
     {
@@ -846,9 +1582,10 @@ impl<Type: PartialEq + Clone + Default + Debug> Debug for Bag<Type>
         }
         else
         {
-            self.data[ ..( self.used - 1 ) ].iter().for_each(
-                |value| write!( fmt, " {:?},",value ).unwrap() );
-                    // the write returned value is dropped instead of propogated
+            for value in &self.data[ ..( self.used - 1 ) ]
+            {
+                write!( fmt, " {:?},", value )?;
+            }
 
             write!( fmt, " {:?} ] Capacity: {:?}",
                     self.data[ self.used - 1 ],
@@ -857,6 +1594,107 @@ impl<Type: PartialEq + Clone + Default + Debug> Debug for Bag<Type>
     }
 }
 
+impl<Type: PartialEq + Clone + Default + Display> Display for Bag<Type>
+{
+    /// Renders the bag's contents in the text format of `bagformat`,
+    /// which can be read back with parse( ).
+    ///
+    /// # Precondition
+    /// The type in the bag implements the trait: `Display`.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    ///
+    /// assert_eq!( bag.to_string(), "{1, 2×2}" );
+    /// ```
+
+    fn fmt( &self, fmt: &mut ::std::fmt::Formatter ) -> ::std::fmt::Result
+    {
+        bagformat::write_bag( fmt, self.into_iter() )
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + FromStr> Bag<Type>
+    where Type::Err: Display
+{
+    /// Read a bag from the text format of `bagformat`, holding at most
+    /// `limit` elements.
+    ///
+    /// # Parameter: `limit`
+    /// The most elements the bag may hold, such as a lower limit than
+    /// that of `FromStr` for text that is not trusted.
+    ///
+    /// # Return
+    /// A bag holding the elements written in `text` in the same
+    /// order, or an Err with the column of the first problem found,
+    /// which for a bag of more than `limit` elements is `TooManyElements`.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// assert!( Bag::<String>::parse_within( "{a×3}", 3 ).is_ok() );
+    /// assert!( Bag::<String>::parse_within( "{a×3, b}", 3 ).is_err() );
+    /// ```
+
+    pub fn parse_within( text: &str, limit: usize ) -> Result<Self, ParseBagError>
+    {
+        let mut bag = Self::new();
+
+        for ( value, count ) in bagformat::parse_runs_within::<Type>( text, limit )?
+        {
+            for _ in 1..count
+            {
+                bag.insert( value.clone() );
+            }
+
+            bag.insert( value );
+        }
+
+        Ok( bag )
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + FromStr> FromStr for Bag<Type>
+    where Type::Err: Display
+{
+    type Err = ParseBagError;
+
+    /// Reads a bag from the text format of `bagformat`.
+    ///
+    /// # Parameter: `text`
+    /// The text of a bag, such as `{1, 2×3}`.
+    ///
+    /// # Return
+    /// A bag holding the elements written in `text` in the same
+    /// order, or an Err with the column of the first problem found,
+    /// which for a bag of more than `bagformat::max_elements::<Type>()`
+    /// elements is `TooManyElements`.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: Bag<i32> = "{1, 2×3}".parse().unwrap();
+    ///
+    /// assert_eq!( bag.occurrences( 2 ), 3 );
+    /// ```
+
+    fn from_str( text: &str ) -> Result<Self, ParseBagError>
+    {
+        Self::parse_within( text, bagformat::max_elements::<Type>() )
+    }
+}
+
 impl<Type: PartialEq + Clone + Default + Hash> Hash for Bag<Type>
 {
     /// Create a hash value for the bag.
@@ -878,12 +1716,15 @@ impl<Type: PartialEq + Clone + Default + Hash> Hash for Bag<Type>
     }
 }
 
-/// An iterator that references a Bag structure
+/// An iterator that references a Bag structure.
+///
+/// It can be iterated from either end and knows exactly how many
+/// elements remain, which are available as a slice with `as_slice()`.
 
+#[ derive( Clone ) ]
 pub struct BagIterator<'a, Type: 'a + PartialEq + Clone + Default>
 {
-    bag: &'a Bag<Type>,
-    index: Option<usize>
+    remaining: &'a [Type]
 }
 
 impl<'a, Type: PartialEq + Clone + Default> IntoIterator for &'a Bag<Type>
@@ -904,7 +1745,7 @@ impl<'a, Type: PartialEq + Clone + Default> IntoIterator for &'a Bag<Type>
 
     fn into_iter( self ) -> Self::IntoIter
     {
-        BagIterator { bag: self, index: None }
+        BagIterator::new( self )
     }
 }
 
@@ -923,7 +1764,33 @@ impl<'a, Type: PartialEq + Clone + Default> BagIterator<'a, Type>
 
     pub fn new( source: &'a Bag<Type> ) -> BagIterator<'a, Type>
     {
-        BagIterator { bag: source, index: None }
+        BagIterator { remaining: &source.data[ ..source.used ] }
+    }
+
+    /// The elements not yet returned by the iterator.
+    ///
+    /// # Postcondition
+    /// The bag and the iterator are not altered by this method.
+    ///
+    /// # Return
+    /// A slice of the remaining elements, in the order `next()` would return them.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// let mut iter = bag.into_iter();
+    /// iter.next();
+    ///
+    /// assert_eq!( iter.as_slice(), &[ 2, 3 ] );
+    /// ```
+
+    pub fn as_slice( &self ) -> &'a [Type]
+    {
+        self.remaining
     }
 }
 
@@ -963,15 +1830,10 @@ impl<'a, Type: PartialEq + Clone + Default> Iterator for BagIterator<'a, Type>
 
     fn next( &mut self ) -> Option<Self::Item>
     {
-        let next_index = 
-            match self.index
-            {
-                Some( i ) => i + 1,
-                None => 0
-            };
+        let ( first, rest ) = self.remaining.split_first()?;
 
-        self.index = Some( next_index );
-        self.bag.data[ ..self.bag.used ].get( next_index )
+        self.remaining = rest;
+        Some( first )
     }
 
     /// Consumes the iterator, counting the number of iterations remaining.
@@ -997,20 +1859,7 @@ impl<'a, Type: PartialEq + Clone + Default> Iterator for BagIterator<'a, Type>
 
     fn count( self ) -> usize
     {
-        match self.index
-        {
-            Some( i ) => {
-                            if self.bag.used - i > 0
-                            {
-                                self.bag.used - i
-                            }
-                            else
-                            {
-                                0
-                            }
-                        },
-            None => self.bag.used
-        }
+        self.remaining.len()
     }
 
     /// Consumes the iterator, returning the last element.
@@ -1019,7 +1868,7 @@ impl<'a, Type: PartialEq + Clone + Default> Iterator for BagIterator<'a, Type>
     /// The bag is not altered by this method and the iterator is consumed.
     ///
     /// # Return
-    /// The last element in the iterator or None if the lenght is zero.
+    /// The last element in the iterator or None if no elements remain.
     ///
     /// # Examples 
     /// ```
@@ -1036,14 +1885,7 @@ impl<'a, Type: PartialEq + Clone + Default> Iterator for BagIterator<'a, Type>
 
     fn last( self ) -> Option<Self::Item>
     {
-        if self.bag.used > 0
-        {
-            self.bag.data.get( self.bag.used - 1 )
-        }
-        else
-        {
-            None
-        }
+        self.remaining.last()
     }
 
     /// Returns the nth element of the iterator.
@@ -1077,14 +1919,87 @@ impl<'a, Type: PartialEq + Clone + Default> Iterator for BagIterator<'a, Type>
 
     fn nth( &mut self, n: usize ) -> Option<Self::Item>
     {
-        let next_index = 
-            match self.index
-            {
-                Some( i ) => i + n + 1,
-                None => n
-            };
+        // an offset past the end consumes the iterator
+        let skipped = n.min( self.remaining.len() );
+
+        self.remaining = &self.remaining[ skipped.. ];
+        self.next()
+    }
+
+    /// The bounds on the remaining length of the iterator.
+    ///
+    /// # Return
+    /// The exact number of elements remaining as both the lower and upper bound.
+
+    fn size_hint( &self ) -> ( usize, Option<usize> )
+    {
+        ( self.remaining.len(), Some( self.remaining.len() ) )
+    }
+}
+
+impl <'a, Type: PartialEq + Clone + Default> DoubleEndedIterator for BagIterator<'a, Type>
+{
+    /// Removes and returns an element from the end of the iterator.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method and the iterator's end will change.
+    ///
+    /// # Return
+    /// The last remaining value in the bag or None if the iteration is finished.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// let mut iter = bag.into_iter();
+    ///
+    /// assert_eq!( Some( &3 ), iter.next_back() );
+    /// assert_eq!( Some( &1 ), iter.next() );
+    /// assert_eq!( Some( &2 ), iter.next_back() );
+    /// assert_eq!( None, iter.next() );
+    /// ```
+
+    fn next_back( &mut self ) -> Option<Self::Item>
+    {
+        let ( last, rest ) = self.remaining.split_last()?;
+
+        self.remaining = rest;
+        Some( last )
+    }
+
+    /// Returns the nth element from the end of the iterator.
+    ///
+    /// # Parameter: `n`
+    /// The non-negative offset from the iterator's end to access.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method and the iterator's end may change.
+    ///
+    /// # Return
+    /// The nth element from the end or None if n is beyond the length of the iterator.
+
+    fn nth_back( &mut self, n: usize ) -> Option<Self::Item>
+    {
+        let kept = self.remaining.len() - n.min( self.remaining.len() );
+
+        self.remaining = &self.remaining[ ..kept ];
+        self.next_back()
+    }
+}
+
+impl <'a, Type: PartialEq + Clone + Default> ExactSizeIterator for BagIterator<'a, Type>
+{
+    /// The number of elements remaining in the iterator.
 
-        self.index = Some( next_index );
-        self.bag.data[ ..self.bag.used ].get( next_index )
+    fn len( &self ) -> usize
+    {
+        self.remaining.len()
     }
 }
+
+/// Once the iterator returns None it always will.
+
+impl <'a, Type: PartialEq + Clone + Default> ::std::iter::FusedIterator for BagIterator<'a, Type> {}
@@ -32,10 +32,15 @@ pub use std::default::Default;
 pub use std::clone::Clone;
 pub use std::cmp::PartialEq;
 pub use std::ops::{ AddAssign, Add };
-pub use std::fmt::Debug;
+pub use std::fmt::{ Debug, Display };
+pub use std::str::FromStr;
 pub use std::hash::Hash;
 pub use std::iter::IntoIterator;
 pub use std::iter::Iterator;
+use std::iter::FromIterator;
+use std::collections::{ HashMap, HashSet, BTreeMap };
+use std::collections::hash_map::Entry;
+use bagformat::{ self, ParseBagError };
 
 /// A container for inserting and removing given values.
 ///
@@ -95,6 +100,7 @@ impl<Type: PartialEq + Clone> Bag<Type>
     /// # Aborts
     /// OOM: Insufficient memory for allocating a new array.
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     pub fn with_capacity( initial_capacity: usize ) -> Self
     {
         if initial_capacity <= 0
@@ -159,6 +165,7 @@ impl<Type: PartialEq + Clone> Bag<Type>
     /// # Aborts
     /// OOM: Insufficient memory for allocating a new array.
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     pub fn ensure_capacity( &mut self, mut new_capacity: usize )
     {
         if new_capacity <= 0
@@ -169,7 +176,7 @@ impl<Type: PartialEq + Clone> Bag<Type>
         {
             if self.data.capacity() < new_capacity
             {
-                new_capacity -= self.data.capacity();
+                new_capacity -= self.data.len();
                 self.data.reserve_exact( new_capacity );
             }
         }
@@ -406,6 +413,242 @@ impl<Type: PartialEq + Clone> Bag<Type>
     }
 }
 
+impl<Type: PartialEq + Clone + Hash + Eq> Bag<Type>
+{
+    /// Iterate over the distinct values in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning each value in the bag once, in the
+    /// order of their first occurrence.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.distinct().collect::<Vec<&i32>>(), vec![ &4, &2 ] );
+    /// ```
+
+    pub fn distinct( &self ) -> impl Iterator<Item = &Type> + '_
+    {
+        self.counts().map( | ( value, _ ) | value )
+    }
+
+    /// Count the distinct values in this bag exactly.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The number of values returned by distinct( ).
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the set of values.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.distinct_len(), 2 );
+    /// ```
+
+    pub fn distinct_len( &self ) -> usize
+    {
+        self.data.iter().collect::<HashSet<&Type>>().len()
+    }
+
+    /// Iterate over the distinct values in this bag with the
+    /// number of times each occurs.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning `( value, multiplicity )` for each value
+    /// in the bag, in the order of their first occurrence.
+    ///
+    /// # Note
+    /// The bag is read once, unlike calling occurrences( _ ) for each element.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.counts().collect::<Vec<( &i32, usize )>>(), vec![ ( &4, 2 ), ( &2, 1 ) ] );
+    /// ```
+
+    pub fn counts( &self ) -> ::std::vec::IntoIter<( &Type, usize )>
+    {
+        let mut positions: HashMap<&Type, usize> = HashMap::new();
+        let mut counts: Vec<( &Type, usize )> = Vec::new();
+
+        for value in &self.data
+        {
+            match positions.entry( value )
+            {
+                Entry::Occupied( entry ) => { counts[ *entry.get() ].1 += 1; }
+                Entry::Vacant( entry ) =>
+                    {
+                        entry.insert( counts.len() );
+                        counts.push( ( value, 1 ) );
+                    }
+            }
+        }
+
+        counts.into_iter()
+    }
+
+    /// List the `k` values that occur most often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the highest
+    /// multiplicity to the lowest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// assert_eq!( bag.most_common( 2 ), vec![ ( &2, 2 ), ( &1, 1 ) ] );
+    /// ```
+
+    pub fn most_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( ::bag::first_counts( self.data.iter() ).into_iter(), k, false )
+    }
+
+    /// List the `k` values that occur least often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the lowest
+    /// multiplicity to the highest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// assert_eq!( bag.least_common( 2 ), vec![ ( &1, 1 ), ( &3, 1 ) ] );
+    /// ```
+
+    pub fn least_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( ::bag::first_counts( self.data.iter() ).into_iter(), k, true )
+    }
+
+    /// Convert this bag into a map from each value to its multiplicity.
+    ///
+    /// # Return
+    /// A `HashMap` holding the number of times each value occurs in the bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the map.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.into_counts()[ &4 ], 2 );
+    /// ```
+
+    pub fn into_counts( self ) -> HashMap<Type, usize>
+    {
+        let mut counts = HashMap::new();
+
+        for value in self.data
+        {
+            *counts.entry( value ).or_insert( 0 ) += 1;
+        }
+
+        counts
+    }
+
+    /// Convert this bag into a sorted map from each value to its multiplicity.
+    ///
+    /// # Return
+    /// A `BTreeMap` holding the number of times each value occurs in
+    /// the bag, iterated in ascending order of the values.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the map.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4 );
+    /// bag.insert( 2 );
+    /// bag.insert( 4 );
+    ///
+    /// assert_eq!( bag.into_sorted_counts().into_iter().collect::<Vec<( i32, usize )>>(), vec![ ( 2, 1 ), ( 4, 2 ) ] );
+    /// ```
+
+    pub fn into_sorted_counts( self ) -> BTreeMap<Type, usize>
+        where Type: Ord
+    {
+        let mut counts = BTreeMap::new();
+
+        for value in self.data
+        {
+            *counts.entry( value ).or_insert( 0 ) += 1;
+        }
+
+        counts
+    }
+}
+
 impl<Type: PartialEq + Clone> Len for Bag<Type>
 {
     /// Determine the number of elements in this bag.
@@ -546,6 +789,7 @@ impl<Type: PartialEq + Clone> WithCapacity for Bag<Type>
     /// bag.insert( 11 );
     /// ```
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     fn with_capacity( initial_capacity: usize ) -> Self
     {
         if initial_capacity <= 0
@@ -599,6 +843,7 @@ impl<Type: PartialEq + Clone> CapacityMut for Bag<Type>
     /// assert_eq!( bag.capacity(), 10 );
     /// ```
 
+    #[ allow( clippy::absurd_extreme_comparisons ) ]
     fn reserve( &mut self, mut new_capacity: usize )
     {
         if new_capacity <= 0
@@ -609,7 +854,7 @@ impl<Type: PartialEq + Clone> CapacityMut for Bag<Type>
         {
             if self.data.capacity() < new_capacity
             {
-                new_capacity -= self.data.capacity();
+                new_capacity -= self.data.len();
                 self.data.reserve_exact( new_capacity );
             }
         }
@@ -757,12 +1002,18 @@ impl<Type: PartialEq + Clone> PartialEq for Bag<Type>
     /// # Return
     /// False if `self == other`.
 
+    #[ allow( clippy::partialeq_ne_impl ) ]
     fn ne( &self, other: &Bag<Type> ) -> bool
     {
         !( self == other )
     }
 }
 
+/// Bags of elements whose equality is an equivalence are equal
+/// to themselves, so they can be the keys of maps and sets.
+
+impl<Type: PartialEq + Clone + Eq> Eq for Bag<Type> {}
+
 impl<Type: PartialEq + Clone> AddAssign for Bag<Type>
 {
     /// Add the contents of another bag to this bag.
@@ -833,6 +1084,65 @@ impl<Type: PartialEq + Clone> Add for Bag<Type>
     }
 }
 
+impl<Type: PartialEq + Clone> FromIterator<Type> for Bag<Type>
+{
+    /// Create a bag of the values of an iterator.
+    ///
+    /// # Return
+    /// A bag holding each value in the order it is returned.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: Bag<i32> = vec![ 1, 2, 2 ].into_iter().collect();
+    ///
+    /// assert_eq!( bag.occurrences( 2 ), 2 );
+    /// ```
+
+    fn from_iter<Values: IntoIterator<Item = Type>>( values: Values ) -> Self
+    {
+        let mut bag = Bag::new();
+
+        bag.extend( values );
+        bag
+    }
+}
+
+impl<Type: PartialEq + Clone> Extend<Type> for Bag<Type>
+{
+    /// Insert each value of an iterator into this bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+
+    fn extend<Values: IntoIterator<Item = Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert( value );
+        }
+    }
+}
+
+impl<'a, Type: PartialEq + Clone> Extend<&'a Type> for Bag<Type>
+{
+    /// Insert a copy of each value of a bag, or other iterator of
+    /// references, into this bag.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+
+    fn extend<Values: IntoIterator<Item = &'a Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert( value.clone() );
+        }
+    }
+}
+
 impl<Type: PartialEq + Clone + Debug> Debug for Bag<Type>
 {
     /// Renders the bag's contents into a human readable form.
@@ -845,9 +1155,10 @@ impl<Type: PartialEq + Clone + Debug> Debug for Bag<Type>
     /// # Postcondition
     /// The bag is not altered by this method.
     ///
-    /// # Panics
-    /// If the iterated write! returns an Err.
+    /// # Return
+    /// An Err if writing to `fmt` failed.
 
+    #[ allow( clippy::len_zero ) ]
     fn fmt( &self, fmt: &mut ::std::fmt::Formatter ) -> ::std::fmt::Result
     {
         write!( fmt, "Bag with {:?} elements: [", self.data.len() )?;
@@ -858,9 +1169,10 @@ impl<Type: PartialEq + Clone + Debug> Debug for Bag<Type>
         }
         else
         {
-            self.data[ ..( self.data.len() - 1 ) ].iter().for_each(
-                |value| write!( fmt, " {:?},",value ).unwrap() );
-                    // the write returned value is dropped instead of propogated
+            for value in &self.data[ ..( self.data.len() - 1 ) ]
+            {
+                write!( fmt, " {:?},", value )?;
+            }
 
             write!( fmt, " {:?} ] Capacity: {:?}",
                     self.data.last(),
@@ -869,6 +1181,107 @@ impl<Type: PartialEq + Clone + Debug> Debug for Bag<Type>
     }
 }
 
+impl<Type: PartialEq + Clone + Display> Display for Bag<Type>
+{
+    /// Renders the bag's contents in the text format of `bagformat`,
+    /// which can be read back with parse( ).
+    ///
+    /// # Precondition
+    /// The type in the bag implements the trait: `Display`.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 2 );
+    ///
+    /// assert_eq!( bag.to_string(), "{1, 2×2}" );
+    /// ```
+
+    fn fmt( &self, fmt: &mut ::std::fmt::Formatter ) -> ::std::fmt::Result
+    {
+        bagformat::write_bag( fmt, self.into_iter() )
+    }
+}
+
+impl<Type: PartialEq + Clone + FromStr> Bag<Type>
+    where Type::Err: Display
+{
+    /// Read a bag from the text format of `bagformat`, holding at most
+    /// `limit` elements.
+    ///
+    /// # Parameter: `limit`
+    /// The most elements the bag may hold, such as a lower limit than
+    /// that of `FromStr` for text that is not trusted.
+    ///
+    /// # Return
+    /// A bag holding the elements written in `text` in the same
+    /// order, or an Err with the column of the first problem found,
+    /// which for a bag of more than `limit` elements is `TooManyElements`.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// assert!( Bag::<String>::parse_within( "{a×3}", 3 ).is_ok() );
+    /// assert!( Bag::<String>::parse_within( "{a×3, b}", 3 ).is_err() );
+    /// ```
+
+    pub fn parse_within( text: &str, limit: usize ) -> Result<Self, ParseBagError>
+    {
+        let mut bag = Self::new();
+
+        for ( value, count ) in bagformat::parse_runs_within::<Type>( text, limit )?
+        {
+            for _ in 1..count
+            {
+                bag.insert( value.clone() );
+            }
+
+            bag.insert( value );
+        }
+
+        Ok( bag )
+    }
+}
+
+impl<Type: PartialEq + Clone + FromStr> FromStr for Bag<Type>
+    where Type::Err: Display
+{
+    type Err = ParseBagError;
+
+    /// Reads a bag from the text format of `bagformat`.
+    ///
+    /// # Parameter: `text`
+    /// The text of a bag, such as `{1, 2×3}`.
+    ///
+    /// # Return
+    /// A bag holding the elements written in `text` in the same
+    /// order, or an Err with the column of the first problem found,
+    /// which for a bag of more than `bagformat::max_elements::<Type>()`
+    /// elements is `TooManyElements`.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: Bag<i32> = "{1, 2×3}".parse().unwrap();
+    ///
+    /// assert_eq!( bag.occurrences( 2 ), 3 );
+    /// ```
+
+    fn from_str( text: &str ) -> Result<Self, ParseBagError>
+    {
+        Self::parse_within( text, bagformat::max_elements::<Type>() )
+    }
+}
+
 impl<Type: PartialEq + Clone + Hash> Hash for Bag<Type>
 {
     /// Create a hash value for the bag.
@@ -889,12 +1302,15 @@ impl<Type: PartialEq + Clone + Hash> Hash for Bag<Type>
     }
 }
 
-/// An iterator that references a Bag structure
+/// An iterator that references a Bag structure.
+///
+/// It can be iterated from either end and knows exactly how many
+/// elements remain, which are available as a slice with `as_slice()`.
 
+#[ derive( Clone ) ]
 pub struct BagIterator<'a, Type: 'a + PartialEq + Clone>
 {
-    bag: &'a Bag<Type>,
-    index: Option<usize>
+    remaining: &'a [Type]
 }
 
 impl<'a, Type: PartialEq + Clone> IntoIterator for &'a Bag<Type>
@@ -915,7 +1331,7 @@ impl<'a, Type: PartialEq + Clone> IntoIterator for &'a Bag<Type>
 
     fn into_iter( self ) -> Self::IntoIter
     {
-        BagIterator { bag: self, index: None }
+        BagIterator::new( self )
     }
 }
 
@@ -934,7 +1350,33 @@ impl<'a, Type: PartialEq + Clone> BagIterator<'a, Type>
 
     pub fn new( source: &'a Bag<Type> ) -> BagIterator<'a, Type>
     {
-        BagIterator { bag: source, index: None }
+        BagIterator { remaining: &source.data[ .. ] }
+    }
+
+    /// The elements not yet returned by the iterator.
+    ///
+    /// # Postcondition
+    /// The bag and the iterator are not altered by this method.
+    ///
+    /// # Return
+    /// A slice of the remaining elements, in the order `next()` would return them.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// let mut iter = bag.into_iter();
+    /// iter.next();
+    ///
+    /// assert_eq!( iter.as_slice(), &[ 2, 3 ] );
+    /// ```
+
+    pub fn as_slice( &self ) -> &'a [Type]
+    {
+        self.remaining
     }
 }
 
@@ -974,15 +1416,10 @@ impl<'a, Type: PartialEq + Clone> Iterator for BagIterator<'a, Type>
 
     fn next( &mut self ) -> Option<Self::Item>
     {
-        let next_index = 
-            match self.index
-            {
-                Some( i ) => i + 1,
-                None => 0
-            };
+        let ( first, rest ) = self.remaining.split_first()?;
 
-        self.index = Some( next_index );
-        self.bag.data.get( next_index )
+        self.remaining = rest;
+        Some( first )
     }
 
     /// Consumes the iterator, counting the number of iterations remaining.
@@ -1008,20 +1445,7 @@ impl<'a, Type: PartialEq + Clone> Iterator for BagIterator<'a, Type>
 
     fn count( self ) -> usize
     {
-        match self.index
-        {
-            Some( i ) => {
-                            if self.bag.data.len() - i > 0
-                            {
-                                self.bag.data.len() - i
-                            }
-                            else
-                            {
-                                0
-                            }
-                        },
-            None => self.bag.data.len()
-        }
+        self.remaining.len()
     }
 
     /// Consumes the iterator, returning the last element.
@@ -1030,7 +1454,7 @@ impl<'a, Type: PartialEq + Clone> Iterator for BagIterator<'a, Type>
     /// The bag is not altered by this method and the iterator is consumed.
     ///
     /// # Return
-    /// The last element in the iterator or None if the lenght is zero.
+    /// The last element in the iterator or None if no elements remain.
     ///
     /// # Examples 
     /// ```
@@ -1047,7 +1471,7 @@ impl<'a, Type: PartialEq + Clone> Iterator for BagIterator<'a, Type>
 
     fn last( self ) -> Option<Self::Item>
     {
-        self.bag.data.last()
+        self.remaining.last()
     }
 
     /// Returns the nth element of the iterator.
@@ -1081,14 +1505,87 @@ impl<'a, Type: PartialEq + Clone> Iterator for BagIterator<'a, Type>
 
     fn nth( &mut self, n: usize ) -> Option<Self::Item>
     {
-        let next_index = 
-            match self.index
-            {
-                Some( i ) => i + n + 1,
-                None => n
-            };
+        // an offset past the end consumes the iterator
+        let skipped = n.min( self.remaining.len() );
+
+        self.remaining = &self.remaining[ skipped.. ];
+        self.next()
+    }
+
+    /// The bounds on the remaining length of the iterator.
+    ///
+    /// # Return
+    /// The exact number of elements remaining as both the lower and upper bound.
 
-        self.index = Some( next_index );
-        self.bag.data.get( next_index )
+    fn size_hint( &self ) -> ( usize, Option<usize> )
+    {
+        ( self.remaining.len(), Some( self.remaining.len() ) )
     }
 }
+
+impl <'a, Type: PartialEq + Clone> DoubleEndedIterator for BagIterator<'a, Type>
+{
+    /// Removes and returns an element from the end of the iterator.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method and the iterator's end will change.
+    ///
+    /// # Return
+    /// The last remaining value in the bag or None if the iteration is finished.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    /// bag.insert( 3 );
+    ///
+    /// let mut iter = bag.into_iter();
+    ///
+    /// assert_eq!( Some( &3 ), iter.next_back() );
+    /// assert_eq!( Some( &1 ), iter.next() );
+    /// assert_eq!( Some( &2 ), iter.next_back() );
+    /// assert_eq!( None, iter.next() );
+    /// ```
+
+    fn next_back( &mut self ) -> Option<Self::Item>
+    {
+        let ( last, rest ) = self.remaining.split_last()?;
+
+        self.remaining = rest;
+        Some( last )
+    }
+
+    /// Returns the nth element from the end of the iterator.
+    ///
+    /// # Parameter: `n`
+    /// The non-negative offset from the iterator's end to access.
+    ///
+    /// # Postcondition
+    /// The bag is not altered by this method and the iterator's end may change.
+    ///
+    /// # Return
+    /// The nth element from the end or None if n is beyond the length of the iterator.
+
+    fn nth_back( &mut self, n: usize ) -> Option<Self::Item>
+    {
+        let kept = self.remaining.len() - n.min( self.remaining.len() );
+
+        self.remaining = &self.remaining[ ..kept ];
+        self.next_back()
+    }
+}
+
+impl <'a, Type: PartialEq + Clone> ExactSizeIterator for BagIterator<'a, Type>
+{
+    /// The number of elements remaining in the iterator.
+
+    fn len( &self ) -> usize
+    {
+        self.remaining.len()
+    }
+}
+
+/// Once the iterator returns None it always will.
+
+impl <'a, Type: PartialEq + Clone> ::std::iter::FusedIterator for BagIterator<'a, Type> {}
@@ -0,0 +1,333 @@
+// FILE: bagbench.rs
+
+//! Benchmarks comparing the bag implementations.
+//!
+//! Run with `cargo run --release -- bench [OPTIONS]`
+//!
+//! Each operation of each bag is timed for every size of bag and
+//! ratio of duplicate values. The bags are `bag::Bag` and `bagVec::Bag`
+//! of `i64`, and `bag::Bag` of `f64`, which is `bagf64::Bag`, to show
+//! the cost of comparing floats; the element type is in the name of
+//! each bag in the results. The results are printed as a table
+//! and written as CSV, with the version of the crate, so that the
+//! runs of different versions can be compared.
+//!
+//! # Options
+//!
+//! * `--sizes <N,N,...>` The sizes of bag to time, 10 up to 10^6 by default
+//!
+//! * `--max-size <N>` Leave out the sizes above `N`
+//!
+//! * `--duplicates <R,R,...>` The fractions of the inserted values that
+//! are duplicates, `0,0.5,0.9` by default
+//!
+//! * `-o`, `--output <file>` Where to write the CSV, `bench_output.txt` by default
+
+use bag;
+use bagVec;
+use bagtrait::BagOps;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::Write;
+use std::ops::Add;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// The bags timed, named by implementation and element type. Since
+/// `bagf64::Bag` is `bag::Bag<f64>`, the third is the slot-array bag
+/// again with floats in place of integers, not another implementation.
+
+pub const BACKENDS: [&str; 3] = [ "bag<i64>", "bagVec<i64>", "bag<f64>" ];
+
+/// The names of the operations timed.
+
+pub const OPERATIONS: [&str; 8] = [ "insert", "erase", "erase_one", "occurrences",
+                                     "add", "add_assign", "clone", "iterate" ];
+
+/// The first row of the CSV results.
+
+pub const CSV_HEADER: &str = "version,bag,operation,size,duplicate_ratio,iterations,total_ns,ns_per_op";
+
+// Operations that take time linear in the size are repeated
+// until about this many elements have been visited.
+const WORK_PER_MEASUREMENT: usize = 10_000_000;
+
+/// The options of the `bench` command.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct BenchOptions
+{
+    pub sizes: Vec<usize>,
+    pub duplicate_ratios: Vec<f64>,
+    pub output: String
+}
+
+impl Default for BenchOptions
+{
+    /// Sizes from 10 to 10^6, duplicate ratios of 0, 0.5 and 0.9, written to `bench_output.txt`.
+
+    fn default() -> Self
+    {
+        BenchOptions { sizes: vec![ 10, 100, 1_000, 10_000, 100_000, 1_000_000 ],
+                       duplicate_ratios: vec![ 0.0, 0.5, 0.9 ],
+                       output: "bench_output.txt".to_string() }
+    }
+}
+
+impl BenchOptions
+{
+    /// Read the options of the `bench` command from its arguments.
+    ///
+    /// # Return
+    /// The options, or an Err describing the first invalid argument.
+
+    pub fn parse( args: &[String] ) -> Result<Self, String>
+    {
+        let mut options = BenchOptions::default();
+        let mut max_size = None;
+        let mut args = args.iter();
+
+        while let Some( arg ) = args.next()
+        {
+            let value = args.next().ok_or_else( || format!( "{} requires a value", arg ) );
+
+            match arg.as_str()
+            {
+                "--sizes" => { options.sizes = parse_list( value? )?; }
+                "--max-size" =>
+                    {
+                        let value = value?;
+                        max_size = Some( value.trim().parse::<usize>().map_err( | _ |
+                            format!( "`{}` is not a valid size", value ) )? );
+                    }
+                "--duplicates" => { options.duplicate_ratios = parse_list( value? )?; }
+                "-o" | "--output" => { options.output = value?.clone(); }
+                other => return Err( format!( "unknown option `{}` for bench", other ) )
+            }
+        }
+
+        // applied after every option so that --sizes may come after it
+        if let Some( max ) = max_size
+        {
+            options.sizes.retain( | &size | size <= max );
+        }
+
+        if options.sizes.contains( &0 )
+        {
+            return Err( "the sizes must be greater than 0".to_string() );
+        }
+
+        if options.duplicate_ratios.iter().any( | ratio | !( 0.0..1.0 ).contains( ratio ) )
+        {
+            return Err( "the duplicate ratios must be at least 0 and less than 1".to_string() );
+        }
+
+        Ok( options )
+    }
+}
+
+// Parses a comma separated list of values.
+fn parse_list<Value: FromStr>( list: &str ) -> Result<Vec<Value>, String>
+{
+    list.split( ',' )
+        .map( | item | item.trim().parse::<Value>().map_err( | _ | format!( "`{}` is not a valid value", item ) ) )
+        .collect()
+}
+
+/// The time taken by repeating one operation on one bag.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct Measurement
+{
+    pub bag: &'static str,
+    pub operation: &'static str,
+    pub size: usize,
+    pub duplicate_ratio: f64,
+    pub iterations: usize,
+    pub total_ns: u128
+}
+
+impl Measurement
+{
+    /// The average time of one repetition of the operation.
+
+    pub fn ns_per_op( &self ) -> f64
+    {
+        self.total_ns as f64 / self.iterations as f64
+    }
+
+    /// The measurement as a row of CSV, with the columns of `CSV_HEADER`.
+
+    pub fn to_csv( &self ) -> String
+    {
+        format!( "{},{},{},{},{},{},{},{:.1}", env!( "CARGO_PKG_VERSION" ), self.bag, self.operation,
+                 self.size, self.duplicate_ratio, self.iterations, self.total_ns, self.ns_per_op() )
+    }
+}
+
+/// Run the `bench` command.
+///
+/// # Return
+/// An Err with a message for the user if the arguments are
+/// invalid or the results could not be written.
+
+pub fn bench( args: &[String] ) -> Result<(), String>
+{
+    let options = BenchOptions::parse( args )?;
+    let write_error = | error: ::std::io::Error | format!( "could not write `{}`: {}", options.output, error );
+    let mut output = File::create( &options.output ).map_err( write_error )?;
+
+    writeln!( output, "{}", CSV_HEADER ).map_err( write_error )?;
+    println!( "{:<12} {:<12} {:>8} {:>10} {:>14}", "bag", "operation", "size", "duplicates", "ns/op" );
+
+    for &size in &options.sizes
+    {
+        for &ratio in &options.duplicate_ratios
+        {
+            for measurement in measure_all( size, ratio )
+            {
+                println!( "{:<12} {:<12} {:>8} {:>10} {:>14.1}", measurement.bag, measurement.operation,
+                          measurement.size, measurement.duplicate_ratio, measurement.ns_per_op() );
+                writeln!( output, "{}", measurement.to_csv() ).map_err( write_error )?;
+            }
+        }
+    }
+
+    println!( "Results written to {}", options.output );
+    Ok( () )
+}
+
+/// Time every operation of every bag, in the order of `BACKENDS`
+/// and `OPERATIONS`.
+///
+/// # Parameter: `ratio`
+/// The fraction of the `size` values inserted that are
+/// duplicates of an earlier value.
+
+pub fn measure_all( size: usize, ratio: f64 ) -> Vec<Measurement>
+{
+    let mut measurements = measure::<bag::Bag<i64>>( BACKENDS[ 0 ], size, ratio );
+
+    measurements.extend( measure::<bagVec::Bag<i64>>( BACKENDS[ 1 ], size, ratio ) );
+    measurements.extend( measure::<bag::Bag<f64>>( BACKENDS[ 2 ], size, ratio ) );
+    measurements
+}
+
+// Times each of OPERATIONS on one type of bag.
+fn measure<B: BagOps + Add<Output = B>>( name: &'static str, size: usize, ratio: f64 ) -> Vec<Measurement>
+    where B::Item: From<i32>
+{
+    let distinct = ( ( size as f64 * ( 1.0 - ratio ) ).round() as usize ).max( 1 );
+    let values: Vec<B::Item> = ( 0..size ).map( | i | B::Item::from( ( i % distinct ) as i32 ) ).collect();
+
+    // the slower operations are repeated fewer times on larger bags
+    let repeats = ( WORK_PER_MEASUREMENT / size ).clamp( 1, 1000 );
+    let searches = repeats.min( size );
+    let record = | operation, iterations, total_ns |
+        Measurement { bag: name, operation, size, duplicate_ratio: ratio, iterations, total_ns };
+
+    let mut measurements = Vec::new();
+    let mut full = B::default();
+
+    let start = Instant::now();
+    for value in &values
+    {
+        full.insert( value.clone() );
+    }
+    measurements.push( record( OPERATIONS[ 0 ], size, start.elapsed().as_nanos() ) );
+
+    let mut bag = full.clone();
+    let start = Instant::now();
+    for value in &values[ ..searches ]
+    {
+        black_box( bag.erase( value.clone() ) );
+    }
+    measurements.push( record( OPERATIONS[ 1 ], searches, start.elapsed().as_nanos() ) );
+
+    let mut bag = full.clone();
+    let start = Instant::now();
+    for value in &values[ ..searches ]
+    {
+        black_box( bag.erase_one( value.clone() ) );
+    }
+    measurements.push( record( OPERATIONS[ 2 ], searches, start.elapsed().as_nanos() ) );
+
+    let start = Instant::now();
+    for value in &values[ ..searches ]
+    {
+        black_box( full.occurrences( value.clone() ) );
+    }
+    measurements.push( record( OPERATIONS[ 3 ], searches, start.elapsed().as_nanos() ) );
+
+    // the copies added are made outside of the timing
+    let mut total_ns = 0;
+    for _ in 0..repeats
+    {
+        let ( left, right ) = ( full.clone(), full.clone() );
+        let start = Instant::now();
+        black_box( left + right );
+        total_ns += start.elapsed().as_nanos();
+    }
+    measurements.push( record( OPERATIONS[ 4 ], repeats, total_ns ) );
+
+    let mut total_ns = 0;
+    for _ in 0..repeats
+    {
+        let ( mut left, right ) = ( full.clone(), full.clone() );
+        let start = Instant::now();
+        left += right;
+        total_ns += start.elapsed().as_nanos();
+        black_box( left );
+    }
+    measurements.push( record( OPERATIONS[ 5 ], repeats, total_ns ) );
+
+    let start = Instant::now();
+    for _ in 0..repeats
+    {
+        black_box( full.clone() );
+    }
+    measurements.push( record( OPERATIONS[ 6 ], repeats, start.elapsed().as_nanos() ) );
+
+    let start = Instant::now();
+    for _ in 0..repeats
+    {
+        for element in full.iter()
+        {
+            black_box( element );
+        }
+    }
+    measurements.push( record( OPERATIONS[ 7 ], repeats, start.elapsed().as_nanos() ) );
+
+    measurements
+}
+
+#[ test ]
+///   Performs a test of the options and of timing every operation on small bags.
+fn test_measure()
+{
+    let args = | line: &str | line.split( ' ' ).map( | arg | arg.to_string() ).collect::<Vec<String>>();
+
+    let options = BenchOptions::parse( &args( "--max-size 1000 --duplicates 0,0.25 -o out.csv" ) ).unwrap();
+    assert_eq!( options.sizes, vec![ 10, 100, 1000 ] );
+    assert_eq!( options.duplicate_ratios, vec![ 0.0, 0.25 ] );
+    assert_eq!( options.output, "out.csv" );
+
+    let options = BenchOptions::parse( &args( "--max-size 50 --sizes 5,40,60" ) ).unwrap();
+    assert_eq!( options.sizes, vec![ 5, 40 ] );
+    assert!( BenchOptions::parse( &args( "--max-size 10,20" ) ).is_err() );
+
+    assert!( BenchOptions::parse( &args( "--sizes 10,0" ) ).is_err() );
+    assert!( BenchOptions::parse( &args( "--duplicates 1" ) ).is_err() );
+    assert!( BenchOptions::parse( &args( "--sizes" ) ).is_err() );
+
+    let measurements = measure_all( 50, 0.5 );
+    assert_eq!( measurements.len(), BACKENDS.len() * OPERATIONS.len() );
+
+    for ( index, measurement ) in measurements.iter().enumerate()
+    {
+        assert_eq!( measurement.bag, BACKENDS[ index / OPERATIONS.len() ] );
+        assert_eq!( measurement.operation, OPERATIONS[ index % OPERATIONS.len() ] );
+        assert!( measurement.iterations > 0 );
+        assert_eq!( measurement.to_csv().split( ',' ).count(), CSV_HEADER.split( ',' ).count() );
+    }
+}
@@ -0,0 +1,443 @@
+// FILE: bagbounded.rs
+
+//! A Bag that never holds more than a fixed number of elements, its
+//! bound. Inserting into a full bag follows its `EvictionPolicy`:
+//! the new element is rejected, or an element already in the bag is
+//! evicted to make room for it. Either way the element that is not
+//! kept is returned to the caller.
+//!
+//! The bound is the bag's capacity, as given by `Capacity`, and can
+//! be raised with `ensure_capacity( _ )` or `reserve( _ )` and
+//! lowered to the size of the bag with `shrink_to_fit()`, as for
+//! `bag::Bag`.
+//!
+//! The type used must have the traits `PartialEq` and `Clone`.
+//!
+//! # Note
+//! Because of the slow linear algorithms of this
+//! class, large bags will have poor performance.
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::len_trait::len::{ Len, Empty, Clear };
+use bag::len_trait::capacity::{ Capacity, WithCapacity, CapacityMut };
+use bagformat;
+use std::collections::VecDeque;
+use std::collections::vec_deque;
+use std::fmt::{ self, Debug, Display, Formatter };
+
+/// What a full bag does with an insert.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum EvictionPolicy
+{
+    /// Keep the bag as it is and reject the new element.
+    Reject,
+    /// Evict the element that has been in the bag the longest.
+    EvictOldest,
+    /// Evict an element chosen at random.
+    EvictRandom,
+    /// Evict the oldest copy of the value with the fewest copies,
+    /// choosing the value whose oldest copy is oldest among ties.
+    /// Since the values are only compared with `PartialEq`, each such
+    /// eviction takes time proportional to the bound times the number
+    /// of distinct values.
+    EvictLeastFrequent
+}
+
+/// The result of inserting into a bounded bag.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum Insertion<Type>
+{
+    /// The element was inserted without evicting anything.
+    Inserted,
+    /// The element was inserted and the given element was evicted.
+    Evicted( Type ),
+    /// The bag was full and the given element, the new one, was not inserted.
+    Rejected( Type )
+}
+
+/// A container for inserting and removing given values, up to a bound.
+///
+/// # Invariant of the BoundedBag struct:
+/// 1. The elements in the bag are stored in `data` from the
+/// oldest to the newest.
+///
+/// 2. `data.len()` is no more than `bound`, which is greater than 0.
+
+#[ derive( Clone ) ]
+pub struct BoundedBag<Type: PartialEq + Clone>
+{
+    data: VecDeque<Type>,
+    bound: usize,
+    policy: EvictionPolicy,
+    rng: XorShiftRng
+}
+
+impl<Type: PartialEq + Clone> BoundedBag<Type>
+{
+    /// Initialize an empty bag that holds at most `bound` elements.
+    ///
+    /// # Parameter: `bound`
+    /// An unsigned integer greater than 0.
+    ///
+    /// # Parameter: `policy`
+    /// What an insert does when the bag is full.
+    ///
+    /// # Panics
+    /// `bound` given is not greater than 0.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: BoundedBag<i32> = BoundedBag::new( 3, EvictionPolicy::EvictOldest );
+    ///
+    /// assert_eq!( bag.capacity(), 3 );
+    /// ```
+
+    pub fn new( bound: usize, policy: EvictionPolicy ) -> Self
+    {
+        let seed = [ rand::random::<u32>() | 1, rand::random(), rand::random(), rand::random() ];
+
+        BoundedBag::with_seed( bound, policy, seed )
+    }
+
+    /// Initialize an empty bag, seeding the choices of
+    /// `EvictionPolicy::EvictRandom` so that they can be repeated.
+    ///
+    /// # Parameter: `seed`
+    /// The seed of the random choices, which must not be all zeros.
+    ///
+    /// # Panics
+    /// `bound` given is not greater than 0, or `seed` is all zeros.
+
+    pub fn with_seed( bound: usize, policy: EvictionPolicy, seed: [u32; 4] ) -> Self
+    {
+        if bound == 0
+        {
+            panic!( "bound must be > 0" );
+        }
+
+        BoundedBag { data: VecDeque::with_capacity( bound.min( 1024 ) ),
+                     bound,
+                     policy,
+                     rng: XorShiftRng::from_seed( seed ) }
+    }
+
+    /// The policy followed when inserting into a full bag.
+
+    pub fn policy( &self ) -> EvictionPolicy
+    {
+        self.policy
+    }
+
+    /// Change the policy followed when inserting into a full bag.
+    ///
+    /// # Postcondition
+    /// The elements of the bag are not altered.
+
+    pub fn set_policy( &mut self, policy: EvictionPolicy )
+    {
+        self.policy = policy;
+    }
+
+    /// Whether the bag holds as many elements as its bound.
+
+    pub fn is_full( &self ) -> bool
+    {
+        self.data.len() >= self.bound
+    }
+
+    /// Potentially raise the bound of this bag.
+    ///
+    /// # Parameter: `new_bound`
+    /// An unsigned integer greater than 0.
+    ///
+    /// # Postcondition
+    /// The bag's bound is at least `new_bound`. If the bound was
+    /// already at or greater than `new_bound`, then it is left unchanged.
+    ///
+    /// # Note
+    /// Like `bag::Bag::ensure_capacity( _ )` this takes a total
+    /// new bound rather than an additional one.
+    ///
+    /// # Panics
+    /// `new_bound` given is not greater than 0.
+
+    pub fn ensure_capacity( &mut self, new_bound: usize )
+    {
+        if new_bound == 0
+        {
+            panic!( "new_bound < 1" );
+        }
+
+        self.bound = self.bound.max( new_bound );
+    }
+
+    /// Add a new element to this bag, following the eviction
+    /// policy if the bag is full.
+    ///
+    /// # Parameter: `new_item`
+    /// The new element that is to be added.
+    ///
+    /// # Postcondition
+    /// Unless it was rejected, `new_item` is the newest element of the
+    /// bag. The bag holds no more elements than its bound.
+    ///
+    /// # Return
+    /// Whether the element was inserted, and the element that was
+    /// evicted or rejected if the bag was full.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = BoundedBag::new( 2, EvictionPolicy::EvictOldest );
+    ///
+    /// assert_eq!( bag.insert( 1 ), Insertion::Inserted );
+    /// assert_eq!( bag.insert( 2 ), Insertion::Inserted );
+    /// assert_eq!( bag.insert( 3 ), Insertion::Evicted( 1 ) );
+    /// ```
+
+    pub fn insert( &mut self, new_item: Type ) -> Insertion<Type>
+    {
+        if !self.is_full()
+        {
+            self.data.push_back( new_item );
+            return Insertion::Inserted;
+        }
+
+        let index = match self.policy
+        {
+            EvictionPolicy::Reject => return Insertion::Rejected( new_item ),
+            EvictionPolicy::EvictOldest => 0,
+            EvictionPolicy::EvictRandom => self.rng.gen_range( 0, self.data.len() ),
+            EvictionPolicy::EvictLeastFrequent => self.least_frequent()
+        };
+
+        let evicted = self.data.remove( index ).unwrap();
+        self.data.push_back( new_item );
+
+        Insertion::Evicted( evicted )
+    }
+
+    // The index of the oldest copy of the value with the fewest copies.
+    // Each element is compared with the distinct values found before
+    // it, as there is no hash to count by, taking O(n * distinct) time.
+    fn least_frequent( &self ) -> usize
+    {
+        // the index of the oldest copy of each value and its copies
+        let mut counts: Vec<( usize, usize )> = Vec::new();
+
+        for ( index, value ) in self.data.iter().enumerate()
+        {
+            match counts.iter_mut().find( | &&mut ( oldest, _ ) | self.data[ oldest ] == *value )
+            {
+                Some( &mut ( _, ref mut copies ) ) => *copies += 1,
+                None => counts.push( ( index, 1 ) )
+            }
+        }
+
+        // the first of the values with the fewest copies has the oldest copy
+        let mut least = ( usize::MAX, 0 );
+
+        for &( oldest, copies ) in &counts
+        {
+            if copies < least.0
+            {
+                least = ( copies, oldest );
+            }
+        }
+
+        least.1
+    }
+
+    /// Erase all copies of `target` from this bag.
+    ///
+    /// # Postcondition
+    /// The other elements keep their order from oldest to newest.
+    ///
+    /// # Return
+    /// The number of copies erased.
+
+    pub fn erase( &mut self, target: Type ) -> usize
+    {
+        let before = self.data.len();
+
+        self.data.retain( | value | *value != target );
+        before - self.data.len()
+    }
+
+    /// Remove the oldest copy of `target` from this bag.
+    ///
+    /// # Postcondition
+    /// The other elements keep their order from oldest to newest.
+    ///
+    /// # Return
+    /// True if a copy of `target` was found and removed.
+
+    pub fn erase_one( &mut self, target: Type ) -> bool
+    {
+        match self.data.iter().position( | value | *value == target )
+        {
+            Some( index ) =>
+                {
+                    self.data.remove( index );
+                    true
+                }
+            None => false
+        }
+    }
+
+    /// Count the number of occurrences of `target` in this bag.
+
+    pub fn occurrences( &self, target: Type ) -> usize
+    {
+        self.data.iter().filter( | &value | *value == target ).count()
+    }
+
+    /// Iterate over the elements from the oldest to the newest.
+
+    pub fn iter( &self ) -> vec_deque::Iter<'_, Type>
+    {
+        self.data.iter()
+    }
+}
+
+impl<Type: PartialEq + Clone> Len for BoundedBag<Type>
+{
+    /// The number of elements in the bag.
+
+    fn len( &self ) -> usize
+    {
+        self.data.len()
+    }
+}
+
+impl<Type: PartialEq + Clone> Empty for BoundedBag<Type>
+{
+    /// Whether the bag holds no elements.
+
+    fn is_empty( &self ) -> bool
+    {
+        self.data.is_empty()
+    }
+}
+
+impl<Type: PartialEq + Clone> Clear for BoundedBag<Type>
+{
+    /// Remove every element, keeping the bound and policy.
+
+    fn clear( &mut self )
+    {
+        self.data.clear();
+    }
+}
+
+impl<Type: PartialEq + Clone> Capacity for BoundedBag<Type>
+{
+    /// The bound of the bag, the most elements it can hold.
+
+    fn capacity( &self ) -> usize
+    {
+        self.bound
+    }
+}
+
+impl<Type: PartialEq + Clone> WithCapacity for BoundedBag<Type>
+{
+    /// Initialize an empty bag that holds at most `bound` elements
+    /// and rejects inserts when it is full.
+    ///
+    /// # Panics
+    /// `bound` given is not greater than 0.
+
+    fn with_capacity( bound: usize ) -> Self
+    {
+        BoundedBag::new( bound, EvictionPolicy::Reject )
+    }
+}
+
+impl<Type: PartialEq + Clone> CapacityMut for BoundedBag<Type>
+{
+    /// Potentially raise the bound of this bag.
+    ///
+    /// Implementation matches ensure_capacity( new_bound ), taking
+    /// a total new bound rather than an additional one.
+    ///
+    /// # Panics
+    /// `new_bound` given is not greater than 0.
+
+    fn reserve( &mut self, new_bound: usize )
+    {
+        self.ensure_capacity( new_bound );
+    }
+
+    /// Lower the bound of this bag to the number of elements it
+    /// holds, but to no less than one.
+    ///
+    /// # Postcondition
+    /// The elements of the bag are not altered, and the bag is full
+    /// unless it is empty.
+
+    fn shrink_to_fit( &mut self )
+    {
+        self.bound = self.data.len().max( 1 );
+        self.data.shrink_to_fit();
+    }
+}
+
+impl<Type: PartialEq + Clone> Default for BoundedBag<Type>
+{
+    /// Initialize an empty bag with a bound of one that rejects
+    /// inserts when it is full.
+
+    fn default() -> Self
+    {
+        BoundedBag::new( 1, EvictionPolicy::Reject )
+    }
+}
+
+impl<Type: PartialEq + Clone> PartialEq for BoundedBag<Type>
+{
+    /// Whether two bags have the same elements in the same order,
+    /// the same bound and the same policy.
+
+    fn eq( &self, other: &BoundedBag<Type> ) -> bool
+    {
+        self.data == other.data && self.bound == other.bound && self.policy == other.policy
+    }
+}
+
+impl<Type: PartialEq + Clone + Debug> Debug for BoundedBag<Type>
+{
+    /// Renders the bag's contents, bound and policy into a human readable form.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "BoundedBag with {} of {} elements ({:?}): {:?}",
+                self.data.len(), self.bound, self.policy, self.data )
+    }
+}
+
+impl<Type: PartialEq + Clone + Display> Display for BoundedBag<Type>
+{
+    /// Renders the bag's contents in the text format of `bagformat`.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        bagformat::write_bag( fmt, self.data.iter() )
+    }
+}
+
+impl<'a, Type: PartialEq + Clone> IntoIterator for &'a BoundedBag<Type>
+{
+    type Item = &'a Type;
+    type IntoIter = vec_deque::Iter<'a, Type>;
+
+    /// Iterate over the elements from the oldest to the newest.
+
+    fn into_iter( self ) -> Self::IntoIter
+    {
+        self.data.iter()
+    }
+}
@@ -0,0 +1,180 @@
+// FILE: bagboundedexam.rs
+
+//! This module holds tests for the bounded bag of bagbounded
+//!
+//! 1) tests of inserting up to the bound and the reject policy
+//!
+//! 2) tests of the evict oldest and evict random policies
+//!
+//! 3) tests of the evict least frequent policy
+//!
+//! 4) tests of erasing, raising the bound and the other member functions
+//!
+//! Run with `cargo test`
+
+use bagbounded::{ BoundedBag, EvictionPolicy, Insertion };
+use bag::len_trait::len::{ Len, Empty, Clear };
+use bag::len_trait::capacity::{ Capacity, WithCapacity, CapacityMut };
+
+fn contents( bag: &BoundedBag<i32> ) -> Vec<i32>
+{
+    bag.iter().cloned().collect()
+}
+
+#[ test ]
+///   Performs tests of inserting up to the bound and the reject policy.
+fn test1()
+{
+    let mut test_bag = BoundedBag::new( 3, EvictionPolicy::Reject );
+
+    // A. Inserts below the bound.
+    assert!( test_bag.is_empty() );
+    assert_eq!( test_bag.insert( 1 ), Insertion::Inserted );
+    assert_eq!( test_bag.insert( 2 ), Insertion::Inserted );
+    assert!( !test_bag.is_full() );
+    assert_eq!( test_bag.insert( 2 ), Insertion::Inserted );
+    assert!( test_bag.is_full() );
+
+    // B. A full bag rejects the new element.
+    assert_eq!( test_bag.insert( 4 ), Insertion::Rejected( 4 ) );
+    assert_eq!( contents( &test_bag ), vec![ 1, 2, 2 ] );
+    assert_eq!( test_bag.len(), 3 );
+    assert_eq!( test_bag.capacity(), 3 );
+}
+
+#[ test ]
+///   Performs tests of the evict oldest and evict random policies.
+fn test2()
+{
+    // A. The oldest element is evicted.
+    let mut test_bag = BoundedBag::new( 3, EvictionPolicy::EvictOldest );
+
+    for value in 1..4
+    {
+        assert_eq!( test_bag.insert( value ), Insertion::Inserted );
+    }
+
+    assert_eq!( test_bag.insert( 4 ), Insertion::Evicted( 1 ) );
+    assert_eq!( test_bag.insert( 5 ), Insertion::Evicted( 2 ) );
+    assert_eq!( contents( &test_bag ), vec![ 3, 4, 5 ] );
+
+    // B. A random element is evicted and the new one is kept.
+    let mut test_bag = BoundedBag::with_seed( 4, EvictionPolicy::EvictRandom, [ 1, 2, 3, 4 ] );
+    let mut evicted = Vec::new();
+
+    for value in 0..100
+    {
+        if let Insertion::Evicted( old ) = test_bag.insert( value )
+        {
+            assert!( old < value );
+            evicted.push( old );
+        }
+
+        assert_eq!( test_bag.iter().last(), Some( &value ) );
+        assert!( test_bag.len() <= 4 );
+    }
+
+    assert_eq!( evicted.len(), 96 );
+
+    // C. The same seed evicts the same elements.
+    let mut repeated = BoundedBag::with_seed( 4, EvictionPolicy::EvictRandom, [ 1, 2, 3, 4 ] );
+    let repeated_evicted: Vec<i32> = ( 0..100 ).filter_map( | value | match repeated.insert( value )
+                                                                {
+                                                                    Insertion::Evicted( old ) => Some( old ),
+                                                                    _ => None
+                                                                } ).collect();
+    assert_eq!( repeated_evicted, evicted );
+}
+
+#[ test ]
+///   Performs tests of the evict least frequent policy.
+fn test3()
+{
+    let mut test_bag = BoundedBag::new( 5, EvictionPolicy::EvictLeastFrequent );
+
+    for &value in &[ 7, 3, 7, 9, 3 ]
+    {
+        test_bag.insert( value );
+    }
+
+    // A. 9 has the fewest copies.
+    assert_eq!( test_bag.insert( 1 ), Insertion::Evicted( 9 ) );
+    assert_eq!( contents( &test_bag ), vec![ 7, 3, 7, 3, 1 ] );
+
+    // B. 1 has the fewest copies even though it is the newest.
+    assert_eq!( test_bag.insert( 3 ), Insertion::Evicted( 1 ) );
+
+    // C. Otherwise the fewest copies need not be of the oldest element.
+    assert_eq!( contents( &test_bag ), vec![ 7, 3, 7, 3, 3 ] );
+    assert_eq!( test_bag.insert( 3 ), Insertion::Evicted( 7 ) );
+    assert_eq!( contents( &test_bag ), vec![ 3, 7, 3, 3, 3 ] );
+
+    // D. Ties go to the value whose oldest copy is oldest, evicting that copy.
+    let mut test_bag = BoundedBag::new( 4, EvictionPolicy::EvictLeastFrequent );
+
+    for &value in &[ 2, 1, 1, 2 ]
+    {
+        test_bag.insert( value );
+    }
+
+    assert_eq!( test_bag.insert( 4 ), Insertion::Evicted( 2 ) );
+    assert_eq!( contents( &test_bag ), vec![ 1, 1, 2, 4 ] );
+}
+
+#[ test ]
+///   Performs tests of erasing, raising the bound and the other member functions.
+fn test4()
+{
+    let mut test_bag = BoundedBag::new( 4, EvictionPolicy::Reject );
+
+    for &value in &[ 5, 6, 5, 6 ]
+    {
+        test_bag.insert( value );
+    }
+
+    // A. Erasing keeps the order and makes room.
+    assert!( test_bag.erase_one( 5 ) );
+    assert!( !test_bag.erase_one( 8 ) );
+    assert_eq!( contents( &test_bag ), vec![ 6, 5, 6 ] );
+    assert_eq!( test_bag.erase( 6 ), 2 );
+    assert_eq!( test_bag.occurrences( 5 ), 1 );
+    assert_eq!( test_bag.insert( 8 ), Insertion::Inserted );
+
+    // B. Raising the bound, never lowering it, and shrinking it to the size.
+    test_bag.ensure_capacity( 6 );
+    test_bag.ensure_capacity( 2 );
+    assert_eq!( test_bag.capacity(), 6 );
+
+    let mut shrunk = test_bag.clone();
+    shrunk.shrink_to_fit();
+    assert_eq!( shrunk.capacity(), 2 );
+    assert!( shrunk.is_full() );
+    assert_eq!( shrunk.insert( 9 ), Insertion::Rejected( 9 ) );
+    shrunk.reserve( 3 );
+    assert_eq!( shrunk.insert( 9 ), Insertion::Inserted );
+    shrunk.clear();
+    shrunk.shrink_to_fit();
+    assert_eq!( shrunk.capacity(), 1 );
+    assert_eq!( shrunk, BoundedBag::default() );
+    assert_eq!( BoundedBag::<i32>::with_capacity( 5 ).policy(), EvictionPolicy::Reject );
+
+    // C. Copies, comparison, policies and text.
+    let copy = test_bag.clone();
+    assert_eq!( copy, test_bag );
+    test_bag.set_policy( EvictionPolicy::EvictOldest );
+    assert_eq!( test_bag.policy(), EvictionPolicy::EvictOldest );
+    assert!( copy != test_bag );
+    assert_eq!( test_bag.to_string(), "{5, 8}" );
+
+    test_bag.clear();
+    assert!( test_bag.is_empty() );
+    assert_eq!( test_bag.capacity(), 6 );
+}
+
+#[ test ]
+#[ should_panic ]
+///   Performs a test of a bound of 0.
+fn test5()
+{
+    let _ = BoundedBag::<i32>::new( 0, EvictionPolicy::Reject );
+}
@@ -0,0 +1,237 @@
+// FILE: bagcli.rs
+
+//! The command line interface of the bag program.
+//!
+//! Run with `cargo run -- <command>`, or `cargo run -- --help`
+//! for the list of commands.
+//!
+//! * `interactive` Run the interactive test program (the default)
+//!
+//! * `run <script>` Run the interactive test program's commands from a file
+//!
+//! * `stats <file>` Load the numbers in a file into a `bagf64::Bag` and print statistics
+//!
+//! * `count [OPTIONS] [FILE...]` Count the tokens in files or standard input
+//! with the generic bag, see `bagcount`
+//!
+//! * `bench [OPTIONS]` Time the operations of the bag implementations
+//! and write the results as CSV, see `bagbench`
+//!
+//! * `fuzz <target> [FILE...]` Run a fuzz target on files or random inputs, see `bagfuzz`
+
+use bagf64;
+use bag::len_trait::Len;
+use std::fs::File;
+use std::io::{ BufReader, Read };
+
+// The options of the commands that take a value, which is never
+// itself an option.
+const VALUE_OPTIONS: &[&str] = &[ "-d", "--delimiter", "-n", "--top", "--format",
+                                  "--sizes", "--max-size", "--duplicates", "-o", "--output",
+                                  "--iterations" ];
+
+const USAGE: &str = "\
+Usage: bag2 [COMMAND]
+
+Commands:
+    interactive     Run the interactive test program (the default)
+    run <script>    Run the interactive test program's commands from a file
+    stats <file>    Load the numbers in a file into a bagf64::Bag and print statistics
+    count [OPTIONS] [FILE...]
+                    Count the tokens in files, or standard input, with the generic bag
+    bench [OPTIONS] Time the operations of the bag implementations,
+                    writing the results as CSV
+    fuzz <target> [FILE...]
+                    Run a fuzz target (operations or parse) on each file,
+                    or on random inputs with [--iterations N]
+    help            Print this message
+
+Options:
+    -h, --help      Print this message, unless it is the value of an option
+
+Count options:
+    --words                 Count whitespace separated words (the default)
+    --lines                 Count whole lines
+    -d, --delimiter <text>  Count the tokens separated by <text>
+    -i, --ignore-case       Fold tokens to lower case before counting
+    -n, --top <N>           Print only the N most frequent tokens
+    --format <format>       Print as text, csv or json (default text)
+    --                      Count the arguments after it as files, even those starting with -
+
+Bench options:
+    --sizes <N,N,...>       The sizes of bag to time (default 10 up to 1000000)
+    --max-size <N>          Leave out the sizes above N
+    --duplicates <R,R,...>  The fractions of duplicate values (default 0,0.5,0.9)
+    -o, --output <file>     Where to write the CSV (default bench_output.txt)";
+
+/// Run the command given by the arguments.
+///
+/// # Parameter: `args`
+/// The command line arguments, not including the program name.
+///
+/// # Return
+/// An Err with a message for the user if the arguments
+/// are invalid or the command failed.
+
+pub fn run( args: &[String] ) -> Result<(), String>
+{
+    let command = match args.first()
+    {
+        Some( command ) => command.as_str(),
+        None => "interactive"
+    };
+
+    let operands = if args.is_empty() { args } else { &args[ 1.. ] };
+
+    if wants_help( operands )
+    {
+        println!( "{}", USAGE );
+        return Ok( () );
+    }
+
+    match command
+    {
+        "interactive" =>
+            {
+                expect_operands( command, operands, 0 )?;
+                ::bagtest::bagtest();
+                Ok( () )
+            },
+        "run" =>
+            {
+                expect_operands( command, operands, 1 )?;
+                let file = open( &operands[ 0 ] )?;
+                ::bagtest::bagscript( &mut BufReader::new( file ) )
+            },
+        "stats" =>
+            {
+                expect_operands( command, operands, 1 )?;
+                stats( &read_to_string( &operands[ 0 ] )? )
+            },
+        "count" => ::bagcount::count( operands ),
+        "bench" => ::bagbench::bench( operands ),
+        "fuzz" => ::bagfuzz::run( operands ),
+        "help" | "-h" | "--help" =>
+            {
+                println!( "{}", USAGE );
+                Ok( () )
+            },
+        other => Err( format!( "unknown command `{}`, see `bag2 --help`", other ) )
+    }
+}
+
+// Whether `-h` or `--help` is among `operands` in the place of an
+// option, rather than as the value of one or after `--`.
+fn wants_help( operands: &[String] ) -> bool
+{
+    let mut operands = operands.iter();
+
+    while let Some( arg ) = operands.next()
+    {
+        match arg.as_str()
+        {
+            "-h" | "--help" => return true,
+            "--" => return false,
+            option if VALUE_OPTIONS.contains( &option ) => { operands.next(); }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+// Checks that the command was given exactly `expected` operands.
+fn expect_operands( command: &str, operands: &[String], expected: usize ) -> Result<(), String>
+{
+    if operands.len() == expected
+    {
+        Ok( () )
+    }
+    else
+    {
+        Err( format!( "`{}` takes {} argument(s) but {} were given, see `bag2 --help`",
+                      command, expected, operands.len() ) )
+    }
+}
+
+fn open( path: &str ) -> Result<File, String>
+{
+    File::open( path ).map_err( | error | format!( "could not open `{}`: {}", path, error ) )
+}
+
+fn read_to_string( path: &str ) -> Result<String, String>
+{
+    let mut text = String::new();
+
+    open( path )?.read_to_string( &mut text )
+                 .map_err( | error | format!( "could not read `{}`: {}", path, error ) )?;
+
+    Ok( text )
+}
+
+/// Load the whitespace separated numbers of `text` into a bag.
+///
+/// # Return
+/// The bag, or an Err naming the line of the first value that is not a number.
+
+pub fn load_numbers( text: &str ) -> Result<bagf64::Bag, String>
+{
+    let mut numbers = bagf64::Bag::new();
+
+    for ( line_number, line ) in text.lines().enumerate()
+    {
+        for word in line.split_whitespace()
+        {
+            numbers.insert( word.parse::<f64>()
+                                .map_err( | _ | format!( "line {}: `{}` is not a number",
+                                                         line_number + 1, word ) )? );
+        }
+    }
+
+    Ok( numbers )
+}
+
+// Prints the statistics of the numbers in `text`.
+fn stats( text: &str ) -> Result<(), String>
+{
+    let numbers = load_numbers( text )?;
+
+    println!( "count     {}", numbers.len() );
+
+    if numbers.len() == 0
+    {
+        return Ok( () );
+    }
+
+    let sum = numbers.sum();
+    let mean = numbers.mean().unwrap();
+    let min = numbers.min().unwrap_or( f64::NAN );
+    let max = numbers.max().unwrap_or( f64::NAN );
+    let variance = numbers.variance().unwrap();
+
+    println!( "sum       {}", sum );
+    println!( "mean      {}", mean );
+    println!( "min       {}", min );
+    println!( "max       {}", max );
+    println!( "variance  {}", variance );
+    println!( "std dev   {}", variance.sqrt() );
+
+    Ok( () )
+}
+
+#[ test ]
+///   Performs a test of telling a request for help from the value of an option.
+fn test_wants_help()
+{
+    let args = | line: &str | line.split( ' ' ).map( | arg | arg.to_string() ).collect::<Vec<String>>();
+
+    assert!( wants_help( &args( "-h" ) ) );
+    assert!( wants_help( &args( "--words --help a.txt" ) ) );
+    assert!( wants_help( &args( "-d , -h" ) ) );
+
+    assert!( !wants_help( &args( "-d -h" ) ) );
+    assert!( !wants_help( &args( "--delimiter --help --lines" ) ) );
+    assert!( !wants_help( &args( "-o -h --sizes 10" ) ) );
+    assert!( !wants_help( &args( "-i -- --help" ) ) );
+    assert!( !wants_help( &[] ) );
+}
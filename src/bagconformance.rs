@@ -0,0 +1,408 @@
+// FILE: bagconformance.rs
+
+//! This module holds the conformance tests that every bag
+//! implementation must pass, written once against `BagOps`.
+//!
+//! 1) basic tests of insert and the constant member functions
+//!
+//! 2) tests of the copy constructor and == method
+//!
+//! 3) tests of the assignment operator
+//!
+//! 4) basic tests for the erase functions
+//!
+//! 5) basic tests for the += and + functions
+//!
+//! 6) tests for the bag iterator
+//!
+//! 7) random operations checked against a model, see bagproperty.rs
+//!
+//! A bag is tested by naming it in `conformance_tests!`, which
+//! generates a module of `#[ test ]`s for it. The elements of the bag
+//! are made from the `i16` values used by the tests.
+//!
+//! ```
+//! conformance_tests!( bag => ::bag::Bag<i16>,
+//!                     bagf64 => ::bagf64::Bag );
+//! ```
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use bagtrait::BagOps;
+use std::fmt::Debug;
+
+/// Generate the conformance tests, `test1` through `test7`, in a
+/// module of the given name for each bag type.
+
+macro_rules! conformance_tests
+{
+    ( $( $name:ident => $bag:ty ),* ) =>
+    {
+        $(
+            mod $name
+            {
+                #[ test ]
+                fn test1() { ::bagconformance::test1::<$bag>(); }
+
+                #[ test ]
+                fn test2() { ::bagconformance::test2::<$bag>(); }
+
+                #[ test ]
+                fn test3() { ::bagconformance::test3::<$bag>(); }
+
+                #[ test ]
+                fn test4() { ::bagconformance::test4::<$bag>(); }
+
+                #[ test ]
+                fn test5() { ::bagconformance::test5::<$bag>(); }
+
+                #[ test ]
+                fn test6() { ::bagconformance::test6::<$bag>(); }
+
+                #[ test ]
+                fn test7() { ::bagproperty::check_random::<$bag>( 200, 60 ); }
+            }
+        )*
+    };
+}
+
+// Makes a bag element from one of the values used by the tests.
+fn value<B: BagOps>( n: i16 ) -> B::Item
+    where B::Item: From<i16>
+{
+    B::Item::from( n )
+}
+
+///   Performs some basic tests of insert and the constant member functions.
+pub fn test1<B: BagOps>()
+    where B::Item: From<i16>
+{
+    #[ allow( non_snake_case ) ]
+    let TEST_SIZE = 3000;
+    let mut test_bag = B::default();
+
+    // A. Testing size for an empty bag.
+    assert_eq!( test_bag.len(), 0 );
+
+    // B. Adding the number 4 to the bag, and then testing size.
+    test_bag.insert( value::<B>( 4 ) );
+    assert_eq!( test_bag.len(), 1 );
+
+    // C. Inserting the number 2 into the bag. Then checking size
+    test_bag.insert( value::<B>( 2 ) );
+    assert_eq!( test_bag.len(), 2 );
+
+    // D. Inserting the number 1 into the bag. Then checking size.
+    test_bag.insert( value::<B>( 1 ) );
+    assert_eq!( test_bag.len(), 3 );
+
+    // E. Inserting the number 3 into the bag. Then checking size.
+    test_bag.insert( value::<B>( 3 ) );
+    assert_eq!( test_bag.len(), 4 );
+
+    // F. Inserting another 2 into the bag. Then checking size.
+    test_bag.insert( value::<B>( 2 ) );
+    assert_eq!( test_bag.len(), 5 );
+
+    //    Then checking occurrences of 2.
+    assert_eq!( test_bag.occurrences( value::<B>( 2 ) ), 2 );
+
+    // G. Inserting the numbers 5, 6, and 7 into the bag. Then checking size.
+    test_bag.insert( value::<B>( 5 ) );
+    test_bag.insert( value::<B>( 6 ) );
+    test_bag.insert( value::<B>( 7 ) );
+    assert_eq!( test_bag.len(), 8 );
+
+    // H. Inserting two more 2's into the bag. And then checking occurrences of 2's.
+    test_bag.insert( value::<B>( 2 ) );
+    test_bag.insert( value::<B>( 2 ) );
+    assert_eq!( test_bag.occurrences( value::<B>( 2 ) ), 4 );
+
+    // I. Inserting TEST_SIZE random items between 0 and 49 and then checking size.
+
+    for _ in 0..TEST_SIZE
+    {
+        test_bag.insert( value::<B>( rand::random::<i16>() % 50 ) );
+    }
+
+    assert_eq!( test_bag.len(), TEST_SIZE + 10 );
+}
+
+///   Performs some tests of the copy constructor and == method.
+pub fn test2<B: BagOps + Debug>()
+    where B::Item: From<i16>
+{
+    let mut test_bag = B::default();
+
+    // A. Testing that copy constructor works okay for empty bag...
+    let copy1 = test_bag.clone();
+    assert_eq!( copy1.len(), 0 );
+
+    // B. Testing copy constructor with 4-item bag...
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    let copy2 = test_bag.clone();
+
+    //    and now testing the == method...
+    assert_eq!( test_bag, copy2 );
+    assert_eq!( copy2, test_bag );
+
+    test_bag.insert( value::<B>( 1 ) ); // Alter the original, but not the copy
+    // C. Then checking size of copy.
+    assert_eq!( copy2.len(), 4 );
+
+    // D. Altering original but not the copy.
+    assert_eq!( test_bag.len(), 5 );
+}
+
+///   Performs some tests of the assignment operator.
+#[ allow( clippy::self_assignment ) ]
+pub fn test3<B: BagOps + Debug>()
+    where B::Item: From<i16>
+{
+    let mut test_bag = B::default();
+
+    // A. Testing that assignment operator works okay for empty bag...
+    let mut copy1 = B::default();
+    copy1.insert( value::<B>( 1 ) );
+    copy1 = test_bag.clone();
+
+    assert_eq!( copy1.len(), 0 );
+
+    // B. Testing assignment operator with 4-item bag...
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    test_bag.insert( value::<B>( 1 ) );
+    let mut copy2 = B::default();
+    copy2.clone_from( &test_bag );
+    test_bag.insert( value::<B>( 1 ) ); // Alter the original, but not the copy
+    //    altering original by an insertion...
+
+    assert_eq!( test_bag.occurrences( value::<B>( 1 ) ), 5 );
+    assert_eq!( copy2.occurrences( value::<B>( 1 ) ), 4 );
+
+    // Test passed.\n   testing size of assigned to...
+    assert_eq!( copy2.len(), 4 );
+
+    //    testing size of original...
+    assert_eq!( test_bag.len(), 5 );
+
+    // C. Testing assignment operator for a self-assignment...
+    let original = test_bag.clone();
+    test_bag = test_bag;
+
+    assert_eq!( test_bag, original );
+}
+
+///   Performs basic tests for the erase functions.
+pub fn test4<B: BagOps>()
+    where B::Item: From<i16>
+{
+    let mut test_bag = B::default();
+
+    // Testing erase from empty bag, should have no effect.
+    assert_eq!( test_bag.erase( value::<B>( 0 ) ), 0 );
+    assert_eq!( test_bag.len(), 0 );
+
+    // Inserting these: 8 6 10 1 7 10 15 3 13 2 5 11 14 4 12
+    for &n in &[ 8, 6, 10, 1, 7, 10, 15, 3, 13, 2, 5, 11, 14, 4, 12 ]
+    {
+        test_bag.insert( value::<B>( n ) );
+    }
+    assert_eq!( test_bag.len(), 15 );
+
+    // Now testing capacity, should be 16.
+    assert_eq!( test_bag.capacity(), 16 );
+
+    // Erasing 0, which is not in bag, so bag should be unchanged.
+    assert!( !test_bag.erase_one( value::<B>( 0 ) ) );
+    assert_eq!( test_bag.len(), 15 );
+
+    // Erasing the 6.
+    assert_eq!( test_bag.erase( value::<B>( 6 ) ), 1 );
+    assert_eq!( test_bag.len(), 14 );
+
+    // Erasing one 10.
+    assert!( test_bag.erase_one( value::<B>( 10 ) ) );
+    assert_eq!( test_bag.len(), 13 );
+
+    // Erasing the 1, 15, 5, 11, 3, 13 and 2 one at a time.
+    let mut expected_len = 13;
+
+    for &n in &[ 1, 15, 5, 11, 3, 13, 2 ]
+    {
+        assert_eq!( test_bag.erase( value::<B>( n ) ), 1 );
+        expected_len -= 1;
+        assert_eq!( test_bag.len(), expected_len );
+    }
+
+    // Erasing the one and only 14.
+    assert!( test_bag.erase_one( value::<B>( 14 ) ) );
+    assert_eq!( test_bag.len(), 5 );
+
+    // Erasing the 4, 12, 8 and 7 one at a time.
+    for &n in &[ 4, 12, 8, 7 ]
+    {
+        assert_eq!( test_bag.erase( value::<B>( n ) ), 1 );
+    }
+    assert_eq!( test_bag.len(), 1 );
+
+    // Erasing the other 10.
+    assert!( test_bag.erase_one( value::<B>( 10 ) ) );
+    assert_eq!( test_bag.len(), 0 );
+
+    // Testing capacity again.
+    assert_eq!( test_bag.capacity(), 16 );
+
+    // Now trimming to size.
+    test_bag.shrink_to_fit();
+    assert_eq!( test_bag.capacity(), 1 );
+
+    // Now trimming to size again.
+    test_bag.shrink_to_fit();
+    assert_eq!( test_bag.capacity(), 1 );
+
+    // Inserting value 5000 into the bag.
+    // Inserting three 5's into the bag and then erasing all of them.
+    test_bag.insert( value::<B>( 5000 ) );
+    test_bag.insert( value::<B>( 5 ) );
+    test_bag.insert( value::<B>( 5 ) );
+    test_bag.insert( value::<B>( 5 ) );
+    assert_eq!( test_bag.erase( value::<B>( 5 ) ), 3 );
+    assert_eq!( test_bag.len(), 1 );
+}
+
+///   Performs basic tests for the += and + functions
+pub fn test5<B: BagOps + ::std::ops::Add<Output = B>>()
+    where B::Item: From<i16>
+{
+    let mut test_bag1 = B::default();
+    let mut test_bag2 = B::default();
+    let mut test_bag3 = B::default();
+
+    // Inserting 2000 1's into test_bag1 and 2000 2's into test_bag2.
+
+    for _ in 0..2000
+    {
+        test_bag1.insert( value::<B>( 1 ) );
+        test_bag2.insert( value::<B>( 2 ) );
+    }
+
+    // Now testing the += function.
+    test_bag1 += test_bag2.clone();
+
+    //   and now testing for occurrences of 1's and 2's in test_bag1.
+    assert_eq!( test_bag1.occurrences( value::<B>( 1 ) ), 2000 );
+    assert_eq!( test_bag1.occurrences( value::<B>( 2 ) ), 2000 );
+    assert_eq!( test_bag2.occurrences( value::<B>( 2 ) ), 2000 );
+
+    // Now testing the + function.
+    test_bag3.clone_from( &( test_bag2.clone() + test_bag2.clone() ) );
+
+    //   and now testing for occurrences of 2's in test_bag3.
+    assert_eq!( test_bag3.occurrences( value::<B>( 2 ) ), 4000 );
+}
+
+///   Performs tests for the bag iterator
+#[ allow( clippy::iter_nth_zero ) ]
+pub fn test6<B: BagOps>()
+    where B::Item: From<i16> + Debug
+{
+    let mut testbag = B::default();
+
+    { // Testing next(), last(), and count()
+        let mut iter = testbag.iter();
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.last(), None );
+        assert_eq!( testbag.iter().count(), 0 );
+    }
+
+    testbag.insert( value::<B>( 8 ) );
+
+    { // Testing next(), last(), and count() again
+        let mut iter = testbag.iter();
+        assert_eq!( iter.next(), Some( &value::<B>( 8 ) ) );
+        assert_eq!( iter.last(), None );
+        assert_eq!( testbag.iter().last(), Some( &value::<B>( 8 ) ) );
+        assert_eq!( testbag.iter().count(), 1 );
+    }
+
+    testbag.insert( value::<B>( 3 ) );
+    testbag.insert( value::<B>( 4 ) );
+    testbag.insert( value::<B>( 7 ) );
+
+    { // Testing next(), last(), and count() again
+        let mut iter = testbag.iter();
+        assert_eq!( iter.next(), Some( &value::<B>( 8 ) ) );
+        assert_eq!( iter.last(), Some( &value::<B>( 7 ) ) );
+        assert_eq!( testbag.iter().count(), 4 );
+    }
+
+    testbag.insert( value::<B>( 2 ) );
+
+    {
+        let mut iter = testbag.iter();
+
+        // Testing the full range of the bag with next()
+        assert_eq!( iter.next(), Some( &value::<B>( 8 ) ) );
+        assert_eq!( iter.next(), Some( &value::<B>( 3 ) ) );
+        assert_eq!( iter.next(), Some( &value::<B>( 4 ) ) );
+        assert_eq!( iter.next(), Some( &value::<B>( 7 ) ) );
+        assert_eq!( iter.next(), Some( &value::<B>( 2 ) ) );
+
+        // ... and then None once it's over.
+        assert_eq!( iter.next(), None );
+
+        // More calls may or may not return None. Here, they always will.
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next(), None );
+    }
+    { // Testing the nth( _ ) method
+        let mut iter = testbag.iter();
+
+        assert_eq!( iter.nth( 1 ), Some( &value::<B>( 3 ) ) );
+        assert_eq!( iter.nth( 1 ), Some( &value::<B>( 7 ) ) );
+        assert_eq!( iter.nth( 1 ), None );
+    }
+    {
+        let mut iter = testbag.iter();
+
+        assert_eq!( iter.nth( 0 ), Some( &value::<B>( 8 ) ) );
+        assert_eq!( iter.nth( 2 ), Some( &value::<B>( 7 ) ) );
+        assert_eq!( iter.nth( 9 ), None );
+    }
+    { // Testing len() and size_hint() as the iterator is used
+        let mut iter = testbag.iter();
+        assert_eq!( iter.len(), 5 );
+        assert_eq!( iter.size_hint(), ( 5, Some( 5 ) ) );
+
+        iter.next();
+        iter.next_back();
+        assert_eq!( iter.len(), 3 );
+        assert_eq!( iter.size_hint(), ( 3, Some( 3 ) ) );
+
+        assert_eq!( iter.nth( usize::MAX ), None );
+        assert_eq!( iter.len(), 0 );
+    }
+    { // Testing next_back() and nth_back( _ )
+        let mut iter = testbag.iter();
+
+        assert_eq!( iter.next_back(), Some( &value::<B>( 2 ) ) );
+        assert_eq!( iter.next(), Some( &value::<B>( 8 ) ) );
+        assert_eq!( iter.nth_back( 1 ), Some( &value::<B>( 4 ) ) );
+        assert_eq!( iter.next_back(), Some( &value::<B>( 3 ) ) );
+        assert_eq!( iter.next_back(), None );
+        assert_eq!( iter.next(), None );
+        assert_eq!( testbag.iter().nth_back( 9 ), None );
+    }
+    { // Testing rev()
+        let reversed: Vec<&B::Item> = testbag.iter().rev().collect();
+        let expected = [ 2, 7, 4, 3, 8 ].iter().map( | &n | value::<B>( n ) ).collect::<Vec<B::Item>>();
+        assert_eq!( reversed, expected.iter().collect::<Vec<&B::Item>>() );
+    }
+}
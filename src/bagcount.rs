@@ -0,0 +1,313 @@
+// FILE: bagcount.rs
+
+//! Counting the frequency of tokens in text with the generic bag.
+//!
+//! Run with `cargo run -- count [OPTIONS] [FILE...]`
+//!
+//! The text is read from the files given, or from standard input if
+//! there are none or a file is named `-`.
+//!
+//! # Options
+//!
+//! * `--words` Count whitespace separated words (the default)
+//!
+//! * `--lines` Count whole lines
+//!
+//! * `-d`, `--delimiter <text>` Count the tokens separated by `text`
+//!
+//! * `-i`, `--ignore-case` Fold tokens to lower case before counting
+//!
+//! * `-n`, `--top <N>` Print only the `N` most frequent tokens
+//!
+//! * `--format <text|csv|json>` The output format, `text` by default
+//!
+//! * `--` The arguments after it are files, even those starting with `-`
+
+use bag::Bag;
+use bagcsv;
+use std::fs::File;
+use std::io::Read;
+
+/// How text is split into tokens.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum Tokenizer
+{
+    /// Whitespace separated words.
+    Words,
+    /// Whole lines, with surrounding whitespace removed.
+    Lines,
+    /// Tokens separated by the given text, with surrounding whitespace removed.
+    Delimiter( String )
+}
+
+/// How the counts are printed.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum Format
+{
+    /// A right aligned count followed by the token on each line.
+    Text,
+    /// `value,count` rows after a header row.
+    Csv,
+    /// An array of `{"value": ..., "count": ...}` objects.
+    Json
+}
+
+/// The options of the `count` command.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct CountOptions
+{
+    pub tokenizer: Tokenizer,
+    pub ignore_case: bool,
+    pub top: Option<usize>,
+    pub format: Format,
+    pub files: Vec<String>
+}
+
+impl Default for CountOptions
+{
+    /// Count words, case sensitive, printing all of them as text from standard input.
+
+    fn default() -> Self
+    {
+        CountOptions { tokenizer: Tokenizer::Words,
+                       ignore_case: false,
+                       top: None,
+                       format: Format::Text,
+                       files: Vec::new() }
+    }
+}
+
+impl CountOptions
+{
+    /// Read the options of the `count` command from its arguments.
+    ///
+    /// # Return
+    /// The options, or an Err describing the first invalid argument.
+
+    pub fn parse( args: &[String] ) -> Result<Self, String>
+    {
+        let mut options = CountOptions::default();
+        let mut args = args.iter();
+
+        while let Some( arg ) = args.next()
+        {
+            match arg.as_str()
+            {
+                "--words" => { options.tokenizer = Tokenizer::Words; }
+                "--lines" => { options.tokenizer = Tokenizer::Lines; }
+                "-d" | "--delimiter" =>
+                    {
+                        let delimiter = value_of( arg, args.next() )?;
+
+                        if delimiter.is_empty()
+                        {
+                            return Err( "the delimiter must not be empty".to_string() );
+                        }
+
+                        options.tokenizer = Tokenizer::Delimiter( delimiter.clone() );
+                    }
+                "-i" | "--ignore-case" => { options.ignore_case = true; }
+                "--" =>
+                    {
+                        options.files.extend( args.by_ref().cloned() );
+                    }
+                "-n" | "--top" =>
+                    {
+                        let top = value_of( arg, args.next() )?;
+                        options.top = Some( top.parse::<usize>()
+                                               .map_err( | _ | format!( "`{}` is not a valid count for {}", top, arg ) )? );
+                    }
+                "--format" =>
+                    {
+                        options.format = match value_of( arg, args.next() )?.as_str()
+                        {
+                            "text" => Format::Text,
+                            "csv" => Format::Csv,
+                            "json" => Format::Json,
+                            other => return Err( format!( "unknown format `{}`, expected text, csv or json", other ) )
+                        };
+                    }
+                option if option.starts_with( '-' ) && option != "-" =>
+                    {
+                        return Err( format!( "unknown option `{}` for count", option ) );
+                    }
+                file => { options.files.push( file.to_string() ); }
+            }
+        }
+
+        Ok( options )
+    }
+}
+
+// Returns the value following an option that requires one.
+fn value_of<'a>( option: &str, value: Option<&'a String> ) -> Result<&'a String, String>
+{
+    value.ok_or_else( || format!( "{} requires a value", option ) )
+}
+
+/// Run the `count` command.
+///
+/// # Return
+/// An Err with a message for the user if the arguments are invalid
+/// or an input could not be read.
+
+pub fn count( args: &[String] ) -> Result<(), String>
+{
+    let options = CountOptions::parse( args )?;
+    let mut tokens = Bag::new();
+
+    if options.files.is_empty()
+    {
+        add_tokens( &mut tokens, &read_input( "-" )?, &options );
+    }
+
+    for file in &options.files
+    {
+        add_tokens( &mut tokens, &read_input( file )?, &options );
+    }
+
+    print!( "{}", render( &top( &tokens, options.top ), options.format ) );
+    Ok( () )
+}
+
+// Reads all of a file, or standard input for `-`.
+fn read_input( path: &str ) -> Result<String, String>
+{
+    let mut text = String::new();
+
+    if path == "-"
+    {
+        ::std::io::stdin().read_to_string( &mut text )
+                          .map_err( | error | format!( "could not read standard input: {}", error ) )?;
+    }
+    else
+    {
+        File::open( path ).and_then( | mut file | file.read_to_string( &mut text ) )
+                          .map_err( | error | format!( "could not read `{}`: {}", path, error ) )?;
+    }
+
+    Ok( text )
+}
+
+/// Insert the tokens of `text` into a bag.
+///
+/// # Parameter: `options`
+/// How to split and fold the tokens. Empty tokens are not inserted.
+
+pub fn add_tokens( tokens: &mut Bag<String>, text: &str, options: &CountOptions )
+{
+    let add = | token: &str |
+        {
+            let token = token.trim();
+
+            if !token.is_empty()
+            {
+                tokens.insert( if options.ignore_case { token.to_lowercase() } else { token.to_string() } );
+            }
+        };
+
+    match options.tokenizer
+    {
+        Tokenizer::Words => text.split_whitespace().for_each( add ),
+        Tokenizer::Lines => text.lines().for_each( add ),
+        Tokenizer::Delimiter( ref delimiter ) => text.split( delimiter.as_str() ).for_each( add )
+    }
+}
+
+/// List the distinct tokens of a bag with their counts.
+///
+/// # Parameter: `limit`
+/// The most tokens to list, or None for all of them.
+///
+/// # Return
+/// The tokens from the most to the least frequent, with tokens
+/// of equal frequency in alphabetical order. If the limit falls among
+/// tokens of equal frequency, those first seen in the text are kept.
+///
+/// # Note
+/// With a limit, only that many tokens are kept while the counts are
+/// read, by `Bag::most_common( _ )`, rather than sorting all of them.
+
+pub fn top( tokens: &Bag<String>, limit: Option<usize> ) -> Vec<( String, usize )>
+{
+    let mut counts: Vec<( String, usize )> = match limit
+    {
+        Some( limit ) => tokens.most_common( limit )
+                               .into_iter()
+                               .map( | ( token, count ) | ( token.clone(), count ) )
+                               .collect(),
+        None => tokens.counts()
+                      .map( | ( token, count ) | ( token.clone(), count ) )
+                      .collect()
+    };
+
+    counts.sort_by( | a, b | b.1.cmp( &a.1 ).then_with( || a.0.cmp( &b.0 ) ) );
+    counts
+}
+
+/// Render a list of tokens and counts in the given format.
+
+pub fn render( counts: &[( String, usize )], format: Format ) -> String
+{
+    let mut output = String::new();
+
+    match format
+    {
+        Format::Text =>
+            {
+                for &( ref token, count ) in counts
+                {
+                    output += &format!( "{:>8} {}\n", count, token );
+                }
+            }
+        Format::Csv =>
+            {
+                output += "value,count\n";
+
+                for &( ref token, count ) in counts
+                {
+                    output += &format!( "{},{}\n", bagcsv::field( token, ',' ), count );
+                }
+            }
+        Format::Json =>
+            {
+                output += "[";
+
+                for ( index, &( ref token, count ) ) in counts.iter().enumerate()
+                {
+                    output += if index == 0 { "\n" } else { ",\n" };
+                    output += &format!( "  {{\"value\": {}, \"count\": {}}}", json_string( token ), count );
+                }
+
+                output += if counts.is_empty() { "]\n" } else { "\n]\n" };
+            }
+    }
+
+    output
+}
+
+// Quotes and escapes a JSON string.
+fn json_string( text: &str ) -> String
+{
+    let mut quoted = String::from( "\"" );
+
+    for c in text.chars()
+    {
+        match c
+        {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c if ( c as u32 ) < 0x20 => quoted += &format!( "\\u{:04x}", c as u32 ),
+            c => quoted.push( c )
+        }
+    }
+
+    quoted.push( '"' );
+    quoted
+}
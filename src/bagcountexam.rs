@@ -0,0 +1,128 @@
+// FILE: bagcountexam.rs
+
+//! This module holds tests for counting tokens with bagcount
+//!
+//! 1) tests of the option parsing
+//!
+//! 2) tests of the tokenizers and case folding
+//!
+//! 3) tests of the ordering and limit of the counts
+//!
+//! 4) tests of the output formats
+//!
+//! Run with `cargo test`
+
+use bag::Bag;
+use bag::len_trait::Len;
+use bagcount::{ CountOptions, Tokenizer, Format, add_tokens, top, render };
+
+fn args( text: &str ) -> Vec<String>
+{
+    text.split_whitespace().map( | arg | arg.to_string() ).collect()
+}
+
+#[ test ]
+///   Performs tests of the option parsing.
+fn test1()
+{
+    // A. No arguments give the defaults.
+    assert_eq!( CountOptions::parse( &[] ), Ok( CountOptions::default() ) );
+
+    // B. Every option together.
+    let options = CountOptions::parse( &args( "-d ; --ignore-case -n 3 --format json a.txt -" ) ).unwrap();
+    assert_eq!( options.tokenizer, Tokenizer::Delimiter( ";".to_string() ) );
+    assert!( options.ignore_case );
+    assert_eq!( options.top, Some( 3 ) );
+    assert_eq!( options.format, Format::Json );
+    assert_eq!( options.files, vec![ "a.txt".to_string(), "-".to_string() ] );
+
+    // C. Arguments after `--` are files, and an option's value may look like an option.
+    let options = CountOptions::parse( &args( "-d -h -- --help -i" ) ).unwrap();
+    assert_eq!( options.tokenizer, Tokenizer::Delimiter( "-h".to_string() ) );
+    assert!( !options.ignore_case );
+    assert_eq!( options.files, vec![ "--help".to_string(), "-i".to_string() ] );
+
+    // D. Invalid arguments are errors.
+    assert!( CountOptions::parse( &args( "--top" ) ).is_err() );
+    assert!( CountOptions::parse( &args( "--top many" ) ).is_err() );
+    assert!( CountOptions::parse( &args( "--format xml" ) ).is_err() );
+    assert!( CountOptions::parse( &args( "--unknown" ) ).is_err() );
+}
+
+#[ test ]
+///   Performs tests of the tokenizers and case folding.
+fn test2()
+{
+    let text = "The cat\nthe  dog\n\nthe cat ";
+    let mut options = CountOptions::default();
+
+    // A. Words.
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, text, &options );
+    assert_eq!( tokens.len(), 6 );
+    assert_eq!( tokens.occurrences( "the".to_string() ), 2 );
+
+    // B. Words, ignoring case.
+    options.ignore_case = true;
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, text, &options );
+    assert_eq!( tokens.occurrences( "the".to_string() ), 3 );
+
+    // C. Lines, skipping the empty line.
+    options.tokenizer = Tokenizer::Lines;
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, text, &options );
+    assert_eq!( tokens.len(), 3 );
+    assert_eq!( tokens.occurrences( "the cat".to_string() ), 2 );
+
+    // D. A delimiter.
+    options.tokenizer = Tokenizer::Delimiter( ",".to_string() );
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, "a, b,,A ,c", &options );
+    assert_eq!( tokens.len(), 4 );
+    assert_eq!( tokens.occurrences( "a".to_string() ), 2 );
+}
+
+#[ test ]
+///   Performs tests of the ordering and limit of the counts.
+fn test3()
+{
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, "b c a c b c d", &CountOptions::default() );
+
+    // A. Most frequent first, ties in alphabetical order.
+    assert_eq!( top( &tokens, None ),
+                vec![ ( "c".to_string(), 3 ), ( "b".to_string(), 2 ),
+                      ( "a".to_string(), 1 ), ( "d".to_string(), 1 ) ] );
+
+    // B. Limited to the top two.
+    assert_eq!( top( &tokens, Some( 2 ) ),
+                vec![ ( "c".to_string(), 3 ), ( "b".to_string(), 2 ) ] );
+
+    // C. A limit among tokens of equal frequency keeps those seen first,
+    // still listed in alphabetical order.
+    let mut tokens = Bag::new();
+    add_tokens( &mut tokens, "z y y x x w w", &CountOptions::default() );
+
+    assert_eq!( top( &tokens, Some( 2 ) ),
+                vec![ ( "x".to_string(), 2 ), ( "y".to_string(), 2 ) ] );
+    assert_eq!( top( &tokens, Some( 0 ) ), vec![] );
+
+    // D. An empty bag has no counts.
+    assert!( top( &Bag::new(), Some( 2 ) ).is_empty() );
+}
+
+#[ test ]
+///   Performs tests of the output formats.
+fn test4()
+{
+    let counts = vec![ ( "say \"hi\", all".to_string(), 2 ), ( "x".to_string(), 1 ) ];
+
+    assert_eq!( render( &counts, Format::Text ),
+                "       2 say \"hi\", all\n       1 x\n" );
+    assert_eq!( render( &counts, Format::Csv ),
+                "value,count\n\"say \"\"hi\"\", all\",2\nx,1\n" );
+    assert_eq!( render( &counts, Format::Json ),
+                "[\n  {\"value\": \"say \\\"hi\\\", all\", \"count\": 2},\n  {\"value\": \"x\", \"count\": 1}\n]\n" );
+    assert_eq!( render( &[], Format::Json ), "[]\n" );
+}
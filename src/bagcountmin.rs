@@ -0,0 +1,306 @@
+// FILE: bagcountmin.rs
+
+//! An approximate Bag for streams too large to count exactly, backed
+//! by a Count-Min Sketch.
+//!
+//! The sketch is `depth` rows of `width` counters. Inserting an element
+//! adds one to a counter in each row, chosen by hashing the element
+//! with the row, and the occurrences of an element are the least of
+//! its counters. The memory used depends only on the width and depth,
+//! not on the number of elements or distinct values.
+//!
+//! The occurrences are never less than the true number. With a width of
+//! `⌈e / ε⌉` and a depth of `⌈ln( 1 / δ )⌉` they are more than the true
+//! number by at most `ε` times the number of elements inserted, except
+//! with a probability of at most `δ`.
+//!
+//! The type used must have the trait `Hash`.
+
+use bag::len_trait::len::{ Len, Empty, Clear };
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{ self, Debug, Formatter };
+use std::hash::{ Hash, Hasher };
+use std::marker::PhantomData;
+use std::ops::{ Add, AddAssign };
+
+/// A container for inserting values and estimating their occurrences.
+///
+/// # Invariant of the CountMinBag struct:
+/// 1. `counters` holds `depth` rows of `width` counters, with the
+/// counter of `row` and `column` at `counters[ row * width + column ]`.
+///
+/// 2. Each row's counters add up to `len`, the number of elements inserted.
+
+pub struct CountMinBag<Type: Hash>
+{
+    counters: Vec<usize>,
+    width: usize,
+    depth: usize,
+    len: usize,
+    marker: PhantomData<Type>
+}
+
+impl<Type: Hash> CountMinBag<Type>
+{
+    /// Initialize an empty sketch of `depth` rows of `width` counters.
+    ///
+    /// # Parameter: `width`
+    /// The counters in each row, greater than 0. The more there are
+    /// the less the occurrences are overestimated.
+    ///
+    /// # Parameter: `depth`
+    /// The rows of counters, greater than 0. The more there are the
+    /// less likely the overestimate is more than the error bound.
+    ///
+    /// # Panics
+    /// `width` or `depth` given is not greater than 0, or there would
+    /// be more than `usize::MAX` counters.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counters.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: CountMinBag<&str> = CountMinBag::new( 1000, 5 );
+    ///
+    /// assert_eq!( bag.width(), 1000 );
+    /// ```
+
+    pub fn new( width: usize, depth: usize ) -> Self
+    {
+        if width == 0 || depth == 0
+        {
+            panic!( "width and depth must be > 0" );
+        }
+
+        let counters = match width.checked_mul( depth )
+        {
+            Some( counters ) => counters,
+            None => panic!( "a sketch of width {} and depth {} has too many counters", width, depth )
+        };
+
+        CountMinBag { counters: vec![ 0; counters ], width, depth, len: 0, marker: PhantomData }
+    }
+
+    /// Initialize an empty sketch sized for an error bound.
+    ///
+    /// # Parameter: `epsilon`
+    /// The overestimate allowed, as a fraction of the number of
+    /// elements inserted, between 0 and 1.
+    ///
+    /// # Parameter: `delta`
+    /// The probability allowed of an overestimate beyond `epsilon`,
+    /// between 0 and 1.
+    ///
+    /// # Return
+    /// A sketch with a width of `⌈e / epsilon⌉` and a depth of `⌈ln( 1 / delta )⌉`.
+    ///
+    /// # Panics
+    /// `epsilon` or `delta` given is not between 0 and 1, or is so
+    /// small that the sketch would have more than `usize::MAX` counters.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: CountMinBag<u32> = CountMinBag::with_error( 0.01, 0.01 );
+    ///
+    /// assert_eq!( ( bag.width(), bag.depth() ), ( 272, 5 ) );
+    /// ```
+
+    pub fn with_error( epsilon: f64, delta: f64 ) -> Self
+    {
+        if !( epsilon > 0.0 && epsilon < 1.0 && delta > 0.0 && delta < 1.0 )
+        {
+            panic!( "epsilon and delta must be between 0 and 1" );
+        }
+
+        CountMinBag::new( ( ::std::f64::consts::E / epsilon ).ceil() as usize,
+                          ( 1.0 / delta ).ln().ceil().max( 1.0 ) as usize )
+    }
+
+    /// The number of counters in each row.
+
+    pub fn width( &self ) -> usize
+    {
+        self.width
+    }
+
+    /// The number of rows of counters.
+
+    pub fn depth( &self ) -> usize
+    {
+        self.depth
+    }
+
+    /// The most the occurrences of a value are likely to be overestimated.
+    ///
+    /// # Return
+    /// `e / width` times the number of elements inserted, which is
+    /// exceeded with a probability of at most `e^-depth`.
+
+    pub fn error_bound( &self ) -> f64
+    {
+        ::std::f64::consts::E / self.width as f64 * self.len as f64
+    }
+
+    // The index of the counter of `item` in `row`.
+    fn index( &self, row: usize, item: &Type ) -> usize
+    {
+        let mut hasher = DefaultHasher::new();
+
+        row.hash( &mut hasher );
+        item.hash( &mut hasher );
+
+        row * self.width + ( hasher.finish() % self.width as u64 ) as usize
+    }
+
+    /// Add a new element to this bag.
+    ///
+    /// # Parameter: `new_item`
+    /// The new element that is to be counted.
+    ///
+    /// # Postcondition
+    /// A counter of `new_item` in each row has been increased by one.
+    /// The element itself is not kept.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = CountMinBag::new( 100, 3 );
+    /// bag.insert( "a" );
+    /// bag.insert( "a" );
+    ///
+    /// assert!( bag.occurrences( "a" ) >= 2 );
+    /// ```
+
+    pub fn insert( &mut self, new_item: Type )
+    {
+        for row in 0..self.depth
+        {
+            let index = self.index( row, &new_item );
+            self.counters[ index ] = self.counters[ index ].saturating_add( 1 );
+        }
+
+        self.len = self.len.saturating_add( 1 );
+    }
+
+    /// Estimate the number of occurrences of a particular element in this bag.
+    ///
+    /// # Parameter: `target`
+    /// The element for which number of occurrences will be estimated.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// At least the number of times that `target` was inserted, and
+    /// likely no more than that plus `error_bound()`.
+
+    pub fn occurrences( &self, target: Type ) -> usize
+    {
+        ( 0..self.depth ).map( | row | self.counters[ self.index( row, &target ) ] )
+                         .min()
+                         .unwrap_or( 0 )
+    }
+}
+
+impl<Type: Hash> Len for CountMinBag<Type>
+{
+    /// The number of elements inserted into the bag.
+
+    fn len( &self ) -> usize
+    {
+        self.len
+    }
+}
+
+impl<Type: Hash> Empty for CountMinBag<Type>
+{
+    /// Whether nothing has been inserted into the bag.
+
+    fn is_empty( &self ) -> bool
+    {
+        self.len == 0
+    }
+}
+
+impl<Type: Hash> Clear for CountMinBag<Type>
+{
+    /// Reset every counter, keeping the width and depth.
+
+    fn clear( &mut self )
+    {
+        self.counters.iter_mut().for_each( | counter | *counter = 0 );
+        self.len = 0;
+    }
+}
+
+impl<Type: Hash> Clone for CountMinBag<Type>
+{
+    /// Copy the counters of the bag.
+
+    fn clone( &self ) -> Self
+    {
+        CountMinBag { counters: self.counters.clone(), width: self.width, depth: self.depth,
+                      len: self.len, marker: PhantomData }
+    }
+}
+
+impl<Type: Hash> PartialEq for CountMinBag<Type>
+{
+    /// Whether two bags have the same dimensions and counters.
+
+    fn eq( &self, other: &CountMinBag<Type> ) -> bool
+    {
+        self.width == other.width && self.depth == other.depth && self.counters == other.counters
+    }
+}
+
+impl<Type: Hash> AddAssign for CountMinBag<Type>
+{
+    /// Add the counts of another bag to this bag, as if every element
+    /// inserted into `addend` had been inserted into this bag.
+    ///
+    /// # Panics
+    /// The bags do not have the same width and depth.
+
+    fn add_assign( &mut self, addend: CountMinBag<Type> )
+    {
+        if self.width != addend.width || self.depth != addend.depth
+        {
+            panic!( "cannot merge a {}x{} sketch into a {}x{} sketch",
+                    addend.width, addend.depth, self.width, self.depth );
+        }
+
+        for ( counter, added ) in self.counters.iter_mut().zip( addend.counters )
+        {
+            *counter = counter.saturating_add( added );
+        }
+
+        self.len = self.len.saturating_add( addend.len );
+    }
+}
+
+impl<Type: Hash> Add for CountMinBag<Type>
+{
+    type Output = CountMinBag<Type>;
+
+    /// Merge two bags into a new bag.
+    ///
+    /// # Panics
+    /// The bags do not have the same width and depth.
+
+    fn add( mut self, addend: CountMinBag<Type> ) -> CountMinBag<Type>
+    {
+        self += addend;
+        self
+    }
+}
+
+impl<Type: Hash> Debug for CountMinBag<Type>
+{
+    /// Renders the dimensions of the sketch and the elements inserted.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "CountMinBag with {} elements: {}x{} counters", self.len, self.width, self.depth )
+    }
+}
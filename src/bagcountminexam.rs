@@ -0,0 +1,137 @@
+// FILE: bagcountminexam.rs
+
+//! This module holds tests for the approximate bag of bagcountmin
+//!
+//! 1) tests of the dimensions and of inserting and counting
+//!
+//! 2) tests of the error bound on a skewed stream
+//!
+//! 3) tests of merging with += and +
+//!
+//! 4) a test of merging sketches of different widths
+//!
+//! 5) a test of an error bound too small for the counters to be counted
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bagcountmin::CountMinBag;
+use bag::len_trait::len::{ Len, Empty, Clear };
+use std::collections::HashMap;
+
+// A stream in which a few values are frequent and most are rare.
+fn skewed_stream( len: usize, seed: u32 ) -> Vec<u32>
+{
+    let mut rng = XorShiftRng::from_seed( [ seed, 2, 3, 4 ] );
+
+    ( 0..len ).map( | _ | if rng.gen_weighted_bool( 2 ) { rng.gen_range( 0, 20 ) } else { rng.gen_range( 0, 5000 ) } )
+              .collect()
+}
+
+#[ test ]
+///   Performs tests of the dimensions and of inserting and counting.
+fn test1()
+{
+    // A. Dimensions from the error bounds.
+    let test_bag: CountMinBag<u32> = CountMinBag::with_error( 0.001, 0.05 );
+    assert_eq!( test_bag.width(), 2719 );
+    assert_eq!( test_bag.depth(), 3 );
+    assert!( test_bag.is_empty() );
+
+    // B. Counts are exact while there are few values.
+    let mut test_bag = CountMinBag::new( 1000, 4 );
+
+    for &word in &[ "a", "b", "a", "c", "a" ]
+    {
+        test_bag.insert( word );
+    }
+
+    assert_eq!( test_bag.len(), 5 );
+    assert_eq!( test_bag.occurrences( "a" ), 3 );
+    assert_eq!( test_bag.occurrences( "c" ), 1 );
+    assert_eq!( test_bag.occurrences( "z" ), 0 );
+
+    // C. Clearing keeps the dimensions.
+    test_bag.clear();
+    assert!( test_bag.is_empty() );
+    assert_eq!( test_bag.occurrences( "a" ), 0 );
+    assert_eq!( test_bag, CountMinBag::new( 1000, 4 ) );
+}
+
+#[ test ]
+///   Performs tests of the error bound on a skewed stream.
+fn test2()
+{
+    let ( epsilon, delta ) = ( 0.002, 0.01 );
+    let stream = skewed_stream( 50_000, 1 );
+    let mut test_bag = CountMinBag::with_error( epsilon, delta );
+    let mut exact: HashMap<u32, usize> = HashMap::new();
+
+    for &value in &stream
+    {
+        test_bag.insert( value );
+        *exact.entry( value ).or_insert( 0 ) += 1;
+    }
+
+    let bound = epsilon * stream.len() as f64;
+    let mut beyond = 0;
+
+    for ( &value, &count ) in &exact
+    {
+        let estimate = test_bag.occurrences( value );
+
+        // A. Never an underestimate.
+        assert!( estimate >= count );
+
+        if ( estimate - count ) as f64 > bound
+        {
+            beyond += 1;
+        }
+    }
+
+    // B. At most a fraction delta of the values are beyond the bound,
+    //    with some slack for the randomness of the hashes.
+    assert!( ( beyond as f64 ) <= delta * exact.len() as f64 * 2.0 + 1.0,
+             "{} of {} values beyond the bound", beyond, exact.len() );
+}
+
+#[ test ]
+///   Performs tests of merging with += and +.
+fn test3()
+{
+    let first = skewed_stream( 2000, 5 );
+    let second = skewed_stream( 3000, 6 );
+
+    let mut left = CountMinBag::new( 200, 4 );
+    let mut right = CountMinBag::new( 200, 4 );
+    let mut both = CountMinBag::new( 200, 4 );
+
+    first.iter().for_each( | &value | { left.insert( value ); both.insert( value ); } );
+    second.iter().for_each( | &value | { right.insert( value ); both.insert( value ); } );
+
+    // A. Merging is the same as counting both streams.
+    assert_eq!( left.clone() + right.clone(), both );
+
+    left += right;
+    assert_eq!( left, both );
+    assert_eq!( left.len(), 5000 );
+}
+
+#[ test ]
+#[ should_panic ]
+///   Performs a test of merging sketches of different widths.
+fn test4()
+{
+    let mut left: CountMinBag<u32> = CountMinBag::new( 100, 4 );
+    left += CountMinBag::new( 200, 4 );
+}
+
+#[ test ]
+#[ should_panic( expected = "has too many counters" ) ]
+///   Performs a test of an error bound too small for the counters to be counted.
+fn test5()
+{
+    let _bag: CountMinBag<u32> = CountMinBag::with_error( 1e-300, 0.01 );
+}
@@ -0,0 +1,435 @@
+// FILE: bagcsv.rs
+
+//! Reading and writing bags as CSV.
+//!
+//! Two layouts are supported:
+//!
+//! * `Layout::Values` One element per row, so a value occurring three
+//! times is written in three rows
+//!
+//! * `Layout::Counts` `value,count` rows, with each distinct value in one row
+//!
+//! The generic `bag::Bag` is read for element types that implement
+//! `FromStr` and written for those that implement `Display`, and
+//! `bagf64::Bag` is read and written as numbers.
+//!
+//! Fields are quoted with `"`, and a `"` in a quoted field is written
+//! twice. Whitespace around unquoted fields and blank rows are ignored.
+//! Errors give the row, counted from 1 and including the header, at
+//! which the record with the problem starts.
+
+use bag::{ self, Bag };
+use bagf64::{ self, NumericCounts };
+use bagformat;
+use std::error::Error;
+use std::fmt::{ self, Display, Formatter };
+use std::hash::Hash;
+use std::io::{ self, Read, Write };
+use std::str::FromStr;
+
+/// How the elements of a bag are laid out in rows.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum Layout
+{
+    /// One element per row.
+    Values,
+    /// Each distinct value and its count per row.
+    Counts
+}
+
+/// The options for reading and writing CSV.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct CsvOptions
+{
+    /// The layout of the rows.
+    pub layout: Layout,
+    /// The character between fields, which may not be `"` or a line break.
+    pub delimiter: char,
+    /// Whether the first row is a header, which is skipped when
+    /// reading and written as `value` or `value,count`.
+    pub header: bool
+}
+
+impl Default for CsvOptions
+{
+    /// One value per row, separated by commas, with a header.
+
+    fn default() -> Self
+    {
+        CsvOptions { layout: Layout::Values, delimiter: ',', header: true }
+    }
+}
+
+/// The problem found reading CSV.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum CsvErrorKind
+{
+    /// The input could not be read, with the reason.
+    Io( String ),
+    /// The delimiter is `"` or a line break.
+    InvalidDelimiter( char ),
+    /// A quoted field is not closed before the end of the input.
+    UnterminatedQuote,
+    /// There is text between the closing quote of a field and the delimiter.
+    TextAfterQuote,
+    /// A row has the wrong number of fields for the layout.
+    FieldCount { expected: usize, found: usize },
+    /// A field could not be read as an element, with the error of the element type.
+    InvalidValue( String, String ),
+    /// A count is not a non-negative integer.
+    InvalidCount( String ),
+    /// The rows hold more than `bagformat::MAX_ELEMENTS` elements in all.
+    TooManyElements
+}
+
+/// An error reading CSV, with the row at which it was found or 0 if
+/// it is not in any row.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct CsvError
+{
+    pub row: usize,
+    pub kind: CsvErrorKind
+}
+
+impl Display for CsvError
+{
+    /// Describes the error and the row where it was found.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        if self.row > 0
+        {
+            write!( fmt, "row {}: ", self.row )?;
+        }
+
+        match self.kind
+        {
+            CsvErrorKind::Io( ref reason ) => write!( fmt, "could not read the CSV: {}", reason ),
+            CsvErrorKind::InvalidDelimiter( delimiter ) => write!( fmt, "{:?} cannot be a delimiter", delimiter ),
+            CsvErrorKind::UnterminatedQuote => write!( fmt, "a quoted field is not closed" ),
+            CsvErrorKind::TextAfterQuote => write!( fmt, "unexpected text after a quoted field" ),
+            CsvErrorKind::FieldCount { expected, found } =>
+                write!( fmt, "expected {} field(s) but found {}", expected, found ),
+            CsvErrorKind::InvalidValue( ref field, ref error ) =>
+                write!( fmt, "`{}` is not a valid value: {}", field, error ),
+            CsvErrorKind::InvalidCount( ref field ) => write!( fmt, "`{}` is not a valid count", field ),
+            CsvErrorKind::TooManyElements => write!( fmt, "the bag would hold too many elements" )
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+/// One row of CSV, with the row at which it starts.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct Record
+{
+    pub row: usize,
+    pub fields: Vec<String>
+}
+
+/// Split CSV text into records.
+///
+/// # Return
+/// The records that are not blank, or an Err with the row of the
+/// first malformed record.
+
+pub fn parse_records( text: &str, delimiter: char ) -> Result<Vec<Record>, CsvError>
+{
+    if delimiter == '"' || delimiter == '\n' || delimiter == '\r'
+    {
+        return Err( CsvError { row: 0, kind: CsvErrorKind::InvalidDelimiter( delimiter ) } );
+    }
+
+    let is_space = | c: char | c != delimiter && c != '\n' && c.is_whitespace();
+    let mut chars = text.chars().peekable();
+    let mut records = Vec::new();
+    let mut line = 1;
+
+    while chars.peek().is_some()
+    {
+        let row = line;
+        let mut fields = Vec::new();
+        let mut quoted = false;
+
+        loop
+        {
+            let mut field = String::new();
+
+            while chars.peek().map( | &c | is_space( c ) ).unwrap_or( false )
+            {
+                chars.next();
+            }
+
+            if chars.peek() == Some( &'"' )
+            {
+                chars.next();
+                quoted = true;
+
+                loop
+                {
+                    match chars.next()
+                    {
+                        None => return Err( CsvError { row, kind: CsvErrorKind::UnterminatedQuote } ),
+                        Some( '"' ) if chars.peek() == Some( &'"' ) =>
+                            {
+                                chars.next();
+                                field.push( '"' );
+                            }
+                        Some( '"' ) => break,
+                        Some( c ) =>
+                            {
+                                if c == '\n'
+                                {
+                                    line += 1;
+                                }
+
+                                field.push( c );
+                            }
+                    }
+                }
+
+                while chars.peek().map( | &c | is_space( c ) ).unwrap_or( false )
+                {
+                    chars.next();
+                }
+
+                match chars.peek()
+                {
+                    Some( &c ) if c != delimiter && c != '\n' =>
+                        return Err( CsvError { row: line, kind: CsvErrorKind::TextAfterQuote } ),
+                    _ => {}
+                }
+            }
+            else
+            {
+                while let Some( &c ) = chars.peek()
+                {
+                    if c == delimiter || c == '\n'
+                    {
+                        break;
+                    }
+
+                    field.push( c );
+                    chars.next();
+                }
+
+                field = field.trim().to_string();
+            }
+
+            fields.push( field );
+
+            match chars.next()
+            {
+                Some( '\n' ) =>
+                    {
+                        line += 1;
+                        break;
+                    }
+                Some( _ ) => {}
+                None => break
+            }
+        }
+
+        if quoted || fields.len() > 1 || !fields[ 0 ].is_empty()
+        {
+            records.push( Record { row, fields } );
+        }
+    }
+
+    Ok( records )
+}
+
+// Reads the values and counts of the records of `input` as
+// laid out by `options`, passing each to `insert`, and fails
+// before passing a count that takes the elements past `limit`.
+fn read_values<Value, Input, Insert>( input: &mut Input, options: &CsvOptions, limit: usize, mut insert: Insert )
+    -> Result<(), CsvError>
+    where Value: FromStr, Value::Err: Display, Input: Read, Insert: FnMut( Value, usize )
+{
+    let mut text = String::new();
+
+    input.read_to_string( &mut text )
+         .map_err( | error | CsvError { row: 0, kind: CsvErrorKind::Io( error.to_string() ) } )?;
+
+    let expected = match options.layout
+    {
+        Layout::Values => 1,
+        Layout::Counts => 2
+    };
+
+    let records = parse_records( &text, options.delimiter )?;
+    let skipped = if options.header { 1 } else { 0 };
+    let mut total = 0usize;
+
+    for record in records.into_iter().skip( skipped )
+    {
+        let row = record.row;
+        let error = | kind | CsvError { row, kind };
+
+        if record.fields.len() != expected
+        {
+            return Err( error( CsvErrorKind::FieldCount { expected, found: record.fields.len() } ) );
+        }
+
+        let value = record.fields[ 0 ].parse::<Value>().map_err( | reason |
+            error( CsvErrorKind::InvalidValue( record.fields[ 0 ].clone(), reason.to_string() ) ) )?;
+
+        let count = match options.layout
+        {
+            Layout::Values => 1,
+            Layout::Counts => record.fields[ 1 ].parse::<usize>()
+                                    .map_err( | _ | error( CsvErrorKind::InvalidCount( record.fields[ 1 ].clone() ) ) )?
+        };
+
+        total = match total.checked_add( count )
+        {
+            Some( total ) if total <= limit => total,
+            _ => return Err( error( CsvErrorKind::TooManyElements ) )
+        };
+
+        insert( value, count );
+    }
+
+    Ok( () )
+}
+
+/// Read a bag from CSV.
+///
+/// # Return
+/// A bag holding the values of the rows in the order they are read,
+/// or an Err with the row of the first problem found, which is
+/// `TooManyElements` at the row whose count takes the bag past
+/// `bagformat::MAX_ELEMENTS` elements.
+///
+/// # Aborts
+/// OOM: Insufficient memory for allocating the bag.
+
+pub fn read_bag<Type, Input: Read>( input: &mut Input, options: &CsvOptions ) -> Result<Bag<Type>, CsvError>
+    where Type: PartialEq + Clone + Default + FromStr, Type::Err: Display
+{
+    let mut bag = Bag::new();
+
+    read_values( input, options, bagformat::MAX_ELEMENTS, | value: Type, count |
+        {
+            for _ in 0..count
+            {
+                bag.insert( value.clone() );
+            }
+        } )?;
+
+    Ok( bag )
+}
+
+/// Read a bag of numbers from CSV.
+///
+/// # Return
+/// A bag holding the numbers of the rows in the order they are read,
+/// or an Err with the row of the first problem found, which is
+/// `TooManyElements` at the row whose count takes the bag past
+/// `bagformat::MAX_ELEMENTS` elements.
+///
+/// # Aborts
+/// OOM: Insufficient memory for allocating the bag.
+
+pub fn read_f64_bag<Input: Read>( input: &mut Input, options: &CsvOptions ) -> Result<bagf64::Bag, CsvError>
+{
+    let mut bag = bagf64::Bag::new();
+
+    read_values( input, options, bagformat::MAX_ELEMENTS, | value: f64, count |
+        {
+            for _ in 0..count
+            {
+                bag.insert( value );
+            }
+        } )?;
+
+    Ok( bag )
+}
+
+/// Quote a CSV field if it contains the delimiter, a quote or a line
+/// break, starts or ends with whitespace, or is empty so that it is
+/// not read as a blank row.
+
+pub fn field( text: &str, delimiter: char ) -> String
+{
+    if text.contains( &[ delimiter, '"', '\n', '\r' ][ .. ] ) || text.trim() != text || text.is_empty()
+    {
+        format!( "\"{}\"", text.replace( '"', "\"\"" ) )
+    }
+    else
+    {
+        text.to_string()
+    }
+}
+
+// Writes the header, if any, and a row for each value and count.
+fn write_rows<Output, Rows>( output: &mut Output, options: &CsvOptions, rows: Rows ) -> io::Result<()>
+    where Output: Write, Rows: Iterator<Item = ( String, usize )>
+{
+    let delimiter = options.delimiter;
+
+    if options.header
+    {
+        match options.layout
+        {
+            Layout::Values => writeln!( output, "value" )?,
+            Layout::Counts => writeln!( output, "value{}count", delimiter )?
+        }
+    }
+
+    for ( value, count ) in rows
+    {
+        match options.layout
+        {
+            Layout::Values => writeln!( output, "{}", field( &value, delimiter ) )?,
+            Layout::Counts => writeln!( output, "{}{}{}", field( &value, delimiter ), delimiter, count )?
+        }
+    }
+
+    Ok( () )
+}
+
+/// Write a bag as CSV.
+///
+/// # Postcondition
+/// The bag is not altered. The rows are in the order of the elements
+/// for `Layout::Values`, and of the first occurrence of each value
+/// for `Layout::Counts`.
+///
+/// # Return
+/// An Err if writing to `output` failed.
+
+pub fn write_bag<Type, Output: Write>( output: &mut Output, bag: &Bag<Type>, options: &CsvOptions ) -> io::Result<()>
+    where Type: PartialEq + Clone + Default + Display + Hash + Eq
+{
+    match options.layout
+    {
+        Layout::Values => write_rows( output, options, bag.into_iter().map( | value | ( value.to_string(), 1 ) ) ),
+        Layout::Counts => write_rows( output, options, bag.counts().map( | ( value, count ) | ( value.to_string(), count ) ) )
+    }
+}
+
+/// Write a bag of numbers as CSV.
+///
+/// # Postcondition
+/// The bag is not altered. The rows are in the order of the elements
+/// for `Layout::Values`, and of the first occurrence of each value
+/// for `Layout::Counts`.
+///
+/// # Return
+/// An Err if writing to `output` failed.
+
+pub fn write_f64_bag<Output: Write>( output: &mut Output, bag: &bagf64::Bag, options: &CsvOptions ) -> io::Result<()>
+{
+    match options.layout
+    {
+        Layout::Values => write_rows( output, options, bag.into_iter().map( | value | ( value.to_string(), 1 ) ) ),
+        Layout::Counts => write_rows( output, options, bag.counts().map( | ( value, count ) | ( value.to_string(), count ) ) )
+    }
+}
@@ -0,0 +1,147 @@
+// FILE: bagcsvexam.rs
+
+//! This module holds tests for reading and writing bags as CSV with bagcsv
+//!
+//! 1) tests of splitting CSV text into records
+//!
+//! 2) tests of reading both layouts
+//!
+//! 3) tests of the errors reading malformed CSV
+//!
+//! 4) tests of writing both layouts and reading them back
+//!
+//! Run with `cargo test`
+
+use bag::Bag;
+use bag::len_trait::Len;
+use bagcsv::{ CsvOptions, CsvError, CsvErrorKind, Layout, Record, parse_records, read_bag, read_f64_bag,
+              write_bag, write_f64_bag };
+
+fn counts_options() -> CsvOptions
+{
+    CsvOptions { layout: Layout::Counts, .. CsvOptions::default() }
+}
+
+fn record( row: usize, fields: &[&str] ) -> Record
+{
+    Record { row, fields: fields.iter().map( | field | field.to_string() ).collect() }
+}
+
+#[ test ]
+///   Performs tests of splitting CSV text into records.
+fn test1()
+{
+    // A. Whitespace around unquoted fields and blank rows are ignored.
+    assert_eq!( parse_records( " a , b\r\n\n  \nc,d", ',' ),
+                Ok( vec![ record( 1, &[ "a", "b" ] ), record( 4, &[ "c", "d" ] ) ] ) );
+
+    // B. Quoted fields keep their text, including delimiters, quotes and line breaks.
+    assert_eq!( parse_records( "\" a,\"\"b\"\"\n\",1\nx, \"\" \n", ',' ),
+                Ok( vec![ record( 1, &[ " a,\"b\"\n", "1" ] ), record( 3, &[ "x", "" ] ) ] ) );
+
+    // C. Other delimiters.
+    assert_eq!( parse_records( "a;b\tc\n", ';' ), Ok( vec![ record( 1, &[ "a", "b\tc" ] ) ] ) );
+    assert_eq!( parse_records( "a\t b\n", '\t' ), Ok( vec![ record( 1, &[ "a", "b" ] ) ] ) );
+
+    // D. Empty text has no records.
+    assert_eq!( parse_records( "", ',' ), Ok( Vec::new() ) );
+}
+
+#[ test ]
+///   Performs tests of reading both layouts.
+fn test2()
+{
+    // A. One value per row, after a header.
+    let bag: Bag<String> = read_bag( &mut "word\nb\na\nb\n".as_bytes(), &CsvOptions::default() ).unwrap();
+    assert_eq!( bag.into_iter().cloned().collect::<Vec<String>>(), vec![ "b", "a", "b" ] );
+
+    // B. Values and counts, without a header.
+    let options = CsvOptions { header: false, delimiter: ';', .. counts_options() };
+    let bag: Bag<i32> = read_bag( &mut "7;2\n-1;1\n3;0\n".as_bytes(), &options ).unwrap();
+    assert_eq!( bag.into_iter().cloned().collect::<Vec<i32>>(), vec![ 7, 7, -1 ] );
+
+    // C. Numbers into a bagf64.
+    let bag = read_f64_bag( &mut "value,count\n1.5,3\n-2e3,1\n".as_bytes(), &counts_options() ).unwrap();
+    assert_eq!( bag.len(), 4 );
+    assert_eq!( bag.occurrences( 1.5 ), 3 );
+    assert_eq!( bag.occurrences( -2000.0 ), 1 );
+
+    // D. A header alone is an empty bag.
+    assert_eq!( read_f64_bag( &mut "value\n".as_bytes(), &CsvOptions::default() ).unwrap().len(), 0 );
+}
+
+#[ test ]
+///   Performs tests of the errors reading malformed CSV.
+fn test3()
+{
+    let error = | text: &str, options: &CsvOptions | read_bag::<i32, _>( &mut text.as_bytes(), options ).unwrap_err();
+
+    // A. The wrong number of fields.
+    let found = error( "value\n1\n2,3\n", &CsvOptions::default() );
+    assert_eq!( found.row, 3 );
+    assert_eq!( found.kind, CsvErrorKind::FieldCount { expected: 1, found: 2 } );
+    assert_eq!( found.to_string(), "row 3: expected 1 field(s) but found 2" );
+
+    // B. Invalid values and counts.
+    let found = error( "value,count\n\n1,2\nx,1\n", &counts_options() );
+    assert_eq!( found.row, 4 );
+    assert_eq!( found.to_string(), "row 4: `x` is not a valid value: invalid digit found in string" );
+    assert_eq!( error( "value,count\n1,-2\n", &counts_options() ).kind, CsvErrorKind::InvalidCount( "-2".to_string() ) );
+
+    // C. Counts past the elements a bag may hold.
+    let found = error( "value,count\n7,18446744073709551615\n", &counts_options() );
+    assert_eq!( found.row, 2 );
+    assert_eq!( found.to_string(), "row 2: the bag would hold too many elements" );
+
+    let text = format!( "1,0\n2,{}\n", ::bagformat::MAX_ELEMENTS + 1 );
+    let options = CsvOptions { header: false, .. counts_options() };
+    assert_eq!( error( &text, &options ), CsvError { row: 2, kind: CsvErrorKind::TooManyElements } );
+
+    // D. Malformed quotes.
+    let found = error( "value\n1\n\"2\n3\n", &CsvOptions::default() );
+    assert_eq!( ( found.row, found.kind ), ( 3, CsvErrorKind::UnterminatedQuote ) );
+    assert_eq!( error( "\"1\"2\n", &CsvOptions::default() ).kind, CsvErrorKind::TextAfterQuote );
+
+    // E. Invalid delimiters.
+    let options = CsvOptions { delimiter: '"', .. CsvOptions::default() };
+    assert_eq!( error( "1\n", &options ).kind, CsvErrorKind::InvalidDelimiter( '"' ) );
+}
+
+#[ test ]
+///   Performs tests of writing both layouts and reading them back.
+fn test4()
+{
+    let mut bag = Bag::new();
+
+    for word in &[ "b", " a", "b", "x,\"y\"", "b" ]
+    {
+        bag.insert( word.to_string() );
+    }
+
+    // A. One value per row.
+    let mut output = Vec::new();
+    write_bag( &mut output, &bag, &CsvOptions::default() ).unwrap();
+    assert_eq!( String::from_utf8( output.clone() ).unwrap(), "value\nb\n\" a\"\nb\n\"x,\"\"y\"\"\"\nb\n" );
+    assert_eq!( read_bag( &mut &output[ .. ], &CsvOptions::default() ), Ok( bag.clone() ) );
+
+    // B. Values and counts with another delimiter.
+    let options = CsvOptions { delimiter: '\t', .. counts_options() };
+    let mut output = Vec::new();
+    write_bag( &mut output, &bag, &options ).unwrap();
+    assert_eq!( String::from_utf8( output.clone() ).unwrap(), "value\tcount\nb\t3\n\" a\"\t1\n\"x,\"\"y\"\"\"\t1\n" );
+
+    let read: Bag<String> = read_bag( &mut &output[ .. ], &options ).unwrap();
+    assert_eq!( read.into_sorted_counts(), bag.into_sorted_counts() );
+
+    // C. Numbers, without a header.
+    let mut numbers = ::bagf64::Bag::new();
+    numbers.insert( 0.1 );
+    numbers.insert( -3.0 );
+    numbers.insert( 0.1 );
+
+    let options = CsvOptions { header: false, .. counts_options() };
+    let mut output = Vec::new();
+    write_f64_bag( &mut output, &numbers, &options ).unwrap();
+    assert_eq!( String::from_utf8( output.clone() ).unwrap(), "0.1,2\n-3,1\n" );
+    assert_eq!( read_f64_bag( &mut &output[ .. ], &options ).unwrap().occurrences( 0.1 ), 2 );
+}
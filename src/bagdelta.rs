@@ -0,0 +1,392 @@
+// FILE: bagdelta.rs
+
+//! The difference between two bags as a change in the count of each
+//! value, so a large bag can be updated by sending only what changed.
+//!
+//! `diff( &from, &to )` gives the `BagDelta` that turns `from` into
+//! `to`, counting the copies of each value as a multiset does: the
+//! order of the elements, which `==` on the bags compares, is not part
+//! of a delta. A delta can be applied to any bag with `apply( _ )` of
+//! `BagOps`, composed with the delta that follows it, and inverted.
+//!
+//! A delta is written and read in the text format of bagformat, with
+//! each value signed by whether copies of it are inserted or removed:
+//!
+//! ```text
+//! {+a×3, -b, +c}
+//! ```
+//!
+//! The values of a delta need `Hash` and `Eq`, which `f64` and `f32`
+//! lack. A delta between bags of floating point numbers is made between
+//! their keys of bagtotal, and applied with `apply_keys( _, _ )`.
+
+use bagformat::{ self, ParseBagError, ParseBagErrorKind };
+use bagtrait::BagOps;
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{ self, Debug, Display, Formatter };
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// The change in the count of each value from one bag to another.
+///
+/// # Invariant of the BagDelta struct:
+/// 1. No value in `changes` has a change of 0.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct BagDelta<Type: Hash + Eq>
+{
+    changes: HashMap<Type, isize>
+}
+
+/// An error applying a delta that removes more copies of a value
+/// than the bag holds.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct ApplyDeltaError<Type>
+{
+    pub value: Type,
+    pub removed: usize,
+    pub present: usize
+}
+
+impl<Type: Display> Display for ApplyDeltaError<Type>
+{
+    /// Describes the value that could not be removed.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "cannot remove {} copies of `{}` from a bag holding {}",
+                self.removed, self.value, self.present )
+    }
+}
+
+impl<Type: Display + Debug> Error for ApplyDeltaError<Type> {}
+
+/// The delta that turns one bag into another.
+///
+/// # Parameter: `from`
+/// The bag the delta is applied to.
+///
+/// # Parameter: `to`
+/// The bag the delta makes of `from`.
+///
+/// # Return
+/// The change in the count of each value whose count differs.
+///
+/// # Examples
+/// ```
+/// let mut from = Bag::new();
+/// from.insert( 1 );
+/// from.insert( 2 );
+///
+/// let mut to = Bag::new();
+/// to.insert( 2 );
+/// to.insert( 3 );
+/// to.insert( 3 );
+///
+/// let delta = diff( &from, &to );
+/// assert_eq!( delta.change( &1 ), -1 );
+/// assert_eq!( delta.change( &3 ), 2 );
+///
+/// from.apply( &delta ).unwrap();
+/// assert_eq!( from.occurrences( 3 ), 2 );
+/// ```
+
+pub fn diff<Bag>( from: &Bag, to: &Bag ) -> BagDelta<Bag::Item>
+    where Bag: BagOps, Bag::Item: Hash + Eq
+{
+    let mut delta = BagDelta::new();
+
+    for value in from.iter()
+    {
+        delta.add_change( value.clone(), -1 );
+    }
+
+    for value in to.iter()
+    {
+        delta.add_change( value.clone(), 1 );
+    }
+
+    delta
+}
+
+impl<Type: Hash + Eq + Clone> BagDelta<Type>
+{
+    /// Initialize a delta that changes nothing.
+
+    pub fn new() -> Self
+    {
+        BagDelta { changes: HashMap::new() }
+    }
+
+    /// Whether the delta changes nothing.
+
+    pub fn is_empty( &self ) -> bool
+    {
+        self.changes.is_empty()
+    }
+
+    /// The number of values whose counts the delta changes.
+
+    pub fn len( &self ) -> usize
+    {
+        self.changes.len()
+    }
+
+    /// The change in the count of `value`, negative if copies are removed.
+
+    pub fn change( &self, value: &Type ) -> isize
+    {
+        self.changes.get( value ).cloned().unwrap_or( 0 )
+    }
+
+    /// Iterate over each value the delta changes, with its change,
+    /// in no particular order.
+
+    pub fn iter( &self ) -> hash_map::Iter<'_, Type, isize>
+    {
+        self.changes.iter()
+    }
+
+    /// Add `change` to the change in the count of `value`.
+    ///
+    /// # Postcondition
+    /// A value whose change becomes 0 is no longer in the delta.
+    ///
+    /// # Panics
+    /// If the change would overflow an isize.
+
+    pub fn add_change( &mut self, value: Type, change: isize )
+    {
+        if change == 0
+        {
+            return;
+        }
+
+        match self.changes.entry( value )
+        {
+            hash_map::Entry::Vacant( entry ) => { entry.insert( change ); }
+            hash_map::Entry::Occupied( mut entry ) =>
+                {
+                    *entry.get_mut() = entry.get().checked_add( change ).expect( "change overflowed" );
+
+                    if *entry.get() == 0
+                    {
+                        entry.remove();
+                    }
+                }
+        }
+    }
+
+    /// The delta of applying this delta and then `next`.
+    ///
+    /// # Postcondition
+    /// Neither delta is altered.
+
+    pub fn compose( &self, next: &BagDelta<Type> ) -> BagDelta<Type>
+    {
+        let mut composed = self.clone();
+
+        for ( value, &change ) in next.iter()
+        {
+            composed.add_change( value.clone(), change );
+        }
+
+        composed
+    }
+
+    /// The delta that undoes this one, turning the bag it makes back
+    /// into the bag it was applied to.
+
+    pub fn invert( &self ) -> BagDelta<Type>
+    {
+        BagDelta { changes: self.changes.iter().map( | ( value, &change ) | ( value.clone(), -change ) ).collect() }
+    }
+
+    /// Apply the delta to a bag, inserting copies of the values whose
+    /// counts rise at its end and erasing copies of those whose counts fall.
+    ///
+    /// # Parameter: `bag`
+    /// Any bag of the values of the delta.
+    ///
+    /// # Postcondition
+    /// If the bag holds enough copies of each value removed, the count
+    /// of each value has changed by its change, and the elements kept
+    /// are in the same order. Otherwise the bag is not altered.
+    ///
+    /// # Return
+    /// An Err naming a value of which the delta removes more copies
+    /// than the bag holds.
+    ///
+    /// # Note
+    /// The bag is counted in one pass and, if the delta removes any
+    /// copies, rebuilt in one more, so the time taken is linear in the
+    /// size of the bag and of the changes.
+
+    pub fn apply_to<Bag>( &self, bag: &mut Bag ) -> Result<(), ApplyDeltaError<Type>>
+        where Bag: BagOps<Item = Type>
+    {
+        self.apply_found( bag, | element | self.changes.get_key_value( element ).map( | ( value, _ ) | value ),
+                          Type::clone )
+    }
+
+    /// Apply the delta to a bag whose elements are counted by their
+    /// keys, such as a bag of `f64` by the keys of bagtotal, which
+    /// have the `Hash` and `Eq` a delta needs.
+    ///
+    /// # Parameter: `bag`
+    /// Any bag of elements that the values of the delta are keys of.
+    ///
+    /// # Parameter: `key`
+    /// The key of an element.
+    ///
+    /// # Postcondition
+    /// As for `apply_to( _ )`, with each element counted as its key,
+    /// and each copy inserted made from its key.
+    ///
+    /// # Return
+    /// An Err naming a key of which the delta removes more elements
+    /// than the bag holds.
+
+    pub fn apply_by<Bag, Key>( &self, bag: &mut Bag, key: Key ) -> Result<(), ApplyDeltaError<Type>>
+        where Bag: BagOps, Key: Fn( &Bag::Item ) -> Type, Type: Into<Bag::Item>
+    {
+        self.apply_found( bag, | element | self.changes.get_key_value( &key( element ) ).map( | ( value, _ ) | value ),
+                          | value | value.clone().into() )
+    }
+
+    // Applies the delta to `bag`, with `find` giving the value of the
+    // delta an element counts as, if any, and `make` an element of a value.
+    fn apply_found<'a, Bag, Find, Make>( &'a self, bag: &mut Bag, find: Find, make: Make )
+        -> Result<(), ApplyDeltaError<Type>>
+        where Bag: BagOps, Find: Fn( &Bag::Item ) -> Option<&'a Type>, Make: Fn( &Type ) -> Bag::Item
+    {
+        let mut removing: HashMap<&Type, usize> = self.changes.iter()
+                                                      .filter( | &( _, &change ) | change < 0 )
+                                                      .map( | ( value, &change ) | ( value, change.unsigned_abs() ) )
+                                                      .collect();
+
+        if !removing.is_empty()
+        {
+            let mut present: HashMap<&Type, usize> = HashMap::new();
+
+            for value in bag.iter().filter_map( &find )
+            {
+                *present.entry( value ).or_insert( 0 ) += 1;
+            }
+
+            for ( &value, &removed ) in &removing
+            {
+                let present = present.get( value ).cloned().unwrap_or( 0 );
+
+                if present < removed
+                {
+                    return Err( ApplyDeltaError { value: value.clone(), removed, present } );
+                }
+            }
+
+            let kept: Vec<Bag::Item> = bag.iter()
+                                          .filter( | &element | match find( element ).and_then( | value | removing.get_mut( value ) )
+                                              {
+                                                  Some( count ) if *count > 0 => { *count -= 1; false }
+                                                  _                           => true
+                                              } )
+                                          .cloned()
+                                          .collect();
+
+            bag.clear();
+
+            for element in kept
+            {
+                bag.insert( element );
+            }
+        }
+
+        for ( value, &change ) in self.iter().filter( | &( _, &change ) | change > 0 )
+        {
+            for _ in 0..change
+            {
+                bag.insert( make( value ) );
+            }
+        }
+
+        Ok( () )
+    }
+}
+
+impl<Type: Hash + Eq + Clone> Default for BagDelta<Type>
+{
+    fn default() -> Self
+    {
+        BagDelta::new()
+    }
+}
+
+// A value in the text of a delta, signed by whether it is inserted.
+struct Signed<Value>( bool, Value );
+
+impl<Value: Display> Display for Signed<Value>
+{
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "{}{}", if self.0 { '+' } else { '-' }, self.1 )
+    }
+}
+
+impl<Value: FromStr> FromStr for Signed<Value>
+    where Value::Err: Display
+{
+    type Err = String;
+
+    fn from_str( text: &str ) -> Result<Self, String>
+    {
+        let inserted = match text.chars().next()
+        {
+            Some( '+' ) => true,
+            Some( '-' ) => false,
+            _           => return Err( "expected `+` or `-` before the value".to_string() )
+        };
+
+        text[ 1.. ].parse::<Value>().map( | value | Signed( inserted, value ) )
+                                    .map_err( | error | error.to_string() )
+    }
+}
+
+impl<Type: Hash + Eq + Clone + Display> Display for BagDelta<Type>
+{
+    /// Renders the delta in the text format, each value signed by
+    /// whether it is inserted or removed, in no particular order.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        bagformat::write_runs( fmt, self.iter().map( | ( value, &change ) |
+                                        ( Signed( change > 0, value ), change.unsigned_abs() ) ) )
+    }
+}
+
+impl<Type: Hash + Eq + Clone + FromStr> FromStr for BagDelta<Type>
+    where Type::Err: Display
+{
+    type Err = ParseBagError;
+
+    /// Reads a delta in the text format. Changes to the same value are
+    /// added together.
+
+    fn from_str( text: &str ) -> Result<Self, ParseBagError>
+    {
+        let mut delta = BagDelta::new();
+
+        for ( Signed( inserted, value ), count ) in bagformat::parse_runs::<Signed<Type>>( text )?
+        {
+            let change = isize::try_from( count ).map_err( | _ |
+                ParseBagError { column: 1, kind: ParseBagErrorKind::TooManyElements } )?;
+
+            delta.add_change( value, if inserted { change } else { -change } );
+        }
+
+        Ok( delta )
+    }
+}
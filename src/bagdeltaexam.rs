@@ -0,0 +1,200 @@
+// FILE: bagdeltaexam.rs
+
+//! This module holds tests for the deltas between bags of bagdelta
+//!
+//! 1) tests of diff( _, _ ) and apply( _ ) on each kind of bag, and of apply_keys( _, _ )
+//!
+//! 2) tests of composing and inverting deltas
+//!
+//! 3) tests of applying a delta that removes too much
+//!
+//! 4) tests of the text format of deltas
+//!
+//! 5) random bags turned into one another by their deltas
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::Bag;
+use bagdelta::{ diff, BagDelta, ApplyDeltaError };
+use bagformat::ParseBagErrorKind;
+use bagtotal::KeyPolicy;
+use bagtrait::BagOps;
+
+// A bag of random values from 0 up to `values`.
+fn random_bag( rng: &mut XorShiftRng, values: i32 ) -> Bag<i32>
+{
+    let mut bag = Bag::new();
+
+    for _ in 0..rng.gen_range( 0, 30 )
+    {
+        bag.insert( rng.gen_range( 0, values ) );
+    }
+
+    bag
+}
+
+#[ test ]
+///   Performs tests of diff( _, _ ) and apply( _ ) on each kind of bag,
+///   and on bags of numbers through their keys.
+fn test1()
+{
+    let from: Bag<i32> = [ 1, 2, 2, 3 ].iter().cloned().collect();
+    let to: Bag<i32> = [ 3, 2, 4, 4, 4 ].iter().cloned().collect();
+
+    // A. The change in the count of each value.
+    let delta = diff( &from, &to );
+    assert_eq!( delta.len(), 3 );
+    assert_eq!( ( delta.change( &1 ), delta.change( &2 ), delta.change( &3 ), delta.change( &4 ) ), ( -1, -1, 0, 3 ) );
+
+    // B. Applying it gives the counts of the bag diffed to.
+    let mut applied = from.clone();
+    applied.apply( &delta ).unwrap();
+    assert!( diff( &applied, &to ).is_empty() );
+
+    // C. Bags of the same counts in another order have an empty delta.
+    let shuffled: Bag<i32> = [ 4, 3, 4, 2, 4 ].iter().cloned().collect();
+    assert!( shuffled != to );
+    assert!( diff( &shuffled, &to ).is_empty() );
+    assert_eq!( diff( &to, &to ), BagDelta::new() );
+
+    // D. The same delta applies to a bag of another kind.
+    let mut vec_bag: ::bagVec::Bag<i32> = [ 2, 1, 2, 3 ].iter().cloned().collect();
+    vec_bag.apply( &delta ).unwrap();
+    assert!( diff( &vec_bag, &[ 2, 3, 4, 4, 4 ].iter().cloned().collect() ).is_empty() );
+
+    // E. The elements kept are in the same order, with those inserted after them.
+    let mut test_bag: Bag<i32> = [ 5, 2, 1, 3, 2, 6 ].iter().cloned().collect();
+    test_bag.apply( &delta ).unwrap();
+    assert_eq!( test_bag.iter().cloned().collect::<Vec<_>>(), vec![ 5, 3, 2, 6, 4, 4, 4 ] );
+
+    // F. A bag of numbers takes the delta between the keys of two bags.
+    let from: Bag<f64> = [ 0.5, -0.0, f64::NAN, 2.0 ].iter().cloned().collect();
+    let to: Bag<f64> = [ 2.0, 0.0, 0.0, 1.5 ].iter().cloned().collect();
+    let policy = KeyPolicy::default();
+    let delta = diff( &from.to_keys( policy ), &to.to_keys( policy ) );
+
+    let mut applied = from.clone();
+    applied.apply_keys( &delta, policy ).unwrap();
+    assert!( diff( &applied.to_keys( policy ), &to.to_keys( policy ) ).is_empty() );
+    assert_eq!( applied.occurrences( 0.0 ), 2 );
+    assert!( !applied.iter().any( | value | value.is_nan() ) );
+
+    let error = from.clone().apply_keys( &delta.compose( &delta ), policy ).unwrap_err();
+    assert_eq!( error.removed, 2 );
+}
+
+#[ test ]
+///   Performs tests of composing and inverting deltas.
+fn test2()
+{
+    let first: Bag<i32> = [ 1, 1, 2 ].iter().cloned().collect();
+    let second: Bag<i32> = [ 2, 3 ].iter().cloned().collect();
+    let third: Bag<i32> = [ 1, 3, 3, 3 ].iter().cloned().collect();
+
+    // A. Composing the deltas of each step gives the delta of both.
+    let composed = diff( &first, &second ).compose( &diff( &second, &third ) );
+    assert_eq!( composed, diff( &first, &third ) );
+    assert_eq!( composed.change( &2 ), -1 );
+    assert_eq!( diff( &first, &second ).compose( &BagDelta::new() ), diff( &first, &second ) );
+
+    // B. The inverse turns the bag back.
+    let delta = diff( &first, &third );
+    assert_eq!( delta.invert(), diff( &third, &first ) );
+    assert!( delta.compose( &delta.invert() ).is_empty() );
+
+    let mut test_bag = first.clone();
+    test_bag.apply( &delta ).unwrap();
+    test_bag.apply( &delta.invert() ).unwrap();
+    assert!( diff( &test_bag, &first ).is_empty() );
+
+    // C. Changes added to a value that cancel remove it.
+    let mut delta = BagDelta::new();
+    delta.add_change( "a", 2 );
+    delta.add_change( "a", -2 );
+    delta.add_change( "b", 0 );
+    assert!( delta.is_empty() );
+}
+
+#[ test ]
+///   Performs tests of applying a delta that removes too much.
+fn test3()
+{
+    let mut delta = BagDelta::new();
+    delta.add_change( 1, 5 );
+    delta.add_change( 2, -3 );
+
+    // A. The bag is not altered.
+    let mut test_bag: Bag<i32> = [ 2, 2, 7 ].iter().cloned().collect();
+    let error = test_bag.apply( &delta ).unwrap_err();
+
+    assert_eq!( error, ApplyDeltaError { value: 2, removed: 3, present: 2 } );
+    assert_eq!( error.to_string(), "cannot remove 3 copies of `2` from a bag holding 2" );
+    assert_eq!( test_bag, [ 2, 2, 7 ].iter().cloned().collect() );
+
+    // B. With enough copies it applies.
+    test_bag.insert( 2 );
+    test_bag.apply( &delta ).unwrap();
+    assert!( diff( &test_bag, &[ 7, 1, 1, 1, 1, 1 ].iter().cloned().collect() ).is_empty() );
+}
+
+#[ test ]
+///   Performs tests of the text format of deltas.
+fn test4()
+{
+    let from: Bag<i32> = [ 1, 2, 2 ].iter().cloned().collect();
+    let to: Bag<i32> = [ 3, 3, 3, 2 ].iter().cloned().collect();
+    let delta = diff( &from, &to );
+
+    // A. Written and read back.
+    let text = delta.to_string();
+    assert!( text.contains( "-1" ) && text.contains( "-2" ) && text.contains( "+3×3" ) );
+    assert_eq!( text.parse::<BagDelta<i32>>(), Ok( delta ) );
+
+    // B. Written values are escaped, and changes to a value are added.
+    let mut delta = BagDelta::new();
+    delta.add_change( "a, b".to_string(), 2 );
+    assert_eq!( delta.to_string(), "{+a\\, b×2}" );
+    assert_eq!( delta.to_string().parse::<BagDelta<String>>(), Ok( delta ) );
+
+    let delta: BagDelta<i32> = "{+4×3, -4, -5, +5}".parse().unwrap();
+    assert_eq!( ( delta.len(), delta.change( &4 ) ), ( 1, 2 ) );
+    assert_eq!( "{ }".parse::<BagDelta<i32>>(), Ok( BagDelta::new() ) );
+
+    // C. Each value must be signed.
+    match "{+1, 2}".parse::<BagDelta<i32>>().unwrap_err().kind
+    {
+        ParseBagErrorKind::InvalidElement( text, _ ) => assert_eq!( text, "2" ),
+        kind                                         => panic!( "unexpected {:?}", kind )
+    }
+
+    assert!( "{+x}".parse::<BagDelta<i32>>().is_err() );
+}
+
+#[ test ]
+///   Performs tests of random bags turned into one another by their deltas.
+fn test5()
+{
+    let mut rng = XorShiftRng::from_seed( [ 47, 2, 3, 4 ] );
+
+    for _ in 0..200
+    {
+        let from = random_bag( &mut rng, 6 );
+        let to = random_bag( &mut rng, 6 );
+        let delta = diff( &from, &to );
+
+        // A. The delta turns one into the other.
+        let mut test_bag = from.clone();
+        test_bag.apply( &delta ).unwrap();
+        assert_eq!( test_bag.clone().into_counts(), to.clone().into_counts() );
+
+        // B. The delta survives its text.
+        assert_eq!( delta.to_string().parse::<BagDelta<i32>>(), Ok( delta.clone() ) );
+
+        // C. The inverse turns it back.
+        test_bag.apply( &delta.invert() ).unwrap();
+        assert_eq!( test_bag.into_counts(), from.into_counts() );
+    }
+}
@@ -0,0 +1,571 @@
+// FILE: bagdurable.rs
+
+//! A `bag::Bag` kept on disk in an append-only write-ahead log, so
+//! that its elements survive the program stopping at any moment.
+//!
+//! Each `insert( _ )`, `erase( _ )` and `erase_one( _ )` is written
+//! to the end of the log before it is made on the bag in memory.
+//! Opening the log replays it, giving back the bag as it was after
+//! the last operation written in full. Compaction replaces the log
+//! with a snapshot of the bag, so the log grows only with the
+//! operations since; it can be done on request or after a number
+//! of operations.
+//!
+//! A write to the log that fails is cut off, leaving the log as it
+//! was. If it cannot be cut off, the bag is marked failed and refuses
+//! further operations until compaction rewrites the log from the bag.
+//!
+//! # The log
+//! The log is a sequence of records, each written as
+//!
+//! ```text
+//! length: u32 LE | checksum: u32 LE | kind: u8 | text
+//! ```
+//!
+//! where `length` counts the bytes of `kind` and `text`, and
+//! `checksum` is their CRC-32. The kind is `I`, `E` or `O` for an
+//! insert, erase or erase_one, whose text is the element as written
+//! by `Display`, or `S` for a snapshot, whose text is each element of
+//! the bag in order, written the same way and prefixed by its length:
+//!
+//! ```text
+//! length: u32 LE | element | length: u32 LE | element | ...
+//! ```
+//!
+//! so that any element, even one written as no text, is read back.
+//! A compacted log is one snapshot record.
+//!
+//! A record cut short by the end of the log is a torn write, from the
+//! program stopping while writing it, and opening the log cuts it off.
+//! A record in full whose checksum does not match, or which cannot be
+//! read, is corruption rather than a torn write: opening the log
+//! returns an Err and leaves the file as it is.
+//!
+//! The type used must have the traits `PartialEq`, `Clone`, `Default`,
+//! `Display` and `FromStr`, and each element must be read back from
+//! its text as an equal element.
+
+use bag::Bag;
+use std::fmt::{ self, Debug, Display, Formatter };
+use std::fs::{ self, File, OpenOptions };
+use std::io::{ self, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::str::{ self, FromStr };
+
+// The bytes of a record before its kind and text.
+const HEADER_LEN: usize = 8;
+
+// The kinds of records.
+const SNAPSHOT: u8 = b'S';
+const INSERT: u8 = b'I';
+const ERASE: u8 = b'E';
+const ERASE_ONE: u8 = b'O';
+
+/// A bag whose operations are logged to a file before they are made.
+///
+/// # Invariant of the DurableBag struct:
+/// 1. Replaying the first `log_len` bytes of the file at `path`
+/// gives `bag`.
+///
+/// 2. `log` is open for appending to that file, and `records` is the
+/// number of operation records since its snapshot.
+///
+/// 3. If `failed`, the file may hold part of a record after the first
+/// `log_len` bytes, and nothing more is appended to it.
+
+pub struct DurableBag<Type: PartialEq + Clone + Default + Display + FromStr>
+{
+    bag: Bag<Type>,
+    path: PathBuf,
+    log: File,
+    log_len: u64,
+    records: usize,
+    compact_after: Option<usize>,
+    sync: bool,
+    discarded: u64,
+    failed: bool
+}
+
+impl<Type: PartialEq + Clone + Default + Display + FromStr> DurableBag<Type>
+    where Type::Err: Display
+{
+    /// Open the log at `path`, replaying it, or create an empty one.
+    ///
+    /// # Parameter: `path`
+    /// The file of the log.
+    ///
+    /// # Postcondition
+    /// The bag is as it was after the last record written in full. A
+    /// torn record, whose header or text runs past the end of the log,
+    /// has been cut from the file.
+    ///
+    /// # Return
+    /// An Err if the file could not be read or written, or holds a
+    /// record in full whose checksum does not match or whose text cannot
+    /// be read, leaving the file as it is.
+    ///
+    /// # Examples
+    /// ```
+    /// let path = ::std::env::temp_dir().join( "example.baglog" );
+    ///
+    /// let mut bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    /// bag.insert( 4 ).unwrap();
+    /// drop( bag );
+    ///
+    /// let bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    /// assert_eq!( bag.bag().occurrences( 4 ), 1 );
+    /// ```
+
+    pub fn open<P: AsRef<Path>>( path: P ) -> io::Result<Self>
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut data = Vec::new();
+
+        match File::open( &path )
+        {
+            Ok( mut file ) => { file.read_to_end( &mut data )?; }
+            Err( ref error ) if error.kind() == io::ErrorKind::NotFound => {}
+            Err( error ) => return Err( error )
+        }
+
+        let mut bag = Bag::new();
+        let mut records = 0;
+        let mut offset = 0;
+
+        while let Some( ( kind, text, next ) ) = read_record( &data, offset ).map_err( | error | invalid_data( offset, error ) )?
+        {
+            match kind
+            {
+                SNAPSHOT =>
+                    {
+                        bag = decode_snapshot( text ).map_err( | error | invalid_data( offset, error ) )?;
+                        records = 0;
+                    }
+                INSERT | ERASE | ERASE_ONE =>
+                    {
+                        let value = parse_element::<Type>( text ).map_err( | error | invalid_data( offset, error ) )?;
+
+                        match kind
+                        {
+                            INSERT  => bag.insert( value ),
+                            ERASE   => { bag.erase( value ); }
+                            _       => { bag.erase_one( value ); }
+                        }
+
+                        records += 1;
+                    }
+                _ => return Err( invalid_data( offset, format!( "unknown record kind {}", kind ) ) )
+            }
+
+            offset = next;
+        }
+
+        let log = OpenOptions::new().create( true ).append( true ).open( &path )?;
+        let discarded = ( data.len() - offset ) as u64;
+
+        if discarded > 0
+        {
+            log.set_len( offset as u64 )?;
+            log.sync_all()?;
+        }
+
+        Ok( DurableBag { bag, path, log, log_len: offset as u64, records,
+                         compact_after: None, sync: true, discarded, failed: false } )
+    }
+
+    /// The bag as logged.
+
+    pub fn bag( &self ) -> &Bag<Type>
+    {
+        &self.bag
+    }
+
+    /// The file of the log.
+
+    pub fn path( &self ) -> &Path
+    {
+        &self.path
+    }
+
+    /// The number of bytes of a torn record cut from the end of the
+    /// log when it was opened.
+
+    pub fn discarded_bytes( &self ) -> u64
+    {
+        self.discarded
+    }
+
+    /// The number of operations logged since the last snapshot.
+
+    pub fn records( &self ) -> usize
+    {
+        self.records
+    }
+
+    /// The number of operations logged after which the log is
+    /// compacted, or None if it is only compacted on request.
+
+    pub fn compact_after( &self ) -> Option<usize>
+    {
+        self.compact_after
+    }
+
+    /// Change the number of operations logged after which the log is
+    /// compacted, or None to compact it only on request.
+    ///
+    /// # Note
+    /// If the compaction after an operation fails, the operation returns
+    /// the Err of the compaction, though it was logged and made.
+    ///
+    /// # Panics
+    /// `records` given is Some( 0 ).
+
+    pub fn set_compact_after( &mut self, records: Option<usize> )
+    {
+        if records == Some( 0 )
+        {
+            panic!( "records must be > 0" );
+        }
+
+        self.compact_after = records;
+    }
+
+    /// Whether each record is synced to the disk before its operation
+    /// is made, which is true unless changed.
+
+    pub fn sync( &self ) -> bool
+    {
+        self.sync
+    }
+
+    /// Change whether each record is synced to the disk before its
+    /// operation is made. Records not synced may be lost if the
+    /// system, rather than the program, stops.
+
+    pub fn set_sync( &mut self, sync: bool )
+    {
+        self.sync = sync;
+    }
+
+    /// Whether a write to the log failed and the part of it written
+    /// could not be cut off, so the log is refused further records
+    /// until it is compacted or opened again.
+
+    pub fn failed( &self ) -> bool
+    {
+        self.failed
+    }
+
+    /// Add a new element to the bag, as `Bag::insert( _ )` does,
+    /// after logging it.
+    ///
+    /// # Return
+    /// An Err if the log could not be written, or of kind `InvalidInput`
+    /// if the text of `new_item` is too long for a record, leaving the
+    /// bag unaltered.
+
+    pub fn insert( &mut self, new_item: Type ) -> io::Result<()>
+    {
+        self.append( INSERT, new_item.to_string().as_bytes() )?;
+        self.bag.insert( new_item );
+        self.after_record()
+    }
+
+    /// Erase all copies of an element from the bag, as `Bag::erase( _ )`
+    /// does, after logging it. An erase of nothing is not logged.
+    ///
+    /// # Return
+    /// The number of items erased from bag, or an Err if the log could
+    /// not be written or the text of `target` is too long for a record,
+    /// leaving the bag unaltered.
+
+    pub fn erase( &mut self, target: Type ) -> io::Result<usize>
+    {
+        if self.bag.find( &target ).is_none()
+        {
+            return Ok( 0 );
+        }
+
+        self.append( ERASE, target.to_string().as_bytes() )?;
+        let erased = self.bag.erase( target );
+        self.after_record()?;
+        Ok( erased )
+    }
+
+    /// Remove one copy of an element from the bag, as `Bag::erase_one( _ )`
+    /// does, after logging it. An erase of nothing is not logged.
+    ///
+    /// # Return
+    /// Whether `target` exists in the bag, or an Err if the log could
+    /// not be written or the text of `target` is too long for a record,
+    /// leaving the bag unaltered.
+
+    pub fn erase_one( &mut self, target: Type ) -> io::Result<bool>
+    {
+        if self.bag.find( &target ).is_none()
+        {
+            return Ok( false );
+        }
+
+        self.append( ERASE_ONE, target.to_string().as_bytes() )?;
+        self.bag.erase_one( target );
+        self.after_record()?;
+        Ok( true )
+    }
+
+    /// Replace the log with a snapshot of the bag.
+    ///
+    /// # Postcondition
+    /// The log is one snapshot record, and records can be added to it
+    /// again if a failed write had stopped them. The new log is written
+    /// beside the old one and renamed over it, and the directory synced,
+    /// so that if the program or the system stops the file holds either
+    /// log whole.
+    ///
+    /// # Return
+    /// An Err if the new log could not be written, or of kind
+    /// `InvalidInput` if the snapshot or an element in it is too long
+    /// for a record, leaving the old log.
+
+    pub fn compact( &mut self ) -> io::Result<()>
+    {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push( ".compact" );
+        let temporary = PathBuf::from( temporary );
+
+        let record = encode_record( SNAPSHOT, &encode_snapshot( &self.bag )? )?;
+        let mut file = File::create( &temporary )?;
+        file.write_all( &record )?;
+        file.sync_all()?;
+        drop( file );
+
+        fs::rename( &temporary, &self.path )?;
+        self.log = OpenOptions::new().append( true ).open( &self.path )?;
+        self.log_len = record.len() as u64;
+        self.records = 0;
+        self.failed = false;
+        sync_directory( &self.path )
+    }
+
+    // Appends a record to the log, cutting off any part of it written
+    // if the write fails, or returns an InvalidInput Err if `text` is
+    // too long for a record. If the part written cannot be cut off, the
+    // log is marked failed and refuses records until it is compacted.
+    fn append( &mut self, kind: u8, text: &[u8] ) -> io::Result<()>
+    {
+        if self.failed
+        {
+            return Err( io::Error::other( "the log holds part of a failed write; compact it or open it again" ) );
+        }
+
+        let record = encode_record( kind, text )?;
+        let mut written = self.log.write_all( &record );
+
+        if written.is_ok() && self.sync
+        {
+            written = self.log.sync_data();
+        }
+
+        match written
+        {
+            Ok( () ) =>
+                {
+                    self.log_len += record.len() as u64;
+                    Ok( () )
+                }
+            Err( error ) =>
+                {
+                    if self.log.set_len( self.log_len ).is_err()
+                    {
+                        self.failed = true;
+                    }
+
+                    Err( error )
+                }
+        }
+    }
+
+    // Counts a logged operation, compacting the log if it is due.
+    fn after_record( &mut self ) -> io::Result<()>
+    {
+        self.records += 1;
+
+        match self.compact_after
+        {
+            Some( limit ) if self.records >= limit => self.compact(),
+            _                                      => Ok( () )
+        }
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + Display + FromStr + Debug> Debug for DurableBag<Type>
+{
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        fmt.debug_struct( "DurableBag" )
+           .field( "bag", &self.bag )
+           .field( "path", &self.path )
+           .field( "records", &self.records )
+           .field( "failed", &self.failed )
+           .finish()
+    }
+}
+
+// Syncs the directory holding `path`, so that a file renamed into it
+// stays renamed if the system stops.
+#[ cfg( unix ) ]
+fn sync_directory( path: &Path ) -> io::Result<()>
+{
+    match path.parent()
+    {
+        Some( directory ) if !directory.as_os_str().is_empty() => File::open( directory )?.sync_all(),
+        _                                                       => File::open( "." )?.sync_all()
+    }
+}
+
+// Directories cannot be opened to be synced elsewhere.
+#[ cfg( not( unix ) ) ]
+fn sync_directory( _path: &Path ) -> io::Result<()>
+{
+    Ok( () )
+}
+
+// The bytes of a record of `kind` holding `text`.
+fn encode_record( kind: u8, text: &[u8] ) -> io::Result<Vec<u8>>
+{
+    if text.len() >= u32::MAX as usize
+    {
+        return Err( io::Error::new( io::ErrorKind::InvalidInput, "a record is too long to log" ) );
+    }
+
+    let mut payload = Vec::with_capacity( 1 + text.len() );
+    payload.push( kind );
+    payload.extend_from_slice( text );
+
+    let mut record = Vec::with_capacity( HEADER_LEN + payload.len() );
+    record.extend_from_slice( &( payload.len() as u32 ).to_le_bytes() );
+    record.extend_from_slice( &crc32( &payload ).to_le_bytes() );
+    record.extend_from_slice( &payload );
+    Ok( record )
+}
+
+// A record read from the log: its kind, its text and the offset after it.
+type Record<'a> = ( u8, &'a [u8], usize );
+
+// Reads the record at `offset` of `data`, or None if there is none or it
+// runs past the end of `data`, or an Err if it is there in full but is
+// not a record.
+fn read_record( data: &[u8], offset: usize ) -> Result<Option<Record<'_>>, String>
+{
+    let header = match data.get( offset..offset + HEADER_LEN )
+    {
+        Some( header ) => header,
+        None           => return Ok( None )
+    };
+
+    let len = u32::from_le_bytes( [ header[ 0 ], header[ 1 ], header[ 2 ], header[ 3 ] ] ) as usize;
+    let checksum = u32::from_le_bytes( [ header[ 4 ], header[ 5 ], header[ 6 ], header[ 7 ] ] );
+    let start = offset + HEADER_LEN;
+
+    let payload = match start.checked_add( len ).and_then( | end | data.get( start..end ) )
+    {
+        Some( payload ) => payload,
+        None            => return Ok( None )
+    };
+
+    if payload.is_empty()
+    {
+        return Err( "record of no length".to_string() );
+    }
+
+    if crc32( payload ) != checksum
+    {
+        return Err( "checksum does not match".to_string() );
+    }
+
+    Ok( Some( ( payload[ 0 ], &payload[ 1.. ], start + len ) ) )
+}
+
+// The text of a snapshot of `bag`: each element in order, prefixed
+// by the length of its text.
+fn encode_snapshot<Type: PartialEq + Clone + Default + Display>( bag: &Bag<Type> ) -> io::Result<Vec<u8>>
+{
+    let mut text = Vec::new();
+
+    for value in bag
+    {
+        let element = value.to_string();
+
+        if element.len() > u32::MAX as usize
+        {
+            return Err( io::Error::new( io::ErrorKind::InvalidInput, "an element is too long to log" ) );
+        }
+
+        text.extend_from_slice( &( element.len() as u32 ).to_le_bytes() );
+        text.extend_from_slice( element.as_bytes() );
+    }
+
+    Ok( text )
+}
+
+// Reads the bag from the text of a snapshot.
+fn decode_snapshot<Type: PartialEq + Clone + Default + FromStr>( text: &[u8] ) -> Result<Bag<Type>, String>
+    where Type::Err: Display
+{
+    let mut bag = Bag::new();
+    let mut offset = 0;
+
+    while offset < text.len()
+    {
+        let cut_short = || "element of the snapshot cut short".to_string();
+        let prefix = text.get( offset..offset + 4 ).ok_or_else( cut_short )?;
+        let len = u32::from_le_bytes( [ prefix[ 0 ], prefix[ 1 ], prefix[ 2 ], prefix[ 3 ] ] ) as usize;
+        let start = offset + 4;
+        let element = text.get( start..start.checked_add( len ).ok_or_else( cut_short )? ).ok_or_else( cut_short )?;
+
+        bag.insert( parse_element( element )? );
+        offset = start + len;
+    }
+
+    Ok( bag )
+}
+
+// Reads an element from its text.
+fn parse_element<Type: FromStr>( text: &[u8] ) -> Result<Type, String>
+    where Type::Err: Display
+{
+    let text = str::from_utf8( text ).map_err( | error | error.to_string() )?;
+    text.parse::<Type>().map_err( | error | error.to_string() )
+}
+
+// The error of a record at `offset` that could not be read.
+fn invalid_data<Message: Display>( offset: usize, message: Message ) -> io::Error
+{
+    io::Error::new( io::ErrorKind::InvalidData, format!( "record at byte {}: {}", offset, message ) )
+}
+
+// The CRC-32 of `bytes`, as used by zip and PNG.
+fn crc32( bytes: &[u8] ) -> u32
+{
+    let mut crc = !0u32;
+
+    for &byte in bytes
+    {
+        crc ^= u32::from( byte );
+
+        for _ in 0..8
+        {
+            crc = if crc & 1 == 1 { ( crc >> 1 ) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+#[ test ]
+///   Performs a test of the checksum against known values.
+fn test_crc32()
+{
+    assert_eq!( crc32( b"" ), 0 );
+    assert_eq!( crc32( b"123456789" ), 0xCBF4_3926 );
+}
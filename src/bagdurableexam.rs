@@ -0,0 +1,246 @@
+// FILE: bagdurableexam.rs
+
+//! This module holds tests for the durable bag of bagdurable
+//!
+//! 1) tests of replaying the log when it is opened again
+//!
+//! 2) tests of torn writes at the end of the log, and of records that are corrupt
+//!
+//! 3) tests of compacting the log into a snapshot
+//!
+//! 4) tests of logs that cannot be read
+//!
+//! 5) a test of compacting after zero operations
+//!
+//! Run with `cargo test`
+
+use bagdurable::DurableBag;
+use bagscratch::scratch_path;
+use std::fs::{ self, OpenOptions };
+use std::io::{ self, Write };
+use std::path::PathBuf;
+
+// The length of the file at `path`.
+fn file_len( path: &PathBuf ) -> u64
+{
+    fs::metadata( path ).unwrap().len()
+}
+
+#[ test ]
+///   Performs tests of replaying the log when it is opened again.
+fn test_replay()
+{
+    let path = scratch_path( "bagdurable", "replay" );
+
+    // A. A new log is an empty bag.
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( test_bag.bag().size(), 0 );
+    assert_eq!( test_bag.discarded_bytes(), 0 );
+    assert!( !test_bag.failed() );
+
+    for &value in &[ 3, 1, 4, 1, 5, 9, 2, 6, 5, 3 ]
+    {
+        test_bag.insert( value ).unwrap();
+    }
+
+    assert_eq!( test_bag.erase( 5 ).unwrap(), 2 );
+    assert!( test_bag.erase_one( 1 ).unwrap() );
+    assert!( !test_bag.erase_one( 7 ).unwrap() );
+    assert_eq!( test_bag.erase( 7 ).unwrap(), 0 );
+    assert_eq!( test_bag.records(), 12 );
+
+    let before = test_bag.bag().clone();
+    drop( test_bag );
+
+    // B. The same elements in the same order.
+    let test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( *test_bag.bag(), before );
+    assert_eq!( test_bag.records(), 12 );
+    fs::remove_file( &path ).unwrap();
+
+    // C. Elements whose text has the characters of the formats.
+    let path = scratch_path( "bagdurable", "strings" );
+    let mut test_bag: DurableBag<String> = DurableBag::open( &path ).unwrap();
+    test_bag.set_sync( false );
+
+    for value in &[ "a, b", "{×}", " spaced ", "line\nbreak", "" ]
+    {
+        test_bag.insert( value.to_string() ).unwrap();
+    }
+
+    test_bag.erase_one( "{×}".to_string() ).unwrap();
+    let before = test_bag.bag().clone();
+    drop( test_bag );
+
+    assert_eq!( *DurableBag::<String>::open( &path ).unwrap().bag(), before );
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of torn writes at the end of the log, and of records that are corrupt.
+fn test_torn_writes()
+{
+    let path = scratch_path( "bagdurable", "torn" );
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+
+    for value in 0..5
+    {
+        test_bag.insert( value ).unwrap();
+    }
+
+    drop( test_bag );
+    let full_len = file_len( &path );
+
+    // A. A record cut short is discarded, with what came before kept.
+    OpenOptions::new().write( true ).open( &path ).unwrap().set_len( full_len - 3 ).unwrap();
+
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( test_bag.bag().size(), 4 );
+    assert_eq!( test_bag.bag().occurrences( 4 ), 0 );
+    assert!( test_bag.discarded_bytes() > 0 );
+
+    // B. The log is cut back so that new records follow the good ones.
+    test_bag.insert( 7 ).unwrap();
+    drop( test_bag );
+
+    let test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( test_bag.discarded_bytes(), 0 );
+    assert_eq!( test_bag.bag().occurrences( 7 ), 1 );
+    assert_eq!( test_bag.bag().size(), 5 );
+    drop( test_bag );
+
+    // C. A record in full whose checksum does not match is corruption,
+    // not a torn write, and the log is left as it is.
+    let good = fs::read( &path ).unwrap();
+    let record_len = good.len() / 5;
+
+    for &record in &[ 2, 4 ]
+    {
+        let mut data = good.clone();
+        data[ record * record_len + 9 ] ^= 0x40;
+        fs::write( &path, &data ).unwrap();
+
+        let error = DurableBag::<i32>::open( &path ).unwrap_err();
+        assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+        assert!( error.to_string().contains( "checksum" ) );
+        assert_eq!( fs::read( &path ).unwrap(), data );
+    }
+
+    // D. Garbage appended to the log.
+    fs::write( &path, &good ).unwrap();
+    OpenOptions::new().append( true ).open( &path ).unwrap().write_all( &[ 200, 0, 0, 0, 1 ] ).unwrap();
+
+    let test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( test_bag.bag().size(), 5 );
+    assert_eq!( test_bag.discarded_bytes(), 5 );
+    drop( test_bag );
+    assert_eq!( fs::read( &path ).unwrap(), good );
+
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of compacting the log into a snapshot.
+fn test_compact()
+{
+    let path = scratch_path( "bagdurable", "compact" );
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    test_bag.set_sync( false );
+
+    for value in 0..100
+    {
+        test_bag.insert( value % 7 ).unwrap();
+    }
+
+    test_bag.erase( 3 ).unwrap();
+
+    // A. The log shrinks to a snapshot of the same bag.
+    let logged_len = file_len( &path );
+    test_bag.compact().unwrap();
+
+    assert!( file_len( &path ) < logged_len / 2 );
+    assert_eq!( test_bag.records(), 0 );
+
+    test_bag.insert( 42 ).unwrap();
+    let before = test_bag.bag().clone();
+    drop( test_bag );
+
+    let test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    assert_eq!( *test_bag.bag(), before );
+    assert_eq!( test_bag.records(), 1 );
+    drop( test_bag );
+    fs::remove_file( &path ).unwrap();
+
+    // B. Compacting after a number of operations.
+    let path = scratch_path( "bagdurable", "compact_after" );
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    test_bag.set_compact_after( Some( 10 ) );
+    assert_eq!( test_bag.compact_after(), Some( 10 ) );
+
+    for value in 0..25
+    {
+        test_bag.insert( value ).unwrap();
+        assert!( test_bag.records() < 10 );
+    }
+
+    assert_eq!( test_bag.records(), 5 );
+    let before = test_bag.bag().clone();
+    drop( test_bag );
+
+    assert_eq!( *DurableBag::<i32>::open( &path ).unwrap().bag(), before );
+    fs::remove_file( &path ).unwrap();
+
+    // C. A snapshot of elements written as no text, or as the formats' characters.
+    let path = scratch_path( "bagdurable", "compact_strings" );
+    let mut test_bag: DurableBag<String> = DurableBag::open( &path ).unwrap();
+    test_bag.set_sync( false );
+
+    for value in &[ "", "a, b", "", "{}", "line\nbreak" ]
+    {
+        test_bag.insert( value.to_string() ).unwrap();
+    }
+
+    test_bag.compact().unwrap();
+    let before = test_bag.bag().clone();
+    drop( test_bag );
+
+    let test_bag = DurableBag::<String>::open( &path ).unwrap();
+    assert_eq!( *test_bag.bag(), before );
+    assert_eq!( test_bag.bag().occurrences( String::new() ), 2 );
+    assert_eq!( test_bag.bag().size(), 5 );
+    drop( test_bag );
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of logs that cannot be read.
+fn test_invalid_records()
+{
+    let path = scratch_path( "bagdurable", "invalid" );
+
+    // A. A record with a good checksum whose element cannot be read.
+    let mut test_bag: DurableBag<String> = DurableBag::open( &path ).unwrap();
+    test_bag.insert( "12".to_string() ).unwrap();
+    test_bag.insert( "twelve".to_string() ).unwrap();
+    drop( test_bag );
+
+    let error = DurableBag::<i32>::open( &path ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    assert!( error.to_string().starts_with( "record at byte 11:" ), "{}", error );
+
+    // B. The log is not altered.
+    assert_eq!( DurableBag::<String>::open( &path ).unwrap().bag().size(), 2 );
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+#[ should_panic( expected = "records must be > 0" ) ]
+///   Performs a test of compacting after zero operations.
+fn test_compact_after_zero()
+{
+    let path = scratch_path( "bagdurable", "zero" );
+    let mut test_bag: DurableBag<i32> = DurableBag::open( &path ).unwrap();
+    fs::remove_file( &path ).unwrap();
+
+    test_bag.set_compact_after( Some( 0 ) );
+}
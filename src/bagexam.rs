@@ -1,6 +1,8 @@
 // FILE: bagexam.rs
 
 //! This module holds a variety of tests for the bag structure
+//! and the bagVec structure, from the conformance tests in
+//! bagconformance.rs
 //!
 //! 1) basic tests of insert and the constant member functions
 //!
@@ -14,351 +16,341 @@
 //!
 //! 6) tests for the bag iterator
 //!
+//! 7) random operations checked against a model
+//!
 //! Run with `cargo test`
 
-extern crate rand;
+use bagtrait::{ BagOps, Clear };
+use std::hash::{ Hash, Hasher };
+use std::collections::hash_map::DefaultHasher;
 
-use bag::Bag;
-//use bagVec::Bag;
-use bag::len_trait::{Len, Capacity, CapacityMut};
+conformance_tests!( bag => ::bag::Bag<i16>,
+                    bag_vec => ::bagVec::Bag<i16> );
 
-#[ test ]
-///   Performs some basic tests of insert and the constant member functions.
-fn test1()
+// Tests that a bag assigned to itself has the same hash.
+#[ allow( clippy::self_assignment ) ]
+fn hash_self_assignment<B: BagOps + Hash>()
+    where B::Item: From<i16>
 {
-    #[ allow( non_snake_case ) ]
-    let TEST_SIZE = 3000;
-    let mut test_bag = Bag::new();
-
-    // A. Testing size for an empty bag.
-    assert_eq!( test_bag.len(), 0 );
-
-    // B. Adding the number 4 to the bag, and then testing size.
-    test_bag.insert( 4 );
-    assert_eq!( test_bag.len(), 1 );
+    let mut test_bag = B::default();
+    test_bag.insert( B::Item::from( 1 ) );
+    test_bag.insert( B::Item::from( 1 ) );
 
-    // C. Inserting the number 2 into the bag. Then checking size
-    test_bag.insert( 2 );
-    assert_eq!( test_bag.len(), 2 );
+    let mut hasher = DefaultHasher::new();
+    test_bag.hash( &mut hasher );
+    let oldbytes = hasher.finish();
 
-    // D. Inserting the number 1 into the bag. Then checking size.
-    test_bag.insert( 1 );
-    assert_eq!( test_bag.len(), 3 );
-
-    // E. Inserting the number 3 into the bag. Then checking size.
-    test_bag.insert( 3 );
-    assert_eq!( test_bag.len(), 4 );
+    test_bag = test_bag;
 
-    // F. Inserting another 2 into the bag. Then checking size.
-    test_bag.insert( 2 );
-    assert_eq!( test_bag.len(), 5 );
+    let mut hasher = DefaultHasher::new();
+    test_bag.hash( &mut hasher );
+    let newbytes = hasher.finish();
 
-    //    Then checking occurrences of 2.
-    assert_eq!( test_bag.occurrences( 2 ), 2 );
+    assert_eq!( oldbytes, newbytes );
+}
 
-    // G. Inserting the numbers 5, 6, and 7 into the bag. Then checking size.
-    test_bag.insert( 5 );
-    test_bag.insert( 6 );
-    test_bag.insert( 7 );
-    assert_eq!( test_bag.len(), 8 );
+#[ test ]
+///   Performs a test of the hash of a self-assigned bag.
+fn test_hash_bag()
+{
+    hash_self_assignment::<::bag::Bag<i16>>();
+}
 
-    // H. Inserting two more 2's into the bag. And then checking occurrences of 2's.
-    test_bag.insert( 2 );
-    test_bag.insert( 2 );
-    assert_eq!( test_bag.occurrences( 2 ), 4 );
+#[ test ]
+///   Performs a test of the hash of a self-assigned bagVec.
+fn test_hash_bag_vec()
+{
+    hash_self_assignment::<::bagVec::Bag<i16>>();
+}
 
-    // I. Inserting TEST_SIZE random items between 0 and 49 and then checking size.
+#[ test ]
+///   Performs a test of the remaining elements of the iterators as slices.
+fn test_iterator_as_slice()
+{
+    let mut test_bag = ::bag::Bag::new();
+    let mut test_bag_vec = ::bagVec::Bag::new();
 
-    for _ in 0..TEST_SIZE
+    for value in 1..5
     {
-        test_bag.insert( ( rand::random::<i16>() % 50 ) );
+        test_bag.insert( value );
+        test_bag_vec.insert( value );
     }
 
-    assert_eq!( test_bag.len(), TEST_SIZE + 10 );
+    let mut iter = test_bag.into_iter();
+    assert_eq!( iter.as_slice(), &[ 1, 2, 3, 4 ] );
+    iter.next();
+    iter.next_back();
+    assert_eq!( iter.as_slice(), &[ 2, 3 ] );
+    iter.nth( 5 );
+    assert_eq!( iter.as_slice(), &[] as &[i32] );
+
+    let mut iter = test_bag_vec.into_iter();
+    iter.next_back();
+    assert_eq!( iter.as_slice(), &[ 1, 2, 3 ] );
+    iter.nth_back( 1 );
+    assert_eq!( iter.as_slice(), &[ 1 ] );
 }
 
 #[ test ]
-///   Performs some tests of the copy constructor and == method.
-fn test2()
+///   Performs a test of collecting and extending the bags from iterators.
+fn test_from_iterator()
 {
-    let mut test_bag = Bag::new();
+    let mut test_bag: ::bag::Bag<i32> = ( 1..4 ).collect();
+    let mut test_bag_vec: ::bagVec::Bag<i32> = ( 1..4 ).collect();
+
+    // A. The values are inserted in the order they are returned
+    assert_eq!( test_bag.into_iter().cloned().collect::<Vec<i32>>(), vec![ 1, 2, 3 ] );
+    assert_eq!( test_bag_vec.into_iter().cloned().collect::<Vec<i32>>(), vec![ 1, 2, 3 ] );
+
+    // B. Extending with values and with references
+    test_bag.extend( vec![ 3, 4 ] );
+    test_bag_vec.extend( &[ 3, 4 ] );
+    assert_eq!( test_bag.size(), 5 );
+    assert_eq!( test_bag_vec.occurrences( 3 ), 2 );
+    assert_eq!( test_bag.to_string(), test_bag_vec.to_string() );
+
+    // C. An empty iterator
+    assert_eq!( ::std::iter::empty().collect::<::bag::Bag<i32>>(), ::bag::Bag::new() );
+}
 
-    // A. Testing that copy constructor works okay for empty bag...
-    let copy1 = test_bag.clone();
-    assert_eq!( copy1.len(), 0 );
+#[ test ]
+///   Performs a test of the distinct values and counts of the bags.
+fn test_counts()
+{
+    let mut test_bag = ::bag::Bag::new();
+    let mut test_bag_vec = ::bagVec::Bag::new();
 
-    // B. Testing copy constructor with 4-item bag...
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    let copy2 = test_bag.clone();
+    for &value in &[ 3, 1, 3, 2, 3, 1 ]
+    {
+        test_bag.insert( value );
+        test_bag_vec.insert( value );
+    }
 
-    //    and now testing the == method...
-    assert_eq!( test_bag, copy2 );
-    assert_eq!( copy2, test_bag );
+    // A. Values in the order of their first occurrence
+    assert_eq!( test_bag.distinct().collect::<Vec<&i16>>(), vec![ &3, &1, &2 ] );
+    assert_eq!( test_bag_vec.distinct().collect::<Vec<&i16>>(), vec![ &3, &1, &2 ] );
 
-    test_bag.insert( 1 ); // Alter the original, but not the copy
-    // C. Then checking size of copy.
-    assert_eq!( copy2.len(), 4 );
+    // B. Counts agree with occurrences( _ )
+    for ( &value, count ) in test_bag.counts()
+    {
+        assert_eq!( count, test_bag.occurrences( value ) );
+    }
 
-    // D. Altering original but not the copy.
-    assert_eq!( test_bag.len(), 5 );
-}
+    assert_eq!( test_bag_vec.counts().collect::<Vec<( &i16, usize )>>(), vec![ ( &3, 3 ), ( &1, 2 ), ( &2, 1 ) ] );
 
-#[ test ]
-///   Performs some tests of the assignment operator.
-fn test3()
-{
-    let mut test_bag = Bag::new();
-    let oldbytes;
-    let newbytes;
-
-    // A. Testing that assignment operator works okay for empty bag...
-    let mut copy1 = Bag::new();
-    copy1.insert( 1 );
-    copy1 = test_bag.clone();
-
-    assert_eq!( copy1.len(), 0 );
-
-    // B. Testing assignment operator with 4-item bag...
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    test_bag.insert( 1 );
-    let mut copy2 = Bag::new();
-    copy2.clone_from( &test_bag );
-    test_bag.insert( 1 ); // Alter the original, but not the copy
-    //    altering original by an insertion...
-
-    assert_eq!( test_bag.occurrences( 1 ), 5 );
-    assert_eq!( copy2.occurrences( 1 ), 4 );
-
-    // Test passed.\n   testing size of assigned to...
-    assert_eq!( copy2.len(), 4 );
-
-    //    testing size of original...
-    assert_eq!( test_bag.len(), 5 );
-
-    // C. Testing assignment operator for a self-assignment...
-    let mut hasher = ::std::collections::hash_map::DefaultHasher::new();
-    oldbytes = ::std::hash::Hash::hash( &test_bag, &mut hasher );
-    test_bag = test_bag;
-    newbytes = ::std::hash::Hash::hash( &test_bag, &mut hasher );
+    // C. Maps of the counts
+    let sorted: Vec<( i16, usize )> = test_bag.clone().into_sorted_counts().into_iter().collect();
+    assert_eq!( sorted, vec![ ( 1, 2 ), ( 2, 1 ), ( 3, 3 ) ] );
 
-    assert_eq!( oldbytes, newbytes );
-}
+    let counts = test_bag_vec.into_counts();
+    assert_eq!( counts.len(), 3 );
+    assert_eq!( counts[ &3 ], 3 );
 
-#[ test ]
-///   Performs basic tests for the erase functions.
-fn test4()
-{
-    let mut test_bag = Bag::new();
-
-    // Testing erase from empty bag, should have no effect.
-    assert_eq!( test_bag.erase( 0 ), 0 );
-    assert_eq!( test_bag.len(), 0 );
-        
-    // Inserting these: 8 6 10 1 7 10 15 3 13 2 5 11 14 4 12
-    test_bag.insert(  8 );
-    test_bag.insert(  6 );
-    test_bag.insert( 10 );
-    test_bag.insert(  1 );
-    test_bag.insert(  7 );
-    test_bag.insert( 10 );
-    test_bag.insert( 15 );
-    test_bag.insert(  3 );
-    test_bag.insert( 13 );
-    test_bag.insert(  2 );
-    test_bag.insert(  5 );
-    test_bag.insert( 11 );
-    test_bag.insert( 14 );
-    test_bag.insert(  4 );
-    test_bag.insert( 12 );
-    assert_eq!( test_bag.len(), 15 );
-
-    // Now testing capacity, should be 16.
-    assert_eq!( test_bag.capacity(), 16 );
-
-    // Erasing 0, which is not in bag, so bag should be unchanged.
-    assert!( !test_bag.erase_one( 0 ) );
-    assert_eq!( test_bag.len(), 15 );
-
-    // Erasing the 6.
-    assert_eq!( test_bag.erase( 6 ), 1 );
-    assert_eq!( test_bag.len(), 14 );
-
-    // Erasing one 10.
-    assert!( test_bag.erase_one( 10 ) );
-    assert_eq!( test_bag.len(), 13 );
-
-    // Erasing the 1.
-    assert_eq!( test_bag.erase( 1 ), 1 );
-    assert_eq!( test_bag.len(), 12 );
-
-    // Erasing the 15.
-    assert_eq!( test_bag.erase( 15 ), 1 );
-    assert_eq!( test_bag.len(), 11 );
-
-    // Erasing the 5.
-    assert_eq!( test_bag.erase( 5 ), 1 );
-    assert_eq!( test_bag.len(), 10 );
-
-    // Erasing the 11.
-    assert_eq!( test_bag.erase( 11 ), 1 );
-    assert_eq!( test_bag.len(), 9 );
-
-    // Erasing the 3.
-    assert_eq!( test_bag.erase( 3 ), 1 );
-    assert_eq!( test_bag.len(), 8 );
-
-    // Erasing the 13.
-    assert_eq!( test_bag.erase( 13 ), 1 );
-    assert_eq!( test_bag.len(), 7 );
-
-    // Erasing the 2.
-    assert_eq!( test_bag.erase( 2 ), 1 );
-    assert_eq!( test_bag.len(), 6 );
-
-    // Erasing the one and only 14.
-    assert!( test_bag.erase_one( 14 ) );
-    assert_eq!( test_bag.len(), 5 );
-
-    // Erasing the 4.
-    assert_eq!( test_bag.erase( 4 ), 1 );
-    assert_eq!( test_bag.len(), 4 );
-
-    // Erasing the 12.
-    assert_eq!( test_bag.erase( 12 ), 1 );
-    assert_eq!( test_bag.len(), 3 );
-
-    // Erasing the 8.
-    assert_eq!( test_bag.erase( 8 ), 1 );
-    assert_eq!( test_bag.len(), 2 );
-
-    // Erasing the 7.
-    assert_eq!( test_bag.erase( 7 ), 1 );
-    assert_eq!( test_bag.len(), 1 );
-
-    // Erasing the other 10.
-    assert!( test_bag.erase_one( 10 ) );
-    assert_eq!( test_bag.len(), 0 );
-
-    // Testing capacity again.
-    assert_eq!( test_bag.capacity(), 16 );
-
-    // Now trimming to size.
-    test_bag.shrink_to_fit();
-    assert_eq!( test_bag.capacity(), 1 );
-
-    // Now trimming to size again.
-    test_bag.shrink_to_fit();
-    assert_eq!( test_bag.capacity(), 1 );
-
-    // Inserting value 5000 into the bag.
-    // Inserting three 5's into the bag and then erasing all of them.
-    test_bag.insert( 5000 );
-    test_bag.insert( 5 ); 
-    test_bag.insert( 5 );
-    test_bag.insert( 5 );
-    assert_eq!( test_bag.erase( 5 ), 3 );
-    assert_eq!( test_bag.len(), 1 );
+    // D. An empty bag has no counts
+    test_bag.clear();
+    assert_eq!( test_bag.counts().count(), 0 );
+    assert!( test_bag.into_counts().is_empty() );
 }
 
 #[ test ]
-///   Performs basic tests for the += and + functions
-fn test5()
+///   Performs a test of the most and least common values of the bags.
+fn test_most_common()
 {
-    let mut test_bag1 = Bag::new();
-    let mut test_bag2 = Bag::new();
-    let mut test_bag3 = Bag::new();
-
-    // Inserting 2000 1's into test_bag1 and 2000 2's into test_bag2.
+    let mut test_bag = ::bag::Bag::new();
+    let mut test_bag_vec = ::bagVec::Bag::new();
 
-    for _ in 0..2000
+    for &value in &[ 5, 1, 3, 1, 3, 2, 3, 4 ]
     {
-        test_bag1.insert( 1 );
-        test_bag2.insert( 2 );
+        test_bag.insert( value );
+        test_bag_vec.insert( value );
     }
 
-    // Now testing the += function.
-    test_bag1 += test_bag2.clone();
+    // A. Ties are broken by first occurrence
+    assert_eq!( test_bag.most_common( 3 ), vec![ ( &3, 3 ), ( &1, 2 ), ( &5, 1 ) ] );
+    assert_eq!( test_bag_vec.most_common( 3 ), vec![ ( &3, 3 ), ( &1, 2 ), ( &5, 1 ) ] );
+    assert_eq!( test_bag.least_common( 4 ), vec![ ( &5, 1 ), ( &2, 1 ), ( &4, 1 ), ( &1, 2 ) ] );
 
-    //   and now testing for occurrences of 1's and 2's in test_bag1.
-    assert_eq!( test_bag1.occurrences( 1 ), 2000 ); 
-    assert_eq!( test_bag2.occurrences( 2 ), 2000 );
+    // B. k beyond the number of values lists all of them
+    assert_eq!( test_bag.most_common( 100 ).len(), 5 );
+    assert_eq!( test_bag_vec.least_common( 100 ).last(), Some( &( &3, 3 ) ) );
 
-    // Now testing the + function.
-    test_bag3.clone_from( &( test_bag2.clone() + test_bag2.clone() ) );
+    // C. k of 0, and an empty bag, list nothing
+    assert!( test_bag.most_common( 0 ).is_empty() );
+    assert!( ::bag::Bag::<i16>::new().least_common( 3 ).is_empty() );
 
-    //   and now testing for occurrences of 2's in test_bag3.
-    assert_eq!( test_bag3.occurrences( 2 ), 4000 );
+    // D. The same as sorting all of the counts
+    let mut sorted: Vec<( &i16, usize )> = test_bag.counts().collect();
+    sorted.sort_by_key( | &( _, count ) | ::std::cmp::Reverse( count ) );
+    assert_eq!( test_bag.most_common( 5 ), sorted );
 }
 
 #[ test ]
-///   Performs tests for the bag iterator
-fn test6()
+///   Performs a test of writing the bags as text and reading them back.
+fn test_display_from_str()
 {
-    let mut testbag = Bag::new();
+    let mut test_bag: ::bag::Bag<String> = ::bag::Bag::new();
 
-    { // Testing next(), last(), and count()
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), None );
-        assert_eq!( iter.last(), None );
-        assert_eq!( testbag.into_iter().count(), 0 );
+    // A. Runs of equal elements are written with their count
+    for word in &[ "a", "b", "b", "b", "c", "a" ]
+    {
+        test_bag.insert( word.to_string() );
     }
 
-    testbag.insert( 8 );
+    assert_eq!( test_bag.to_string(), "{a, b×3, c, a}" );
+    assert_eq!( test_bag.to_string().parse::<::bag::Bag<String>>(), Ok( test_bag.clone() ) );
+
+    // B. The characters of the format are escaped
+    test_bag.insert( r" {x, y×2}\ ".to_string() );
+    assert_eq!( test_bag.to_string(), r"{a, b×3, c, a, \ \{x\, y\×2\}\\\ }" );
+    assert_eq!( test_bag.to_string().parse::<::bag::Bag<String>>(), Ok( test_bag.clone() ) );
+
+    // C. The empty bag
+    assert_eq!( ::bagVec::Bag::<i16>::new().to_string(), "{}" );
+    assert_eq!( "{ }".parse::<::bagVec::Bag<i16>>(), Ok( ::bagVec::Bag::new() ) );
+
+    // D. Numbers, with whitespace around them
+    let test_bag_vec: ::bagVec::Bag<i16> = " { 1 , 2 ×2, -3 } ".parse().unwrap();
+    assert_eq!( test_bag_vec.into_iter().cloned().collect::<Vec<i16>>(), vec![ 1, 2, 2, -3 ] );
+    assert_eq!( test_bag_vec.to_string(), "{1, 2×2, -3}" );
+
+    // E. Errors name the column and the problem
+    let error = "{1, 2, 300000}".parse::<::bag::Bag<i16>>().unwrap_err();
+    assert_eq!( error.to_string(), "column 8: `300000` is not a valid element: number too large to fit in target type" );
+
+    // F. Empty elements, alone and in runs
+    let mut test_bag: ::bag::Bag<String> = ::bag::Bag::new();
+    test_bag.insert( String::new() );
+    assert_eq!( test_bag.to_string(), r"{\0}" );
+    assert_eq!( test_bag.to_string().parse::<::bag::Bag<String>>(), Ok( test_bag.clone() ) );
+
+    test_bag.insert( String::new() );
+    test_bag.insert( "a".to_string() );
+    test_bag.insert( "0".to_string() );
+    assert_eq!( test_bag.to_string(), r"{\0×2, a, 0}" );
+    assert_eq!( test_bag.to_string().parse::<::bag::Bag<String>>(), Ok( test_bag.clone() ) );
+
+    // G. Counts are limited, so a short text cannot exhaust memory
+    let error = "{a×99999999999}".parse::<::bagVec::Bag<String>>().unwrap_err();
+    assert_eq!( error.kind, ::bagformat::ParseBagErrorKind::TooManyElements );
+    assert!( format!( "{{a×{}}}", ::bagformat::MAX_ELEMENTS + 1 ).parse::<::bag::Bag<String>>().is_err() );
+
+    // H. The limit is scaled by the size of the elements, or given
+    let limit = ::bagformat::max_elements::<String>();
+    assert!( limit * ::std::mem::size_of::<String>() <= ::bagformat::MAX_BYTES );
+    assert!( ::bagformat::max_elements::<u8>() > limit );
+
+    let error = format!( "{{a×{}}}", limit + 1 ).parse::<::bag::Bag<String>>().unwrap_err();
+    assert_eq!( error.kind, ::bagformat::ParseBagErrorKind::TooManyElements );
+
+    assert_eq!( ::bag::Bag::<String>::parse_within( "{a×2, b}", 3 ).map( | bag | bag.size() ), Ok( 3 ) );
+    let error = ::bagVec::Bag::<String>::parse_within( "{a×2, b×2}", 3 ).unwrap_err();
+    assert_eq!( error.kind, ::bagformat::ParseBagErrorKind::TooManyElements );
+}
 
-    { // Testing next(), last(), and count() again
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), Some( &8 ) );
-        assert_eq!( iter.last(), Some( &8 ) );
-        assert_eq!( testbag.into_iter().count(), 1 );
-    }
+#[ test ]
+///   Performs a test of sorting the bag and of its sorted flag.
+fn test_sort()
+{
+    let mut test_bag: ::bag::Bag<i16> = ::bag::Bag::new();
 
-    testbag.insert( 3 );
-    testbag.insert( 4 );
-    testbag.insert( 7 );
+    // A. Empty and single element bags are sorted
+    assert!( test_bag.is_sorted() );
+    test_bag.insert( 5 );
+    assert!( test_bag.is_sorted() );
 
-    { // Testing next(), last(), and count() again
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), Some( &8 ) );
-        assert_eq!( iter.last(), Some( &7 ) );
-        assert_eq!( testbag.into_iter().count(), 4 );
+    for &value in &[ 3, 5, 1, 3, 9, 3 ]
+    {
+        test_bag.insert( value );
     }
 
-    testbag.insert( 2 );
+    assert!( !test_bag.is_sorted() );
+    assert_eq!( test_bag.sorted_iter().cloned().collect::<Vec<i16>>(), vec![ 1, 3, 3, 3, 5, 5, 9 ] );
+    assert_eq!( test_bag.into_iter().cloned().collect::<Vec<i16>>(), vec![ 5, 3, 5, 1, 3, 9, 3 ] );
 
-    {
-        let mut iter = testbag.into_iter();
+    // B. Sorting in place
+    test_bag.sort();
+    assert!( test_bag.is_sorted() );
+    assert_eq!( test_bag.into_iter().cloned().collect::<Vec<i16>>(), vec![ 1, 3, 3, 3, 5, 5, 9 ] );
+    assert_eq!( test_bag.binary_search_count( &3 ), 3 );
+    assert_eq!( test_bag.binary_search_count( &4 ), 0 );
+    assert_eq!( test_bag.binary_search_count( &9 ), 1 );
 
-        // Testing the full range of the bag with next()
-        assert_eq!( iter.next(), Some( &8 ) );
-        assert_eq!( iter.next(), Some( &3 ) );
-        assert_eq!( iter.next(), Some( &4 ) );
-        assert_eq!( iter.next(), Some( &7 ) );
-        assert_eq!( iter.next(), Some( &2 ) );
+    // C. Erasing keeps the order, inserting does not
+    test_bag.erase_one( 1 );
+    assert_eq!( test_bag.erase( 3 ), 3 );
+    assert!( test_bag.is_sorted() );
+    assert_eq!( test_bag.into_iter().cloned().collect::<Vec<i16>>(), vec![ 5, 5, 9 ] );
 
-        // ... and then None once it's over.
-        assert_eq!( iter.next(), None );
+    test_bag.insert( 7 );
+    assert!( !test_bag.is_sorted() );
+    assert_eq!( test_bag.binary_search_count( &5 ), 2 );
+
+    // D. Copies keep the flag, which clear( ) and += update
+    test_bag.sort();
+    assert!( test_bag.clone().is_sorted() );
+
+    let mut other = ::bag::Bag::new();
+    other += test_bag.clone();
+    assert!( other.is_sorted() );
+    other += test_bag.clone();
+    assert!( !other.is_sorted() );
+    other.clear();
+    assert!( other.is_sorted() );
+    assert!( ( test_bag.clone() + ::bag::Bag::new() ).is_sorted() );
+
+    // E. Counts of the sorted values
+    other.insert( 2 );
+    other.insert( -1 );
+    other.insert( 2 );
+    assert_eq!( other.dedup_counts(), vec![ ( -1, 1 ), ( 2, 2 ) ] );
+    assert!( other.is_sorted() );
+
+    // F. A custom order does not mark the bag sorted
+    other.sort_by( | a, b | b.cmp( a ) );
+    assert_eq!( other.into_iter().cloned().collect::<Vec<i16>>(), vec![ 2, 2, -1 ] );
+    assert!( !other.is_sorted() );
+    assert_eq!( other.binary_search_count( &2 ), 2 );
+}
 
-        // More calls may or may not return None. Here, they always will.
-        assert_eq!( iter.next(), None );
-        assert_eq!( iter.next(), None );
-    }
-    { // Testing the nth( _ ) method
-        let mut iter = testbag.into_iter();
+#[ test ]
+///   Performs a test of random erases from a sorted bag against a sorted list.
+fn test_sorted_erase()
+{
+    let mut test_bag: ::bag::Bag<i16> = ::bag::Bag::new();
+    let mut model: Vec<i16> = Vec::new();
 
-        assert_eq!( iter.nth( 1 ), Some( &3 ) );
-        assert_eq!( iter.nth( 1 ), Some( &7 ) );
-        assert_eq!( iter.nth( 1 ), None );
-    }
+    for index in 0..200
     {
-        let mut iter = testbag.into_iter();
+        let value = ( index * 37 % 23 ) as i16;
+        test_bag.insert( value );
+        model.push( value );
+    }
+
+    test_bag.sort();
+    model.sort();
 
-        assert_eq!( iter.nth( 0 ), Some( &8 ) );
-        assert_eq!( iter.nth( 2 ), Some( &7 ) );
-        assert_eq!( iter.nth( 9 ), None );
+    for index in 0..60
+    {
+        let target = ( index * 11 % 25 ) as i16;
+
+        if index % 3 == 0
+        {
+            assert_eq!( test_bag.erase( target ), model.iter().filter( | &&value | value == target ).count() );
+            model.retain( | &value | value != target );
+        }
+        else
+        {
+            let position = model.iter().position( | &value | value == target );
+            assert_eq!( test_bag.erase_one( target ), position.is_some() );
+
+            if let Some( position ) = position
+            {
+                model.remove( position );
+            }
+        }
+
+        assert!( test_bag.is_sorted() );
+        assert_eq!( test_bag.into_iter().cloned().collect::<Vec<i16>>(), model );
+        assert_eq!( test_bag.binary_search_count( &target ), model.iter().filter( | &&value | value == target ).count() );
     }
 }
@@ -1,6 +1,7 @@
 // FILE: bagexamf64.rs
 
-//! This module holds a variety of tests for the bagf64 structure
+//! This module holds a variety of tests for the bagf64 structure,
+//! from the conformance tests in bagconformance.rs
 //!
 //! 1) basic tests of insert and the constant member functions
 //!
@@ -14,340 +15,90 @@
 //!
 //! 6) tests for the bag iterator
 //!
+//! 7) random operations checked against a model
+//!
 //! Run with `cargo test`
 
-extern crate rand;
+use bagf64::NumericCounts;
 
-use bagf64::Bag;
-use bag::len_trait::{Len, Capacity, CapacityMut};
+conformance_tests!( bagf64 => ::bagf64::Bag );
 
 #[ test ]
-///   Performs some basic tests of insert and the constant member functions. 
-fn test1()
+///   Performs a test of the remaining elements of the iterator as a slice.
+fn test_iterator_as_slice()
 {
-    #[ allow( non_snake_case ) ]
-    let TEST_SIZE = 3000;
-    let mut test_bag = Bag::new();
-
-    // A. Testing size for an empty bag.
-    assert_eq!( test_bag.len(), 0 );
-
-    // B. Adding the number 4 to the bag, and then testing size.
-    test_bag.insert( 4.0 );
-    assert_eq!( test_bag.len(), 1 );
-
-    // C. Inserting the number 2 into the bag. Then checking size
-    test_bag.insert( 2.0 );
-    assert_eq!( test_bag.len(), 2 );
-
-    // D. Inserting the number 1 into the bag. Then checking size.
-    test_bag.insert( 1.0 );
-    assert_eq!( test_bag.len(), 3 );
-
-    // E. Inserting the number 3 into the bag. Then checking size.
-    test_bag.insert( 3.0 );
-    assert_eq!( test_bag.len(), 4 );
-
-    // F. Inserting another 2 into the bag. Then checking size.
-    test_bag.insert( 2.0 );
-    assert_eq!( test_bag.len(), 5 );
-
-    //    Then checking occurrences of 2.
-    assert_eq!( test_bag.occurrences( 2.0 ), 2 );
-
-    // G. Inserting the numbers 5, 6, and 7 into the bag. Then checking size.
-    test_bag.insert( 5.0 );
-    test_bag.insert( 6.0 );
-    test_bag.insert( 7.0 );
-    assert_eq!( test_bag.len(), 8 );
-
-    // H. Inserting two more 2's into the bag. And then checking occurrences of 2's.
-    test_bag.insert( 2.0 );
-    test_bag.insert( 2.0 );
-    assert_eq!( test_bag.occurrences( 2.0 ), 4 );
-
-    // I. Inserting TEST_SIZE random items between 0 and 49 and then checking size.
-
-    for _ in 0..TEST_SIZE
-    {
-        test_bag.insert( ( rand::random::<f64>() % 50.0 ) );
-    }
-
-    assert_eq!( test_bag.len(), TEST_SIZE + 10 );
+    let mut test_bag = ::bagf64::Bag::new();
+    test_bag.insert( 1.5 );
+    test_bag.insert( 2.5 );
+    test_bag.insert( 3.5 );
+
+    let mut iter = test_bag.into_iter();
+    assert_eq!( iter.as_slice(), &[ 1.5, 2.5, 3.5 ] );
+    iter.next_back();
+    assert_eq!( iter.as_slice(), &[ 1.5, 2.5 ] );
+    iter.next();
+    iter.next();
+    assert_eq!( iter.as_slice(), &[] as &[f64] );
 }
 
 #[ test ]
-///   Performs some tests of the copy constructor and == method.
-fn test2()
+///   Performs a test of the distinct values and counts of the bag.
+fn test_counts()
 {
-    let mut test_bag = Bag::new();
-
-    // A. Testing that copy constructor works okay for empty bag...
-    let copy1 = test_bag.clone();
-    assert_eq!( copy1.len(), 0 );
-
-    // B. Testing copy constructor with 4-item bag...
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    let copy2 = test_bag.clone();
-
-    //    and now testing the == method...
-    assert_eq!( test_bag, copy2 );
-    assert_eq!( copy2, test_bag );
-
-    test_bag.insert( 1.0 ); // Alter the original, but not the copy
-    // C. Then checking size of copy.
-    assert_eq!( copy2.len(), 4 );
-
-    // D. Altering original but not the copy.
-    assert_eq!( test_bag.len(), 5 );
-}
-
-#[ test ]
-///   Performs some tests of the assignment operator.
-fn test3()
-{
-    let mut test_bag = Bag::new();
-
-    // A. Testing that assignment operator works okay for empty bag...
-    let mut copy1 = Bag::new();
-    copy1.insert( 1.0 );
-    copy1 = test_bag.clone();
-
-    assert_eq!( copy1.len(), 0 );
-
-    // B. Testing assignment operator with 4-item bag...
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    test_bag.insert( 1.0 );
-    let mut copy2 = Bag::new();
-    copy2.clone_from( &test_bag );
-    test_bag.insert( 1.0 ); // Alter the original, but not the copy
-    //    altering original by an insertion...
-
-    assert_eq!( test_bag.occurrences( 1.0 ), 5 );
-    assert_eq!( copy2.occurrences( 1.0 ), 4 );
-
-    // Test passed.\n   testing size of assigned to...
-    assert_eq!( copy2.len(), 4 );
-
-    //    testing size of original...
-    assert_eq!( test_bag.len(), 5 );
-}
-
-#[ test ]
-///   Performs basic tests for the erase functions
-fn test4()
-{
-    let mut test_bag = Bag::new();
-
-    // Testing erase from empty bag, should have no effect.
-    assert_eq!( test_bag.erase( 0.0 ), 0 );
-    assert_eq!( test_bag.len(), 0 );
-        
-    // Inserting these: 8 6 10 1 7 10 15 3 13 2 5 11 14 4 12
-    test_bag.insert(  8.0 );
-    test_bag.insert(  6.0 );
-    test_bag.insert( 10.0 );
-    test_bag.insert(  1.0 );
-    test_bag.insert(  7.0 );
-    test_bag.insert( 10.0 );
-    test_bag.insert( 15.0 );
-    test_bag.insert(  3.0 );
-    test_bag.insert( 13.0 );
-    test_bag.insert(  2.0 );
-    test_bag.insert(  5.0 );
-    test_bag.insert( 11.0 );
-    test_bag.insert( 14.0 );
-    test_bag.insert(  4.0 );
-    test_bag.insert( 12.0 );
-    assert_eq!( test_bag.len(), 15 );
-
-    // Now testing capacity, should be 16.
-    assert_eq!( test_bag.capacity(), 16 );
-
-    // Erasing 0, which is not in bag, so bag should be unchanged.
-    assert!( !test_bag.erase_one( 0.0 ) );
-    assert_eq!( test_bag.len(), 15 );
+    let mut test_bag = ::bagf64::Bag::new();
 
-    // Erasing the 6.
-    assert_eq!( test_bag.erase( 6.0 ), 1 );
-    assert_eq!( test_bag.len(), 14 );
-
-    // Erasing one 10.
-    assert!( test_bag.erase_one( 10.0 ) );
-    assert_eq!( test_bag.len(), 13 );
-
-    // Erasing the 1.
-    assert_eq!( test_bag.erase( 1.0 ), 1 );
-    assert_eq!( test_bag.len(), 12 );
-
-    // Erasing the 15.
-    assert_eq!( test_bag.erase( 15.0 ), 1 );
-    assert_eq!( test_bag.len(), 11 );
-
-    // Erasing the 5.
-    assert_eq!( test_bag.erase( 5.0 ), 1 );
-    assert_eq!( test_bag.len(), 10 );
-
-    // Erasing the 11.
-    assert_eq!( test_bag.erase( 11.0 ), 1 );
-    assert_eq!( test_bag.len(), 9 );
-
-    // Erasing the 3.
-    assert_eq!( test_bag.erase( 3.0 ), 1 );
-    assert_eq!( test_bag.len(), 8 );
-
-    // Erasing the 13.
-    assert_eq!( test_bag.erase( 13.0 ), 1 );
-    assert_eq!( test_bag.len(), 7 );
-
-    // Erasing the 2.
-    assert_eq!( test_bag.erase( 2.0 ), 1 );
-    assert_eq!( test_bag.len(), 6 );
-
-    // Erasing the one and only 14.
-    assert!( test_bag.erase_one( 14.0 ) );
-    assert_eq!( test_bag.len(), 5 );
-
-    // Erasing the 4.
-    assert_eq!( test_bag.erase( 4.0 ), 1 );
-    assert_eq!( test_bag.len(), 4 );
-
-    // Erasing the 12.
-    assert_eq!( test_bag.erase( 12.0 ), 1 );
-    assert_eq!( test_bag.len(), 3 );
-
-    // Erasing the 8.
-    assert_eq!( test_bag.erase( 8.0 ), 1 );
-    assert_eq!( test_bag.len(), 2 );
-
-    // Erasing the 7.
-    assert_eq!( test_bag.erase( 7.0 ), 1 );
-    assert_eq!( test_bag.len(), 1 );
-
-    // Erasing the other 10.
-    assert!( test_bag.erase_one( 10.0 ) );
-    assert_eq!( test_bag.len(), 0 );
-
-    // Testing capacity again.
-    assert_eq!( test_bag.capacity(), 16 );
+    for &value in &[ 2.5, -1.0, 2.5, 0.0, -0.0, f64::NAN, f64::NAN ]
+    {
+        test_bag.insert( value );
+    }
 
-    // Now trimming to size.
-    test_bag.shrink_to_fit();
-    assert_eq!( test_bag.capacity(), 1 );
+    // A. Zeros are counted together, each NaN on its own
+    let counts: Vec<( f64, usize )> = test_bag.counts().map( | ( &value, count ) | ( value, count ) ).collect();
+    assert_eq!( counts.len(), 5 );
+    assert_eq!( &counts[ ..3 ], &[ ( 2.5, 2 ), ( -1.0, 1 ), ( 0.0, 2 ) ] );
+    assert!( counts[ 3 ].0.is_nan() && counts[ 4 ].0.is_nan() );
+    assert_eq!( test_bag.distinct().count(), 5 );
 
-    // Now trimming to size again.
-    test_bag.shrink_to_fit();
-    assert_eq!( test_bag.capacity(), 1 );
+    // B. The counts add up to the length
+    assert_eq!( test_bag.counts().map( | ( _, count ) | count ).sum::<usize>(), 7 );
 
-    // Inserting value 5000 into the bag.
-    // Inserting three 5's into the bag and then erasing all of them.
-    test_bag.insert( 5000.0 );
-    test_bag.insert( 5.0 ); 
-    test_bag.insert( 5.0 );
-    test_bag.insert( 5.0 );
-    assert_eq!( test_bag.erase( 5.0 ), 3 );
-    assert_eq!( test_bag.len(), 1 );
+    // C. Sorted counts, NaNs last
+    let sorted = test_bag.into_counts();
+    assert_eq!( &sorted[ ..3 ], &[ ( -1.0, 1 ), ( 0.0, 2 ), ( 2.5, 2 ) ] );
+    assert!( sorted[ 3 ].0.is_nan() && sorted[ 4 ].0.is_nan() );
 }
 
 #[ test ]
-///   Performs basic tests for the += and + functions
-fn test5()
+///   Performs a test of the most and least common values of the bag.
+fn test_most_common()
 {
-    let mut test_bag1 = Bag::new();
-    let mut test_bag2 = Bag::new();
-    let mut test_bag3 = Bag::new();
-
-    // Inserting 2000 1's into test_bag1 and 2000 2's into test_bag2.
+    let mut test_bag = ::bagf64::Bag::new();
 
-    for _ in 0..2000
+    for &value in &[ 0.5, 1.5, 1.5, -0.0, 0.0, 2.5 ]
     {
-        test_bag1.insert( 1.0 );
-        test_bag2.insert( 2.0 );
+        test_bag.insert( value );
     }
 
-    // Now testing the += function.
-    test_bag1 += test_bag2.clone();
-
-    //   and now testing for occurrences of 1's and 2's in test_bag1.
-    assert_eq!( test_bag1.occurrences( 1.0 ), 2000 ); 
-    assert_eq!( test_bag2.occurrences( 2.0 ), 2000 );
-
-    // Now testing the + function.
-    test_bag3.clone_from( &( test_bag2.clone() + test_bag2.clone() ) );
-
-    //   and now testing for occurrences of 2's in test_bag3.
-    assert_eq!( test_bag3.occurrences( 2.0 ), 4000 );
+    assert_eq!( test_bag.most_common( 2 ), vec![ ( &1.5, 2 ), ( &-0.0, 2 ) ] );
+    assert_eq!( test_bag.least_common( 2 ), vec![ ( &0.5, 1 ), ( &2.5, 1 ) ] );
+    assert!( test_bag.most_common( 0 ).is_empty() );
 }
 
 #[ test ]
-///   Performs tests for the bag iterator
-fn test6()
+///   Performs a test of writing the bag as text and reading it back.
+fn test_display_from_str()
 {
-    let mut testbag = Bag::new();
-
-    { // Testing next(), last(), and count()
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), None );
-        assert_eq!( iter.last(), None );
-        assert_eq!( testbag.into_iter().count(), 0 );
-    }
-
-    testbag.insert( 8.0 );
-
-    { // Testing next(), last(), and count() again
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), Some( &8.0 ) );
-        assert_eq!( iter.last(), Some( &8.0 ) );
-        assert_eq!( testbag.into_iter().count(), 1 );
-    }
-
-    testbag.insert( 3.0 );
-    testbag.insert( 4.0 );
-    testbag.insert( 7.0 );
-
-    { // Testing next(), last(), and count() again
-        let mut iter = testbag.into_iter();
-        assert_eq!( iter.next(), Some( &8.0 ) );
-        assert_eq!( iter.last(), Some( &7.0 ) );
-        assert_eq!( testbag.into_iter().count(), 4 );
-    }
-
-    testbag.insert( 2.0 );
+    let mut test_bag = ::bagf64::Bag::new();
 
+    for &value in &[ 1.5, 1.5, -0.0, f64::INFINITY, 1e300, 0.1 ]
     {
-        let mut iter = testbag.into_iter();
-
-        // Testing the full range of the bag with next()
-        assert_eq!( iter.next(), Some( &8.0 ) );
-        assert_eq!( iter.next(), Some( &3.0 ) );
-        assert_eq!( iter.next(), Some( &4.0 ) );
-        assert_eq!( iter.next(), Some( &7.0 ) );
-        assert_eq!( iter.next(), Some( &2.0 ) );
-
-        // ... and then None once it's over.
-        assert_eq!( iter.next(), None );
-
-        // More calls may or may not return None. Here, they always will.
-        assert_eq!( iter.next(), None );
-        assert_eq!( iter.next(), None );
+        test_bag.insert( value );
     }
-    { // Testing the nth( _ ) method
-        let mut iter = testbag.into_iter();
 
-        assert_eq!( iter.nth( 1 ), Some( &3.0 ) );
-        assert_eq!( iter.nth( 1 ), Some( &7.0 ) );
-        assert_eq!( iter.nth( 1 ), None );
-    }
-    {
-        let mut iter = testbag.into_iter();
+    assert_eq!( test_bag.to_string(), format!( "{{1.5×2, -0, inf, {}, 0.1}}", 1e300 ) );
+    assert_eq!( test_bag.to_string().parse::<::bagf64::Bag>(), Ok( test_bag.clone() ) );
 
-        assert_eq!( iter.nth( 0 ), Some( &8.0 ) );
-        assert_eq!( iter.nth( 2 ), Some( &7.0 ) );
-        assert_eq!( iter.nth( 9 ), None );
-    }
+    let error = "{1.5, one}".parse::<::bagf64::Bag>().unwrap_err();
+    assert_eq!( error.column, 7 );
+    assert!( "{1.5×0}".parse::<::bagf64::Bag>().is_err() );
 }
@@ -0,0 +1,407 @@
+// FILE: bagformat.rs
+
+//! The text format of the bags, written by their `Display` and read
+//! by their `FromStr` implementations.
+//!
+//! A bag is written as its elements between braces, separated by
+//! commas, with a run of equal elements written once followed by `×`
+//! and the length of the run:
+//!
+//! ```text
+//! {a, b×3, c}
+//! ```
+//!
+//! Whitespace around the elements and counts is ignored. A `\` makes
+//! the character after it part of the element, which is how the
+//! characters `\ , { } ×`, and whitespace at the start or end of an
+//! element, are written. An element whose text is empty is written
+//! as `\0` alone.
+//!
+//! Reading the text of a bag gives back an equal bag, holding the
+//! same elements in the same order. A bag read by `FromStr` holds at
+//! most `max_elements::<Type>()` elements, which fill at most
+//! `MAX_BYTES` of its array, so that a short text with a large count
+//! cannot exhaust memory. What each element holds on the heap, as a
+//! `String` does, is besides; a bag read from text that is not trusted
+//! can be given a lower limit with `parse_within( _, _ )`.
+
+use std::error::Error;
+use std::fmt::{ self, Display, Formatter };
+use std::mem;
+use std::str::FromStr;
+
+/// The most elements of a bag read by its `FromStr`, whatever their
+/// type.
+
+pub const MAX_ELEMENTS: usize = 1 << 26;
+
+/// The most bytes of the elements of a bag read by its `FromStr`,
+/// counted by the size of their type.
+
+pub const MAX_BYTES: usize = 1 << 26;
+
+/// The most elements of `Type` a bag read by its `FromStr` holds.
+///
+/// # Return
+/// The number of elements that fill `MAX_BYTES`, and no more than
+/// `MAX_ELEMENTS`.
+
+pub fn max_elements<Type>() -> usize
+{
+    ( MAX_BYTES / mem::size_of::<Type>().max( 1 ) ).min( MAX_ELEMENTS )
+}
+
+// The text of an empty element.
+const EMPTY_ELEMENT: &str = "\\0";
+
+/// Why the text of a bag could not be read.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum ParseBagErrorKind
+{
+    /// The text does not start with `{`.
+    ExpectedOpenBrace,
+    /// The text ends before the closing `}`.
+    ExpectedCloseBrace,
+    /// There is more than whitespace after the closing `}`.
+    TrailingText,
+    /// The text ends with a `\` that escapes nothing.
+    DanglingEscape,
+    /// An element is empty.
+    EmptyElement,
+    /// An element has more than one `×`.
+    RepeatedCount,
+    /// A count is not a number greater than 0.
+    InvalidCount( String ),
+    /// The bag would hold more elements than the limit it is read
+    /// with, or than `usize::MAX`.
+    TooManyElements,
+    /// The element type could not be read from the text,
+    /// with the error of the element type.
+    InvalidElement( String, String )
+}
+
+/// An error reading the text of a bag, with the column, counted in
+/// characters from 1, at which it was found.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub struct ParseBagError
+{
+    pub column: usize,
+    pub kind: ParseBagErrorKind
+}
+
+impl Display for ParseBagError
+{
+    /// Describes the error and where it was found.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "column {}: ", self.column )?;
+
+        match self.kind
+        {
+            ParseBagErrorKind::ExpectedOpenBrace => write!( fmt, "expected `{{` to start the bag" ),
+            ParseBagErrorKind::ExpectedCloseBrace => write!( fmt, "expected `}}` to end the bag" ),
+            ParseBagErrorKind::TrailingText => write!( fmt, "unexpected text after the end of the bag" ),
+            ParseBagErrorKind::DanglingEscape => write!( fmt, "`\\` at the end of the text escapes nothing" ),
+            ParseBagErrorKind::EmptyElement => write!( fmt, "expected an element" ),
+            ParseBagErrorKind::RepeatedCount => write!( fmt, "an element may have only one `×`" ),
+            ParseBagErrorKind::InvalidCount( ref count ) =>
+                write!( fmt, "`{}` is not a count greater than 0", count ),
+            ParseBagErrorKind::TooManyElements => write!( fmt, "the bag would hold too many elements" ),
+            ParseBagErrorKind::InvalidElement( ref text, ref error ) =>
+                write!( fmt, "`{}` is not a valid element: {}", text, error )
+        }
+    }
+}
+
+impl Error for ParseBagError {}
+
+/// Write the elements of a bag in the text format.
+///
+/// # Parameter: `elements`
+/// The elements of the bag in their stored order.
+///
+/// # Return
+/// An Err if writing to `fmt` failed.
+
+pub fn write_bag<'a, Value, Elements>( fmt: &mut Formatter, elements: Elements ) -> fmt::Result
+    where Value: 'a + Display + PartialEq, Elements: Iterator<Item = &'a Value>
+{
+    let mut elements = elements.peekable();
+
+    // each run of equal elements, as it is reached
+    let runs = ::std::iter::from_fn( move ||
+        {
+            let element = elements.next()?;
+            let mut count = 1;
+
+            while elements.peek() == Some( &element )
+            {
+                elements.next();
+                count += 1;
+            }
+
+            Some( ( element, count ) )
+        } );
+
+    write_runs( fmt, runs )
+}
+
+/// Write runs of elements in the text format.
+///
+/// # Parameter: `runs`
+/// Each element with the length of its run, which is greater than 0.
+///
+/// # Return
+/// An Err if writing to `fmt` failed.
+
+pub fn write_runs<Value, Runs>( fmt: &mut Formatter, runs: Runs ) -> fmt::Result
+    where Value: Display, Runs: Iterator<Item = ( Value, usize )>
+{
+    let mut first = true;
+
+    write!( fmt, "{{" )?;
+
+    for ( element, count ) in runs
+    {
+        if !first
+        {
+            write!( fmt, ", " )?;
+        }
+
+        write!( fmt, "{}", escape( &element.to_string() ) )?;
+
+        if count > 1
+        {
+            write!( fmt, "×{}", count )?;
+        }
+
+        first = false;
+    }
+
+    write!( fmt, "}}" )
+}
+
+// Escapes the characters of an element's text that have a meaning in the format.
+fn escape( text: &str ) -> String
+{
+    if text.is_empty()
+    {
+        return EMPTY_ELEMENT.to_string();
+    }
+
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len();
+    let mut escaped = String::with_capacity( text.len() );
+
+    for ( index, c ) in text.char_indices()
+    {
+        if "\\,{}×".contains( c ) || index < start || index >= end
+        {
+            escaped.push( '\\' );
+        }
+
+        escaped.push( c );
+    }
+
+    escaped
+}
+
+/// Read the text of a bag.
+///
+/// # Return
+/// Each run of equal elements, in order, as the element and the
+/// length of the run, or an Err describing the first problem found.
+
+pub fn parse_runs<Value: FromStr>( text: &str ) -> Result<Vec<( Value, usize )>, ParseBagError>
+    where Value::Err: Display
+{
+    parse_runs_within( text, usize::MAX )
+}
+
+/// Read the text of a bag of at most `limit` elements.
+///
+/// # Return
+/// Each run of equal elements, in order, as the element and the
+/// length of the run, or an Err describing the first problem found,
+/// which is `TooManyElements` at the count of the run that passes
+/// `limit`.
+
+pub fn parse_runs_within<Value: FromStr>( text: &str, limit: usize ) -> Result<Vec<( Value, usize )>, ParseBagError>
+    where Value::Err: Display
+{
+    let mut chars = text.chars().enumerate().peekable();
+    let error = | column: usize, kind | Err( ParseBagError { column: column + 1, kind } );
+
+    while chars.peek().map( | &( _, c ) | c.is_whitespace() ).unwrap_or( false )
+    {
+        chars.next();
+    }
+
+    let mut start = match chars.next()
+    {
+        Some( ( column, '{' ) ) => column + 1,
+        Some( ( column, _ ) ) => return error( column, ParseBagErrorKind::ExpectedOpenBrace ),
+        None => return error( 0, ParseBagErrorKind::ExpectedOpenBrace )
+    };
+
+    let mut runs = Vec::new();
+    let mut total: usize = 0;
+
+    // each element's characters, with whether each was escaped
+    let mut element: Vec<( char, bool )> = Vec::new();
+    let mut count: Option<( usize, String )> = None;
+
+    loop
+    {
+        let ( column, c ) = match chars.next()
+        {
+            Some( next ) => next,
+            None => return error( text.chars().count(), ParseBagErrorKind::ExpectedCloseBrace )
+        };
+
+        match c
+        {
+            '\\' =>
+                {
+                    let escaped = match chars.next()
+                    {
+                        Some( ( _, escaped ) ) => escaped,
+                        None => return error( column, ParseBagErrorKind::DanglingEscape )
+                    };
+
+                    match count
+                    {
+                        Some( ( _, ref mut digits ) ) => { digits.push( escaped ); }
+                        None => { element.push( ( escaped, true ) ); }
+                    }
+                }
+            '×' =>
+                {
+                    if count.is_some()
+                    {
+                        return error( column, ParseBagErrorKind::RepeatedCount );
+                    }
+
+                    count = Some( ( column, String::new() ) );
+                }
+            ',' | '}' =>
+                {
+                    // `{}` and `{ }` are the empty bag
+                    let empty_bag = c == '}' && runs.is_empty() && count.is_none()
+                                    && element.iter().all( | &( c, escaped ) | !escaped && c.is_whitespace() );
+
+                    if !empty_bag
+                    {
+                        let run = parse_run( &element, count.take(), start )?;
+
+                        total = match total.checked_add( run.1 )
+                        {
+                            Some( total ) if total <= limit => total,
+                            _ => return error( column, ParseBagErrorKind::TooManyElements )
+                        };
+
+                        runs.push( run );
+                    }
+
+                    element.clear();
+                    start = column + 1;
+
+                    if c == '}'
+                    {
+                        break;
+                    }
+                }
+            c =>
+                {
+                    match count
+                    {
+                        Some( ( _, ref mut digits ) ) => { digits.push( c ); }
+                        None => { element.push( ( c, false ) ); }
+                    }
+                }
+        }
+    }
+
+    if let Some( ( column, _ ) ) = chars.find( | &( _, c ) | !c.is_whitespace() )
+    {
+        return error( column, ParseBagErrorKind::TrailingText );
+    }
+
+    Ok( runs )
+}
+
+// Reads one element, and its count if it has one, starting at
+// the 0 based column `start`.
+fn parse_run<Value: FromStr>( element: &[( char, bool )], count: Option<( usize, String )>, start: usize )
+    -> Result<( Value, usize ), ParseBagError>
+    where Value::Err: Display
+{
+    let skipped = element.iter().take_while( | &&( c, escaped ) | !escaped && c.is_whitespace() ).count();
+    let kept = element.len() - element.iter().rev().take_while( | &&( c, escaped ) | !escaped && c.is_whitespace() ).count();
+    let element = &element[ skipped..kept.max( skipped ) ];
+
+    if element.is_empty()
+    {
+        return Err( ParseBagError { column: start + skipped + 1, kind: ParseBagErrorKind::EmptyElement } );
+    }
+
+    // `\0` alone is the empty element
+    let text: String = if element == [ ( '0', true ) ] { String::new() } else { element.iter().map( | &( c, _ ) | c ).collect() };
+
+    let value = text.parse::<Value>().map_err( | error |
+        ParseBagError { column: start + skipped + 1,
+                        kind: ParseBagErrorKind::InvalidElement( text.clone(), error.to_string() ) } )?;
+
+    let count = match count
+    {
+        Some( ( column, digits ) ) =>
+            {
+                match digits.trim().parse::<usize>()
+                {
+                    Ok( count ) if count > 0 => count,
+                    _ => return Err( ParseBagError { column: column + 2,
+                                                     kind: ParseBagErrorKind::InvalidCount( digits.trim().to_string() ) } )
+                }
+            }
+        None => 1
+    };
+
+    Ok( ( value, count ) )
+}
+
+#[ test ]
+///   Performs a test of the errors reading the text of a bag.
+fn test_parse_errors()
+{
+    let kind = | text: &str | parse_runs::<i32>( text ).unwrap_err().kind;
+    let column = | text: &str | parse_runs::<i32>( text ).unwrap_err().column;
+
+    assert_eq!( kind( "" ), ParseBagErrorKind::ExpectedOpenBrace );
+    assert_eq!( kind( "  1, 2}" ), ParseBagErrorKind::ExpectedOpenBrace );
+    assert_eq!( column( "  1, 2}" ), 3 );
+    assert_eq!( kind( "{1, 2" ), ParseBagErrorKind::ExpectedCloseBrace );
+    assert_eq!( kind( "{1} 2" ), ParseBagErrorKind::TrailingText );
+    assert_eq!( column( "{1} 2" ), 5 );
+    assert_eq!( kind( "{1\\" ), ParseBagErrorKind::DanglingEscape );
+    assert_eq!( kind( "{1, , 2}" ), ParseBagErrorKind::EmptyElement );
+    assert_eq!( column( "{1, , 2}" ), 5 );
+    assert_eq!( kind( "{1×2×3}" ), ParseBagErrorKind::RepeatedCount );
+    assert_eq!( kind( "{1×0}" ), ParseBagErrorKind::InvalidCount( "0".to_string() ) );
+    assert_eq!( kind( "{1×}" ), ParseBagErrorKind::InvalidCount( "".to_string() ) );
+    assert_eq!( column( "{1× x}" ), 4 );
+    assert_eq!( kind( &format!( "{{1×{}, 2×{}}}", usize::MAX, 1 ) ), ParseBagErrorKind::TooManyElements );
+    assert_eq!( parse_runs_within::<i32>( "{1×3, 2×2}", 4 ).unwrap_err().column, 10 );
+    assert_eq!( parse_runs_within::<i32>( "{1×3, 2}", 4 ), Ok( vec![ ( 1, 3 ), ( 2, 1 ) ] ) );
+
+    let error = parse_runs::<i32>( "{1, 2x}" ).unwrap_err();
+    assert_eq!( error.column, 5 );
+    assert_eq!( error.to_string(), "column 5: `2x` is not a valid element: invalid digit found in string" );
+
+    assert_eq!( parse_runs::<i32>( " { } " ), Ok( Vec::new() ) );
+    assert_eq!( parse_runs::<i32>( "{ 1 × 2 ,3}" ), Ok( vec![ ( 1, 2 ), ( 3, 1 ) ] ) );
+    assert_eq!( parse_runs::<String>( "{\\0×2, \\0\\0, \\0 }" ),
+                Ok( vec![ ( String::new(), 2 ), ( "00".to_string(), 1 ), ( String::new(), 1 ) ] ) );
+}
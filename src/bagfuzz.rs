@@ -0,0 +1,407 @@
+// FILE: bagfuzz.rs
+
+//! Fuzz targets for the bags.
+//!
+//! Each target takes an arbitrary slice of bytes and must not panic,
+//! other than by a failed check of its own, for any input. The targets
+//! take plain `&[u8]` so they can be driven by any fuzzer.
+//!
+//! Run with `cargo run --release -- fuzz <target> [FILE...]`, which
+//! runs the target on each file given, or on random inputs if there
+//! are none.
+//!
+//! # Targets
+//!
+//! * `operations` Interprets the bytes as operations on each bag,
+//! see `fuzz_operations`
+//!
+//! * `parse` Feeds the bytes to the parsers of bag contents,
+//! see `fuzz_parse`
+
+extern crate rand;
+
+use bagtrait::BagOps;
+use bagcli;
+use bagformat;
+use bagcsv::{ self, CsvOptions };
+use bagcount::{ self, CountOptions };
+use bag::Bag;
+use bag::len_trait::Len;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Add;
+use std::panic;
+
+/// The names of the fuzz targets.
+
+pub const TARGETS: [&str; 2] = [ "operations", "parse" ];
+
+/// Run a fuzz target on one input.
+///
+/// # Return
+/// False if there is no target named `target`.
+
+pub fn fuzz( target: &str, data: &[u8] ) -> bool
+{
+    match target
+    {
+        "operations" => fuzz_operations( data ),
+        "parse" => fuzz_parse( data ),
+        _ => return false
+    }
+
+    true
+}
+
+/// Interpret `data` as a sequence of operations, applying them to a
+/// `bag::Bag`, a `bagVec::Bag` and a `bagf64::Bag`.
+///
+/// Each operation is an opcode byte followed by its operands:
+///
+/// * `insert`, `erase` and `erase_one` of a value
+///
+/// * `reserve` of a capacity, skipping the documented panic for 0
+///
+/// * `shrink_to_fit`, `clear` and `clone`
+///
+/// * `+=` and `+` of a bag of up to 7 values
+///
+/// * a sequence of `next`, `nth`, `count`, `last`, `next_back`, `nth_back`
+/// and `rev` calls on an iterator, where `nth` and `nth_back` may be given
+/// offsets up to `usize::MAX`
+///
+/// # Panics
+/// If the bags disagree with each other or with a count of
+/// each value, or an iterator disagrees with the elements it
+/// iterates over.
+
+pub fn fuzz_operations( data: &[u8] )
+{
+    run_operations::<::bag::Bag<u8>>( data );
+    run_operations::<::bagVec::Bag<u8>>( data );
+    run_operations::<::bagf64::Bag>( data );
+}
+
+fn run_operations<B: BagOps + Add<Output = B> + Debug>( data: &[u8] )
+    where B::Item: From<u8> + Debug
+{
+    let mut bag = B::default();
+    let mut model = [ 0usize; 256 ];
+    let mut bytes = data.iter().cloned();
+
+    while let Some( opcode ) = bytes.next()
+    {
+        let operand = bytes.next().unwrap_or( 0 );
+
+        match opcode % 10
+        {
+            0 =>
+                {
+                    bag.insert( B::Item::from( operand ) );
+                    model[ operand as usize ] += 1;
+                }
+            1 =>
+                {
+                    assert_eq!( bag.erase( B::Item::from( operand ) ), model[ operand as usize ] );
+                    model[ operand as usize ] = 0;
+                }
+            2 =>
+                {
+                    let present = model[ operand as usize ] > 0;
+                    assert_eq!( bag.erase_one( B::Item::from( operand ) ), present );
+
+                    if present
+                    {
+                        model[ operand as usize ] -= 1;
+                    }
+                }
+            3 =>
+                {
+                    // reserve( 0 ) is documented to panic
+                    if operand > 0
+                    {
+                        bag.reserve( operand as usize * 4 );
+                    }
+                }
+            4 => { bag.shrink_to_fit(); }
+            5 | 6 =>
+                {
+                    let mut other = B::default();
+
+                    for value in bytes.by_ref().take( operand as usize % 8 )
+                    {
+                        other.insert( B::Item::from( value ) );
+                        model[ value as usize ] += 1;
+                    }
+
+                    if opcode % 10 == 5
+                    {
+                        bag += other;
+                    }
+                    else
+                    {
+                        let sum = bag.clone() + other.clone();
+                        check( &sum, &model );
+
+                        bag += other;
+                        assert_eq!( sum, bag );
+                    }
+                }
+            7 =>
+                {
+                    bag.clear();
+                    model = [ 0; 256 ];
+                }
+            8 =>
+                {
+                    let copy = bag.clone();
+                    assert_eq!( copy, bag );
+                    bag.clone_from( &copy );
+                }
+            _ => { fuzz_iterator( &bag, &mut bytes, operand ); }
+        }
+
+        check( &bag, &model );
+    }
+}
+
+// Checks the bag's length and capacity and the number of each value.
+fn check<B: BagOps + Debug>( bag: &B, model: &[usize; 256] )
+    where B::Item: From<u8> + Debug
+{
+    assert_eq!( bag.len(), model.iter().sum::<usize>(), "{:?}", bag );
+    assert!( bag.len() <= bag.capacity(), "{:?}", bag );
+
+    for ( value, &expected ) in model.iter().enumerate()
+    {
+        if expected > 0
+        {
+            assert_eq!( bag.occurrences( B::Item::from( value as u8 ) ), expected, "{:?}", bag );
+        }
+    }
+}
+
+// Runs `steps` iterator calls read from `bytes` on an iterator of the
+// bag, comparing each result with the same call on a slice iterator.
+fn fuzz_iterator<B: BagOps, Bytes: Iterator<Item = u8>>( bag: &B, bytes: &mut Bytes, steps: u8 )
+    where B::Item: Debug
+{
+    let elements: Vec<&B::Item> = bag.iter().collect();
+    assert_eq!( elements.len(), bag.len() );
+
+    let mut iter = bag.iter();
+    let mut expected = elements.iter().cloned();
+
+    for _ in 0..( steps % 16 )
+    {
+        let call = bytes.next().unwrap_or( 0 );
+
+        // offsets near usize::MAX check for overflow
+        let n = if call >= 200 { usize::MAX - ( 255 - call ) as usize } else { ( call / 8 ) as usize % 8 };
+
+        match call % 8
+        {
+            0 => assert_eq!( iter.next(), expected.next() ),
+            1 => assert_eq!( iter.nth( n ), expected.nth( n ) ),
+            2 =>
+                {
+                    assert_eq!( iter.count(), expected.count() );
+                    return;
+                }
+            3 =>
+                {
+                    assert_eq!( iter.last(), expected.next_back() );
+                    return;
+                }
+            4 => assert_eq!( iter.next_back(), expected.next_back() ),
+            5 => assert_eq!( iter.nth_back( n ), expected.nth_back( n ) ),
+            6 =>
+                {
+                    assert_eq!( iter.rev().collect::<Vec<_>>(), expected.rev().collect::<Vec<_>>() );
+                    return;
+                }
+            _ => {}
+        }
+
+        assert_eq!( iter.len(), expected.len() );
+        assert_eq!( iter.size_hint(), expected.size_hint() );
+    }
+
+    // once finished the iterator stays finished
+    if iter.len() == 0
+    {
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+    }
+}
+
+/// Feed `data`, as text, to each parser of bag contents: the number
+/// loader of the `stats` command, the option parser and tokenizers
+/// of the `count` command, the text format of the bags and CSV.
+///
+/// # Panics
+/// If a parser accepts text and then builds a bag that
+/// disagrees with the text, or a bag's text is not read back
+/// as an equal bag.
+
+pub fn fuzz_parse( data: &[u8] )
+{
+    let text = String::from_utf8_lossy( data );
+
+    if let Ok( numbers ) = bagcli::load_numbers( &text )
+    {
+        assert_eq!( numbers.len(), text.split_whitespace().count() );
+    }
+
+    let args: Vec<String> = text.split_whitespace().map( | arg | arg.to_string() ).collect();
+    let _ = CountOptions::parse( &args );
+
+    for options in &[ "--words", "--lines", "-d ,", "-i -d \u{0}" ]
+    {
+        let args: Vec<String> = options.split( ' ' ).map( | arg | arg.to_string() ).collect();
+        let options = CountOptions::parse( &args ).unwrap();
+        let mut tokens = Bag::new();
+
+        bagcount::add_tokens( &mut tokens, &text, &options );
+
+        let counts = bagcount::top( &tokens, None );
+        assert_eq!( counts.iter().map( | &( _, count ) | count ).sum::<usize>(), tokens.len() );
+
+        let written = tokens.to_string();
+        assert_eq!( written.parse::<Bag<String>>().as_ref(), Ok( &tokens ), "{}", written );
+    }
+
+    let _ = bagformat::parse_runs::<f64>( &text );
+
+    for &delimiter in &[ ',', ';', '\t', ' ' ]
+    {
+        let options = CsvOptions { delimiter, .. CsvOptions::default() };
+
+        if let Ok( records ) = bagcsv::parse_records( &text, delimiter )
+        {
+            assert!( records.windows( 2 ).all( | pair | pair[ 0 ].row < pair[ 1 ].row ) );
+        }
+
+        let _ = bagcsv::read_f64_bag( &mut text.as_bytes(), &options );
+
+        if let Ok( bag ) = bagcsv::read_bag::<String, _>( &mut text.as_bytes(), &options )
+        {
+            let mut written = Vec::new();
+            bagcsv::write_bag( &mut written, &bag, &options ).unwrap();
+            assert_eq!( bagcsv::read_bag( &mut &written[ .. ], &options ).as_ref(), Ok( &bag ) );
+        }
+    }
+
+    if let Ok( runs ) = bagformat::parse_runs::<String>( &text )
+    {
+        let len: usize = runs.iter().map( | &( _, count ) | count ).sum();
+
+        // counts can ask for more elements than there is memory for
+        if len <= 10_000
+        {
+            let bag = text.parse::<Bag<String>>().unwrap();
+            assert_eq!( bag.len(), len );
+            assert_eq!( bag.to_string().parse::<Bag<String>>().as_ref(), Ok( &bag ) );
+        }
+    }
+}
+
+/// Run the `fuzz` command.
+///
+/// # Parameter: `args`
+/// The target name, then either input files or `--iterations N`
+/// for the number of random inputs to run, 10000 by default.
+///
+/// # Return
+/// An Err with a message for the user if the arguments are invalid,
+/// an input could not be read or the target failed on an input.
+
+pub fn run( args: &[String] ) -> Result<(), String>
+{
+    let target = args.first().ok_or_else( || format!( "fuzz requires a target: {}", TARGETS.join( ", " ) ) )?;
+
+    if !TARGETS.contains( &target.as_str() )
+    {
+        return Err( format!( "unknown fuzz target `{}`, expected one of: {}", target, TARGETS.join( ", " ) ) );
+    }
+
+    let args = &args[ 1.. ];
+
+    if args.first().map( | arg | arg == "--iterations" ).unwrap_or( false ) || args.is_empty()
+    {
+        let iterations = match args.get( 1 )
+        {
+            Some( count ) => count.parse::<usize>().map_err( | _ | format!( "`{}` is not a valid count", count ) )?,
+            None if args.is_empty() => 10_000,
+            None => return Err( "--iterations requires a value".to_string() )
+        };
+
+        for _ in 0..iterations
+        {
+            let len = rand::random::<usize>() % 256;
+            let data: Vec<u8> = ( 0..len ).map( | _ | rand::random::<u8>() ).collect();
+
+            run_input( target, &data, "a random input" )?;
+        }
+
+        println!( "{} random inputs passed", iterations );
+        return Ok( () );
+    }
+
+    for path in args
+    {
+        let mut data = Vec::new();
+
+        File::open( path ).and_then( | mut file | file.read_to_end( &mut data ) )
+                          .map_err( | error | format!( "could not read `{}`: {}", path, error ) )?;
+
+        run_input( target, &data, path )?;
+    }
+
+    println!( "{} inputs passed", args.len() );
+    Ok( () )
+}
+
+// Runs the target on one input, reporting a panic with the input.
+fn run_input( target: &str, data: &[u8], name: &str ) -> Result<(), String>
+{
+    panic::catch_unwind( || { fuzz( target, data ); } )
+        .map_err( | _ | format!( "{} failed on {}: {:?}", target, name, data ) )
+}
+
+#[ test ]
+///   Performs the fuzz targets on random inputs.
+fn test_random()
+{
+    for target in &TARGETS
+    {
+        for _ in 0..2000
+        {
+            let len = rand::random::<usize>() % 128;
+            let data: Vec<u8> = ( 0..len ).map( | _ | rand::random::<u8>() ).collect();
+
+            fuzz( target, &data );
+        }
+    }
+}
+
+#[ test ]
+///   Performs the operations target on the iterator inputs it has failed on.
+fn test_iterator_regressions()
+{
+    // insert 1, insert 2, then iterate: next(), nth( usize::MAX - 6 )
+    fuzz_operations( &[ 0, 1, 0, 2, 9, 2, 0, 249 ] );
+
+    // insert 1, insert 2, then iterate: nth( usize::MAX - 14 ), count()
+    fuzz_operations( &[ 0, 1, 0, 2, 9, 2, 241, 2 ] );
+
+    // insert 1, insert 2, then iterate: next(), next(), next(), count()
+    fuzz_operations( &[ 0, 1, 0, 2, 9, 4, 0, 0, 0, 2 ] );
+
+    // insert 1, insert 2, then iterate: nth_back( usize::MAX - 2 ), next()
+    fuzz_operations( &[ 0, 1, 0, 2, 9, 2, 253, 0 ] );
+
+    // insert 1, then iterate: next(), last()
+    fuzz_operations( &[ 0, 1, 9, 2, 0, 3 ] );
+}
@@ -0,0 +1,275 @@
+// FILE: baghyperloglog.rs
+
+//! A HyperLogLog estimator of the number of distinct values in a
+//! bag or stream, using memory that depends only on its precision.
+//!
+//! The hash of each value chooses one of `2^precision` registers, and
+//! the register keeps the most leading zeros seen in the rest of the
+//! hashes given to it. The estimate is made from the harmonic mean of
+//! the registers, and has a standard error of about
+//! `1.04 / sqrt( 2^precision )`.
+//!
+//! It is fed with `insert( _ )` or `extend( _ )`, from the elements
+//! of a bag as in `estimator.extend( &bag )`, or from any iterator.
+//!
+//! The type used must have the trait `Hash`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{ self, Debug, Formatter };
+use std::hash::{ Hash, Hasher };
+use std::marker::PhantomData;
+use std::ops::{ Add, AddAssign };
+
+/// The least precision allowed.
+
+pub const MIN_PRECISION: u32 = 4;
+
+/// The greatest precision allowed.
+
+pub const MAX_PRECISION: u32 = 16;
+
+/// An estimator of the number of distinct values inserted.
+///
+/// # Invariant of the HyperLogLog struct:
+/// 1. There are `2^precision` registers.
+///
+/// 2. Each register holds the greatest rank, the position of the first
+/// 1 bit after the register's index, of the hashes of the values
+/// given to it, or 0 if no value has been.
+
+pub struct HyperLogLog<Type: Hash>
+{
+    registers: Vec<u8>,
+    precision: u32,
+    marker: PhantomData<Type>
+}
+
+impl<Type: Hash> HyperLogLog<Type>
+{
+    /// Initialize an estimator that has seen no values.
+    ///
+    /// # Parameter: `precision`
+    /// The number of bits of the hash choosing a register, from
+    /// `MIN_PRECISION` to `MAX_PRECISION`. Each more bit doubles the
+    /// memory used and reduces the error by a factor of `sqrt( 2 )`.
+    ///
+    /// # Panics
+    /// `precision` given is not from `MIN_PRECISION` to `MAX_PRECISION`.
+    ///
+    /// # Examples
+    /// ```
+    /// let estimator: HyperLogLog<u32> = HyperLogLog::new( 12 );
+    ///
+    /// assert_eq!( estimator.estimate_distinct(), 0 );
+    /// ```
+
+    pub fn new( precision: u32 ) -> Self
+    {
+        if !( MIN_PRECISION..=MAX_PRECISION ).contains( &precision )
+        {
+            panic!( "precision must be from {} to {}", MIN_PRECISION, MAX_PRECISION );
+        }
+
+        HyperLogLog { registers: vec![ 0; 1 << precision ], precision, marker: PhantomData }
+    }
+
+    /// The number of bits of the hash choosing a register.
+
+    pub fn precision( &self ) -> u32
+    {
+        self.precision
+    }
+
+    /// The standard error of the estimate, as a fraction of the
+    /// number of distinct values.
+
+    pub fn relative_error( &self ) -> f64
+    {
+        1.04 / ( self.registers.len() as f64 ).sqrt()
+    }
+
+    /// Whether no value has been seen.
+
+    pub fn is_empty( &self ) -> bool
+    {
+        self.registers.iter().all( | &register | register == 0 )
+    }
+
+    /// Forget every value seen, keeping the precision.
+
+    pub fn clear( &mut self )
+    {
+        self.registers.iter_mut().for_each( | register | *register = 0 );
+    }
+
+    /// Add a value to those seen by the estimator.
+    ///
+    /// # Postcondition
+    /// The register chosen by the hash of `new_item` holds at least the
+    /// rank of the hash. The value itself is not kept.
+
+    pub fn insert( &mut self, new_item: Type )
+    {
+        self.insert_hash( &new_item );
+    }
+
+    // Updates the register of the hash of `item`.
+    fn insert_hash<Item: Hash + ?Sized>( &mut self, item: &Item )
+    {
+        let mut hasher = DefaultHasher::new();
+        item.hash( &mut hasher );
+
+        let hash = hasher.finish();
+        let index = ( hash >> ( 64 - self.precision ) ) as usize;
+
+        // the rank of the remaining bits, counting from 1
+        let rank = ( ( hash << self.precision ).leading_zeros() + 1 ).min( 64 - self.precision + 1 ) as u8;
+
+        if rank > self.registers[ index ]
+        {
+            self.registers[ index ] = rank;
+        }
+    }
+
+    /// Estimate the number of distinct values seen.
+    ///
+    /// # Postcondition
+    /// This method does not alter the estimator.
+    ///
+    /// # Return
+    /// The estimate, which is within `relative_error()` of the
+    /// true number about two times in three.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut estimator: HyperLogLog<i32> = HyperLogLog::new( 12 );
+    /// estimator.extend( vec![ 1, 2, 2, 3 ] );
+    ///
+    /// assert_eq!( estimator.estimate_distinct(), 3 );
+    /// ```
+
+    pub fn estimate_distinct( &self ) -> usize
+    {
+        let registers = self.registers.len() as f64;
+        let alpha = match self.registers.len()
+        {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / ( 1.0 + 1.079 / registers )
+        };
+
+        let sum: f64 = self.registers.iter().map( | &rank | ( -f64::from( rank ) ).exp2() ).sum();
+        let estimate = alpha * registers * registers / sum;
+        let empty = self.registers.iter().filter( | &&rank | rank == 0 ).count();
+
+        // small numbers of values are better estimated by the empty registers
+        if estimate <= 2.5 * registers && empty > 0
+        {
+            ( registers * ( registers / empty as f64 ).ln() ).round() as usize
+        }
+        else
+        {
+            estimate.round() as usize
+        }
+    }
+}
+
+impl<Type: Hash> Extend<Type> for HyperLogLog<Type>
+{
+    /// Add each value of a stream to those seen by the estimator.
+
+    fn extend<Values: IntoIterator<Item = Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert_hash( &value );
+        }
+    }
+}
+
+impl<'a, Type: 'a + Hash> Extend<&'a Type> for HyperLogLog<Type>
+{
+    /// Add each value of a bag, or other stream of references, to
+    /// those seen by the estimator.
+
+    fn extend<Values: IntoIterator<Item = &'a Type>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert_hash( value );
+        }
+    }
+}
+
+impl<Type: Hash> Clone for HyperLogLog<Type>
+{
+    /// Copy the registers of the estimator.
+
+    fn clone( &self ) -> Self
+    {
+        HyperLogLog { registers: self.registers.clone(), precision: self.precision, marker: PhantomData }
+    }
+}
+
+impl<Type: Hash> PartialEq for HyperLogLog<Type>
+{
+    /// Whether two estimators have the same precision and registers.
+
+    fn eq( &self, other: &HyperLogLog<Type> ) -> bool
+    {
+        self.precision == other.precision && self.registers == other.registers
+    }
+}
+
+impl<Type: Hash> AddAssign for HyperLogLog<Type>
+{
+    /// Add the values seen by another estimator to those seen by this one.
+    ///
+    /// # Postcondition
+    /// The estimator is the same as one fed the values of both.
+    ///
+    /// # Panics
+    /// The estimators do not have the same precision.
+
+    fn add_assign( &mut self, addend: HyperLogLog<Type> )
+    {
+        if self.precision != addend.precision
+        {
+            panic!( "cannot merge an estimator of precision {} into one of precision {}",
+                    addend.precision, self.precision );
+        }
+
+        for ( register, added ) in self.registers.iter_mut().zip( addend.registers )
+        {
+            *register = ( *register ).max( added );
+        }
+    }
+}
+
+impl<Type: Hash> Add for HyperLogLog<Type>
+{
+    type Output = HyperLogLog<Type>;
+
+    /// Merge two estimators into a new estimator.
+    ///
+    /// # Panics
+    /// The estimators do not have the same precision.
+
+    fn add( mut self, addend: HyperLogLog<Type> ) -> HyperLogLog<Type>
+    {
+        self += addend;
+        self
+    }
+}
+
+impl<Type: Hash> Debug for HyperLogLog<Type>
+{
+    /// Renders the precision and the estimate.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "HyperLogLog of precision {} estimating {} distinct values",
+                self.precision, self.estimate_distinct() )
+    }
+}
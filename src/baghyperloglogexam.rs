@@ -0,0 +1,133 @@
+// FILE: baghyperloglogexam.rs
+
+//! This module holds tests for the distinct value estimator of
+//! baghyperloglog and the exact distinct_len() of the bags
+//!
+//! 1) tests of the precision and of small numbers of values
+//!
+//! 2) tests of the error of the estimate on large numbers of values
+//!
+//! 3) tests of merging with += and +
+//!
+//! 4) tests of feeding the estimator from the bags against distinct_len()
+//!
+//! Run with `cargo test`
+
+use bagf64::NumericCounts;
+use baghyperloglog::HyperLogLog;
+
+#[ test ]
+///   Performs tests of the precision and of small numbers of values.
+fn test1()
+{
+    let mut estimator = HyperLogLog::new( 10 );
+
+    // A. An empty estimator.
+    assert!( estimator.is_empty() );
+    assert_eq!( estimator.estimate_distinct(), 0 );
+    assert_eq!( estimator.precision(), 10 );
+    assert!( ( estimator.relative_error() - 1.04 / 32.0 ).abs() < 1e-12 );
+
+    // B. Duplicates are not counted again.
+    for _ in 0..5
+    {
+        estimator.insert( "a" );
+        estimator.insert( "b" );
+    }
+
+    assert_eq!( estimator.estimate_distinct(), 2 );
+
+    // C. Small numbers of values are all but exact.
+    estimator.extend( ( 0..100 ).map( | n | if n % 2 == 0 { "even" } else { "odd" } ) );
+    assert_eq!( estimator.estimate_distinct(), 4 );
+
+    estimator.clear();
+    assert!( estimator.is_empty() );
+}
+
+#[ test ]
+///   Performs tests of the error of the estimate on large numbers of values.
+fn test2()
+{
+    for &precision in &[ 8, 12, 14 ]
+    {
+        for &distinct in &[ 1000, 100_000 ]
+        {
+            let mut estimator = HyperLogLog::new( precision );
+
+            // every value twice
+            estimator.extend( ( 0..distinct ).chain( 0..distinct ) );
+
+            let error = ( estimator.estimate_distinct() as f64 - distinct as f64 ).abs() / distinct as f64;
+
+            // four standard errors
+            assert!( error < 4.0 * estimator.relative_error(),
+                     "precision {}: estimated {} of {}", precision, estimator.estimate_distinct(), distinct );
+        }
+    }
+}
+
+#[ test ]
+///   Performs tests of merging with += and +.
+fn test3()
+{
+    let mut left = HyperLogLog::new( 12 );
+    let mut right = HyperLogLog::new( 12 );
+    let mut both = HyperLogLog::new( 12 );
+
+    left.extend( 0..30_000u32 );
+    right.extend( 20_000..50_000u32 );
+    both.extend( 0..50_000u32 );
+
+    // A. Merging is the same as feeding both, counting the overlap once.
+    assert_eq!( left.clone() + right.clone(), both );
+
+    left += right;
+    assert_eq!( left.estimate_distinct(), both.estimate_distinct() );
+}
+
+#[ test ]
+///   Performs tests of feeding the estimator from the bags against distinct_len().
+fn test4()
+{
+    let mut test_bag = ::bag::Bag::new();
+    let mut test_bag_vec = ::bagVec::Bag::new();
+    let mut test_bagf64 = ::bagf64::Bag::new();
+
+    for value in 0..3000
+    {
+        test_bag.insert( value % 700 );
+        test_bag_vec.insert( value % 7 );
+        test_bagf64.insert( ( value % 50 ) as f64 );
+    }
+
+    // A. The exact number of distinct values.
+    assert_eq!( test_bag.distinct_len(), 700 );
+    assert_eq!( test_bag_vec.distinct_len(), 7 );
+    assert_eq!( test_bagf64.distinct_len(), 50 );
+
+    // B. The estimate from the same bag.
+    let mut estimator: HyperLogLog<i32> = HyperLogLog::new( 12 );
+    estimator.extend( &test_bag );
+
+    let error = ( estimator.estimate_distinct() as f64 - 700.0 ).abs() / 700.0;
+    assert!( error < 4.0 * estimator.relative_error() );
+
+    let mut estimator: HyperLogLog<i32> = HyperLogLog::new( 12 );
+    estimator.extend( &test_bag_vec );
+    assert_eq!( estimator.estimate_distinct(), test_bag_vec.distinct_len() );
+
+    // C. Numbers are fed by their bits, with -0.0 and 0.0 made the same.
+    let mut estimator = HyperLogLog::new( 12 );
+    estimator.extend( test_bagf64.distinct().map( | value | value.to_bits() ) );
+    assert_eq!( estimator.estimate_distinct(), 50 );
+}
+
+#[ test ]
+#[ should_panic ]
+///   Performs a test of merging estimators of different precisions.
+fn test5()
+{
+    let mut left: HyperLogLog<u32> = HyperLogLog::new( 10 );
+    left += HyperLogLog::new( 11 );
+}
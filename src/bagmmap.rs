@@ -0,0 +1,655 @@
+// FILE: bagmmap.rs
+
+//! A read-only bag of numbers kept in a file in the bag binary format
+//! and memory-mapped, so that a bag larger than memory can be counted,
+//! iterated and summarized without reading it onto the heap.
+//!
+//! `write_bag( _, _ )` writes a `bag::Bag` of numbers, such as a `bagf64::Bag`,
+//! in the binary format, and `write_values( _, _ )` writes numbers from
+//! any iterator, so a large file need not be held in a bag first.
+//! `MappedBag::open( _ )` maps such a file and reads each number from
+//! the mapped bytes when it is asked for; the operating system pages
+//! the file in and out as it is read. Opening is `unsafe`, as the
+//! mapped bytes are only sound while no one else changes the file.
+//!
+//! The answers of `occurrences( _ )` and of the aggregates are those
+//! of a `bag::Bag` holding the same numbers in the same order.
+//!
+//! # The binary format
+//! A file is a 16 byte header followed by the numbers:
+//!
+//! ```text
+//! magic: "BAGN" | version: u8 | type: u8 | reserved: 2 bytes | count: u64 LE | numbers LE
+//! ```
+//!
+//! where `version` is 1, `type` is the code of the type of number,
+//! the reserved bytes are 0, and `count` numbers follow, each written
+//! little-endian in the size of its type. The codes are `b`, `h`, `i`
+//! and `q` for `i8`, `i16`, `i32` and `i64`, `B`, `H`, `I` and `Q` for
+//! the unsigned types of those sizes, and `f` and `d` for `f32` and
+//! `f64`.
+//!
+//! # Note
+//! On Unix the file is mapped with `mmap`; elsewhere it is read into
+//! memory when opened. The file must not be truncated or written while
+//! it is mapped: a number read from a part cut off stops the program
+//! with a bus error, and the bytes of a part written over change under
+//! the borrows the bag hands out, which is undefined behavior.
+
+use bag::Bag;
+use bagnum::{ self, Numeric, Len, Empty };
+use std::convert::TryFrom;
+use std::fmt::{ self, Debug, Formatter };
+use std::fs::File;
+use std::io::{ self, Write };
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::slice::ChunksExact;
+
+// The bytes of the header before the numbers.
+const HEADER_LEN: usize = 16;
+
+// The first bytes of every file in the binary format.
+const MAGIC: &[u8; 4] = b"BAGN";
+
+// The version of the binary format written.
+const VERSION: u8 = 1;
+
+// The numbers written to a writer at a time.
+const WRITE_CHUNK: usize = 8192;
+
+/// The numbers a bag in the binary format can hold, which are those
+/// of `Numeric` of a fixed size.
+
+pub trait Binary: Numeric
+{
+    /// The code of the type in the header of a file.
+    const CODE: u8;
+
+    /// The number of bytes of each number in a file.
+    const SIZE: usize;
+
+    /// Append this number to `bytes`, little-endian.
+    fn write_le( self, bytes: &mut Vec<u8> );
+
+    /// The number in the first `SIZE` bytes of `bytes`, little-endian.
+    fn read_le( bytes: &[u8] ) -> Self;
+}
+
+// Implements Binary for each type with its code, and names the type
+// of each code for the errors of opening a file.
+macro_rules! binary_numbers
+{
+    ( $( $number:ident => $code:expr ),* ) =>
+    {
+        $(
+            impl Binary for $number
+            {
+                const CODE: u8 = $code;
+                const SIZE: usize = ::std::mem::size_of::<$number>();
+
+                fn write_le( self, bytes: &mut Vec<u8> )
+                {
+                    bytes.extend_from_slice( &self.to_le_bytes() );
+                }
+
+                fn read_le( bytes: &[u8] ) -> Self
+                {
+                    let mut le = [ 0; ::std::mem::size_of::<$number>() ];
+                    le.copy_from_slice( &bytes[ ..Self::SIZE ] );
+
+                    $number::from_le_bytes( le )
+                }
+            }
+        )*
+
+        // The name of the type of number of `code`.
+        fn type_name( code: u8 ) -> Option<&'static str>
+        {
+            match code
+            {
+                $( $code => Some( stringify!( $number ) ), )*
+                _ => None
+            }
+        }
+    };
+}
+
+binary_numbers!( i8 => b'b', i16 => b'h', i32 => b'i', i64 => b'q',
+                 u8 => b'B', u16 => b'H', u32 => b'I', u64 => b'Q',
+                 f32 => b'f', f64 => b'd' );
+
+/// Write a bag in the binary format.
+///
+/// # Parameter: `bag`
+/// The bag written, whose numbers are written in its order.
+///
+/// # Parameter: `out`
+/// Where the file is written. The numbers are written in large
+/// chunks, so `out` need not be buffered.
+///
+/// # Return
+/// An Err if `out` could not be written.
+///
+/// # Examples
+/// ```
+/// let mut bag = bagf64::Bag::new();
+/// bag.insert( 2.5 );
+/// bag.insert( 4.0 );
+///
+/// let mut file = Vec::new();
+/// write_bag( &bag, &mut file ).unwrap();
+///
+/// assert_eq!( file.len(), 16 + 2 * 8 );
+/// ```
+
+pub fn write_bag<Type: Binary, Out: Write>( bag: &Bag<Type>, out: Out ) -> io::Result<()>
+{
+    write_values( bag.into_iter().cloned(), out )
+}
+
+/// Write numbers in the binary format, as a bag holding them in order.
+///
+/// # Parameter: `values`
+/// The numbers, as many as the `len()` of the iterator.
+///
+/// # Parameter: `out`
+/// Where the file is written.
+///
+/// # Return
+/// An Err if `out` could not be written, or of kind `InvalidInput`
+/// if the iterator gave another number of numbers than its `len()`,
+/// in which case what was written is not a valid file.
+///
+/// # Examples
+/// ```
+/// let mut file = Vec::new();
+/// write_values( ( 0..1000 ).map( | value | value as f32 ), &mut file ).unwrap();
+/// ```
+
+pub fn write_values<Type, Values, Out>( values: Values, mut out: Out ) -> io::Result<()>
+    where Type: Binary, Values: ExactSizeIterator<Item = Type>, Out: Write
+{
+    let count = values.len();
+    let mut bytes = Vec::with_capacity( HEADER_LEN.max( WRITE_CHUNK * Type::SIZE ) );
+
+    bytes.extend_from_slice( MAGIC );
+    bytes.extend_from_slice( &[ VERSION, Type::CODE, 0, 0 ] );
+    bytes.extend_from_slice( &( count as u64 ).to_le_bytes() );
+
+    let mut written = 0;
+
+    for value in values
+    {
+        value.write_le( &mut bytes );
+        written += 1;
+
+        if bytes.len() >= WRITE_CHUNK * Type::SIZE
+        {
+            out.write_all( &bytes )?;
+            bytes.clear();
+        }
+    }
+
+    out.write_all( &bytes )?;
+
+    if written != count
+    {
+        return Err( io::Error::new( io::ErrorKind::InvalidInput,
+                                    format!( "{} numbers were written after a count of {}", written, count ) ) );
+    }
+
+    out.flush()
+}
+
+/// A read-only bag of the numbers of a file in the binary format,
+/// read from the file as they are needed.
+///
+/// # Invariant of the MappedBag struct:
+/// 1. `map` holds the bytes of a file whose header has the code of
+/// `Type` and a count of `len`, followed by exactly `len` numbers.
+
+pub struct MappedBag<Type: Binary>
+{
+    map: map::Map,
+    len: usize,
+    marker: PhantomData<Type>
+}
+
+impl<Type: Binary> MappedBag<Type>
+{
+    /// Map the file at `path`.
+    ///
+    /// # Parameter: `path`
+    /// A file in the binary format holding numbers of `Type`.
+    ///
+    /// # Postcondition
+    /// The file is mapped until the bag is dropped; no number of it has
+    /// been read but those of the header.
+    ///
+    /// # Return
+    /// An Err if the file could not be opened or mapped, or of kind
+    /// `InvalidData` if it is not in the binary format, holds another
+    /// type of number, or is not as long as its count says.
+    ///
+    /// # Safety
+    /// The file must not be truncated or written, by this program or
+    /// any other, until the bag is dropped. The bag reads the mapped
+    /// bytes as memory no one else changes; if the file is cut short
+    /// the program stops with a bus error, and if it is written the
+    /// behavior is undefined. Only the reading into memory done where
+    /// there is no `mmap` is safe whatever happens to the file.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: MappedBag<f64> = unsafe { MappedBag::open( "numbers.bag" ) }.unwrap();
+    ///
+    /// println!( "{} numbers, with a mean of {:?}", bag.len(), bag.mean() );
+    /// ```
+
+    pub unsafe fn open<P: AsRef<Path>>( path: P ) -> io::Result<Self>
+    {
+        let file = File::open( path )?;
+        let file_len = usize::try_from( file.metadata()?.len() ).map_err( | _ |
+            invalid_data( "the file is too large to map".to_string() ) )?;
+
+        if file_len < HEADER_LEN
+        {
+            return Err( invalid_data( format!( "the file of {} bytes is too short for a header", file_len ) ) );
+        }
+
+        let map = map::Map::new( &file, file_len )?;
+        let len = read_header::<Type>( map.bytes() )?;
+
+        if len.checked_mul( Type::SIZE ).and_then( | size | size.checked_add( HEADER_LEN ) ) != Some( file_len )
+        {
+            return Err( invalid_data( format!( "the file of {} bytes does not hold the {} numbers of its header",
+                                               file_len, len ) ) );
+        }
+
+        Ok( MappedBag { map, len, marker: PhantomData } )
+    }
+
+    /// The number at `index` in the order of the file.
+    ///
+    /// # Return
+    /// The number, or None if `index` is not less than `len()`.
+
+    pub fn get( &self, index: usize ) -> Option<Type>
+    {
+        if index < self.len
+        {
+            Some( Type::read_le( &self.values()[ index * Type::SIZE.. ] ) )
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Determine the number of times `target` is in this bag.
+    ///
+    /// # Postcondition
+    /// Every number of the file has been read.
+    ///
+    /// # Return
+    /// The number of numbers that are `==` to `target`, so that `-0.0`
+    /// and `0.0` count as each other and NaN is never counted.
+    ///
+    /// # Examples
+    /// ```
+    /// let bag: MappedBag<f64> = unsafe { MappedBag::open( "numbers.bag" ) }.unwrap();
+    ///
+    /// println!( "{} zeros", bag.occurrences( 0.0 ) );
+    /// ```
+
+    pub fn occurrences( &self, target: Type ) -> usize
+    {
+        self.iter().filter( | &value | value == target ).count()
+    }
+
+    /// Iterate over the numbers in the order of the file.
+    ///
+    /// # Return
+    /// An iterator reading each number as it is returned, which can
+    /// be iterated from either end.
+
+    pub fn iter( &self ) -> MappedIter<'_, Type>
+    {
+        MappedIter { chunks: self.values().chunks_exact( Type::SIZE ), marker: PhantomData }
+    }
+
+    /// Copy the numbers into a bag in memory.
+    ///
+    /// # Return
+    /// A bag holding the numbers in the order of the file.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocation.
+
+    pub fn to_bag( &self ) -> Bag<Type>
+    {
+        let mut bag = Bag::with_capacity( self.len );
+
+        for value in self.iter()
+        {
+            bag.insert( value );
+        }
+
+        bag
+    }
+
+    // The bytes of the numbers, after the header.
+    fn values( &self ) -> &[u8]
+    {
+        &self.map.bytes()[ HEADER_LEN.. ]
+    }
+}
+
+impl<Type: Binary> MappedBag<Type>
+{
+    /// Add up the numbers in this bag.
+    ///
+    /// # Return
+    /// The sum of the numbers, or 0 for an empty bag.
+    ///
+    /// # Panics
+    /// The sum of integers overflows. checked_sum( ) does not panic.
+
+    pub fn sum( &self ) -> Type
+    {
+        match self.checked_sum()
+        {
+            Some( sum ) => sum,
+            None => panic!( "the sum of the bag overflows" )
+        }
+    }
+
+    /// Add up the numbers in this bag, checking for overflow.
+    ///
+    /// # Return
+    /// The sum of the numbers, 0 for an empty bag, or None if
+    /// the sum of integers overflows.
+
+    pub fn checked_sum( &self ) -> Option<Type>
+    {
+        bagnum::checked_sum( self.iter() )
+    }
+
+    /// The mean of the numbers in this bag.
+    ///
+    /// # Return
+    /// The mean as an `f64`, or None for an empty bag.
+
+    pub fn mean( &self ) -> Option<f64>
+    {
+        bagnum::mean( self.iter() )
+    }
+
+    /// The least number in this bag.
+    ///
+    /// # Return
+    /// The least number, not counting NaNs, or None if the bag
+    /// holds no other number. Of `-0.0` and `0.0` the first to occur
+    /// is returned.
+
+    pub fn min( &self ) -> Option<Type>
+    {
+        bagnum::extreme( self.iter(), | value, least | value < least )
+    }
+
+    /// The greatest number in this bag.
+    ///
+    /// # Return
+    /// The greatest number, not counting NaNs, or None if the bag
+    /// holds no other number. Of `-0.0` and `0.0` the first to occur
+    /// is returned.
+
+    pub fn max( &self ) -> Option<Type>
+    {
+        bagnum::extreme( self.iter(), | value, greatest | value > greatest )
+    }
+
+    /// The variance of the numbers in this bag, as a population.
+    ///
+    /// # Postcondition
+    /// The numbers have been read twice, once for their mean.
+    ///
+    /// # Return
+    /// The mean of the squared differences of the numbers from
+    /// their mean, or None for an empty bag.
+
+    pub fn variance( &self ) -> Option<f64>
+    {
+        bagnum::variance( self.iter() )
+    }
+
+    /// The standard deviation of the numbers in this bag, as a population.
+    ///
+    /// # Return
+    /// The square root of variance( ), or None for an empty bag.
+
+    pub fn std_dev( &self ) -> Option<f64>
+    {
+        self.variance().map( f64::sqrt )
+    }
+}
+
+impl<Type: Binary> Len for MappedBag<Type>
+{
+    /// Determine the number of numbers in this bag, from the header
+    /// of its file.
+
+    fn len( &self ) -> usize
+    {
+        self.len
+    }
+}
+
+impl<Type: Binary> Empty for MappedBag<Type>
+{
+    /// Determine if the bag is empty.
+
+    fn is_empty( &self ) -> bool
+    {
+        self.len == 0
+    }
+}
+
+impl<Type: Binary> Debug for MappedBag<Type>
+{
+    /// Names the type and number of the numbers, without reading them.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "MappedBag<{}> of {} numbers", type_name( Type::CODE ).unwrap_or( "?" ), self.len )
+    }
+}
+
+impl<'a, Type: Binary> IntoIterator for &'a MappedBag<Type>
+{
+    type Item = Type;
+    type IntoIter = MappedIter<'a, Type>;
+
+    /// Generate an iterator for the bag, as iter( ) does.
+
+    fn into_iter( self ) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+/// An iterator over the numbers of a `MappedBag`, reading each from
+/// the mapped file as it is returned.
+
+#[ derive( Clone ) ]
+pub struct MappedIter<'a, Type: Binary>
+{
+    chunks: ChunksExact<'a, u8>,
+    marker: PhantomData<Type>
+}
+
+impl<'a, Type: Binary> Iterator for MappedIter<'a, Type>
+{
+    type Item = Type;
+
+    fn next( &mut self ) -> Option<Type>
+    {
+        self.chunks.next().map( Type::read_le )
+    }
+
+    fn size_hint( &self ) -> ( usize, Option<usize> )
+    {
+        self.chunks.size_hint()
+    }
+
+    fn nth( &mut self, n: usize ) -> Option<Type>
+    {
+        self.chunks.nth( n ).map( Type::read_le )
+    }
+}
+
+impl<'a, Type: Binary> DoubleEndedIterator for MappedIter<'a, Type>
+{
+    fn next_back( &mut self ) -> Option<Type>
+    {
+        self.chunks.next_back().map( Type::read_le )
+    }
+}
+
+impl<'a, Type: Binary> ExactSizeIterator for MappedIter<'a, Type> {}
+
+impl<'a, Type: Binary> FusedIterator for MappedIter<'a, Type> {}
+
+// The count of numbers in the header at the start of `bytes`, checking
+// that it is a header of the binary format for numbers of `Type`.
+fn read_header<Type: Binary>( bytes: &[u8] ) -> io::Result<usize>
+{
+    if &bytes[ ..4 ] != MAGIC
+    {
+        return Err( invalid_data( "the file is not in the bag binary format".to_string() ) );
+    }
+
+    if bytes[ 4 ] != VERSION
+    {
+        return Err( invalid_data( format!( "version {} of the bag binary format is not supported", bytes[ 4 ] ) ) );
+    }
+
+    if bytes[ 5 ] != Type::CODE
+    {
+        let held = match type_name( bytes[ 5 ] )
+        {
+            Some( name ) => name.to_string(),
+            None         => format!( "unknown type {}", bytes[ 5 ] )
+        };
+
+        return Err( invalid_data( format!( "the file holds numbers of {}, not of {}",
+                                           held, type_name( Type::CODE ).unwrap_or( "?" ) ) ) );
+    }
+
+    let mut count = [ 0; 8 ];
+    count.copy_from_slice( &bytes[ 8..HEADER_LEN ] );
+
+    usize::try_from( u64::from_le_bytes( count ) ).map_err( | _ |
+        invalid_data( "the file holds too many numbers to map".to_string() ) )
+}
+
+// An error of a file that is not a bag of the type opened.
+fn invalid_data( message: String ) -> io::Error
+{
+    io::Error::new( io::ErrorKind::InvalidData, message )
+}
+
+// The bytes of a file, mapped into memory.
+#[ cfg( unix ) ]
+mod map
+{
+    extern crate libc;
+
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+    use std::slice;
+
+    // The mapping of the first `len` bytes of a file, unmapped when
+    // dropped.
+    pub struct Map
+    {
+        address: *mut libc::c_void,
+        len: usize
+    }
+
+    // The mapping is only read, so it can be shared between threads.
+    unsafe impl Send for Map {}
+    unsafe impl Sync for Map {}
+
+    impl Map
+    {
+        // Map the first `len` bytes of `file`, which must not be 0.
+        pub fn new( file: &File, len: usize ) -> io::Result<Map>
+        {
+            let address = unsafe
+            {
+                libc::mmap( ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0 )
+            };
+
+            if address == libc::MAP_FAILED
+            {
+                return Err( io::Error::last_os_error() );
+            }
+
+            Ok( Map { address, len } )
+        }
+
+        // The mapped bytes.
+        pub fn bytes( &self ) -> &[u8]
+        {
+            unsafe { slice::from_raw_parts( self.address as *const u8, self.len ) }
+        }
+    }
+
+    impl Drop for Map
+    {
+        fn drop( &mut self )
+        {
+            unsafe
+            {
+                libc::munmap( self.address, self.len );
+            }
+        }
+    }
+}
+
+// The bytes of a file, read into memory where it cannot be mapped.
+#[ cfg( not( unix ) ) ]
+mod map
+{
+    use std::fs::File;
+    use std::io::{ self, Read };
+
+    // The first `len` bytes of a file.
+    pub struct Map
+    {
+        data: Vec<u8>
+    }
+
+    impl Map
+    {
+        // Read the first `len` bytes of `file`.
+        pub fn new( file: &File, len: usize ) -> io::Result<Map>
+        {
+            let mut data = vec![ 0; len ];
+            ( &*file ).read_exact( &mut data )?;
+
+            Ok( Map { data } )
+        }
+
+        // The bytes read.
+        pub fn bytes( &self ) -> &[u8]
+        {
+            &self.data
+        }
+    }
+}
@@ -0,0 +1,223 @@
+// FILE: bagmmapexam.rs
+
+//! This module holds tests for the memory-mapped bag of bagmmap
+//!
+//! 1) tests of counting and iterating a mapped bag
+//!
+//! 2) tests of the aggregates against those of bagnum
+//!
+//! 3) tests of files that cannot be mapped as a bag
+//!
+//! 4) tests of writing numbers from an iterator
+//!
+//! 5) tests of a sum that overflows
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bagmmap::{ write_bag, write_values, Binary, MappedBag };
+use bagscratch::scratch_path;
+use bag::Bag;
+use bagf64::NumericCounts;
+use bag::len_trait::len::{ Len, Empty };
+use std::fs::{ self, File };
+use std::io;
+use std::path::PathBuf;
+
+// The file at `path` mapped. No test writes or truncates a file while
+// it is mapped, as `MappedBag::open( _ )` requires; removing it leaves
+// the mapping whole.
+fn open<Type: Binary>( path: &PathBuf ) -> io::Result<MappedBag<Type>>
+{
+    unsafe { MappedBag::open( path ) }
+}
+
+// The bag written to the file at `path` and mapped.
+fn mapped<Type: Binary>( bag: &Bag<Type>, path: &PathBuf ) -> MappedBag<Type>
+{
+    write_bag( bag, File::create( path ).unwrap() ).unwrap();
+    open( path ).unwrap()
+}
+
+// Whether the aggregates of `test_bag` are those of `bag`.
+fn assert_same_aggregates<Type: Binary>( test_bag: &MappedBag<Type>, bag: &Bag<Type> )
+{
+    assert_eq!( format!( "{:?}", test_bag.checked_sum() ), format!( "{:?}", bag.checked_sum() ) );
+    assert_eq!( format!( "{:?}", test_bag.min() ), format!( "{:?}", bag.min() ) );
+    assert_eq!( format!( "{:?}", test_bag.max() ), format!( "{:?}", bag.max() ) );
+    assert_eq!( format!( "{:?}", test_bag.mean() ), format!( "{:?}", bag.mean() ) );
+    assert_eq!( format!( "{:?}", test_bag.variance() ), format!( "{:?}", bag.variance() ) );
+    assert_eq!( format!( "{:?}", test_bag.std_dev() ), format!( "{:?}", bag.std_dev() ) );
+}
+
+#[ test ]
+///   Performs tests of counting and iterating a mapped bag.
+fn test_count_and_iterate()
+{
+    let path = scratch_path( "bagmmap", "count" );
+    let bag: ::bagf64::Bag = [ 1.5, -0.0, 2.0, 1.5, f64::NAN, 0.0, 1.5 ].iter().cloned().collect();
+    let test_bag = mapped( &bag, &path );
+
+    // A. The length comes from the header.
+    assert_eq!( test_bag.len(), 7 );
+    assert!( !test_bag.is_empty() );
+    assert_eq!( fs::metadata( &path ).unwrap().len(), 16 + 7 * 8 );
+
+    // B. Numbers are counted as bagnum counts them.
+    assert_eq!( test_bag.occurrences( 1.5 ), 3 );
+    assert_eq!( test_bag.occurrences( 0.0 ), bag.occurrences( 0.0 ) );
+    assert_eq!( test_bag.occurrences( f64::NAN ), 0 );
+    assert_eq!( test_bag.occurrences( 7.0 ), 0 );
+
+    // C. Iterating from either end in the order of the bag.
+    let values: Vec<u64> = test_bag.iter().map( f64::to_bits ).collect();
+    assert_eq!( values, bag.into_iter().map( | value | value.to_bits() ).collect::<Vec<u64>>() );
+    assert_eq!( test_bag.iter().next_back(), Some( 1.5 ) );
+    assert_eq!( test_bag.iter().len(), 7 );
+    assert_eq!( ( &test_bag ).into_iter().nth( 2 ), Some( 2.0 ) );
+    assert!( test_bag.get( 1 ).unwrap().is_sign_negative() );
+    assert_eq!( test_bag.get( 7 ), None );
+
+    // D. Copied back into a bag in memory.
+    assert_eq!( test_bag.to_bag().size(), 7 );
+    assert_eq!( test_bag.to_bag().into_counts().len(), bag.clone().into_counts().len() );
+    assert_eq!( format!( "{:?}", test_bag ), "MappedBag<f64> of 7 numbers" );
+    drop( test_bag );
+
+    // E. An empty bag.
+    let test_bag = mapped( &Bag::<i32>::new(), &path );
+    assert!( test_bag.is_empty() );
+    assert_eq!( test_bag.iter().next(), None );
+    assert_eq!( test_bag.sum(), 0 );
+    assert_eq!( test_bag.mean(), None );
+    assert_eq!( test_bag.min(), None );
+
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of the aggregates against those of bagnum.
+fn test_aggregates()
+{
+    let mut rng = XorShiftRng::from_seed( [ 49, 2, 3, 4 ] );
+    let path = scratch_path( "bagmmap", "aggregates" );
+
+    for _ in 0..50
+    {
+        // A. Bags of f64s, with NaNs and zeros of either sign.
+        let mut bag = ::bagf64::Bag::new();
+
+        for _ in 0..rng.gen_range( 0, 200 )
+        {
+            bag.insert( match rng.gen_range( 0, 20 )
+                        {
+                            0 => f64::NAN,
+                            1 => -0.0,
+                            2 => 0.0,
+                            _ => rng.gen_range( -1000.0, 1000.0 )
+                        } );
+        }
+
+        assert_same_aggregates( &mapped( &bag, &path ), &bag );
+
+        // B. Bags of integers, whose sums can overflow.
+        let mut bag = Bag::new();
+
+        for _ in 0..rng.gen_range( 0, 20 )
+        {
+            bag.insert( rng.gen::<i16>() );
+        }
+
+        let test_bag = mapped( &bag, &path );
+        assert_same_aggregates( &test_bag, &bag );
+        assert_eq!( test_bag.occurrences( bag.max().unwrap_or( 0 ) ), bag.occurrences( bag.max().unwrap_or( 0 ) ) );
+    }
+
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of files that cannot be mapped as a bag.
+fn test_invalid_files()
+{
+    let path = scratch_path( "bagmmap", "invalid" );
+    let bag = [ 1i32, 2, 3 ].iter().cloned().collect();
+    write_bag( &bag, File::create( &path ).unwrap() ).unwrap();
+    let data = fs::read( &path ).unwrap();
+
+    // A. A bag of another type.
+    let error = open::<f32>( &path ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::InvalidData );
+    assert_eq!( error.to_string(), "the file holds numbers of i32, not of f32" );
+
+    // B. A file cut short, or with more than its numbers.
+    fs::write( &path, &data[ ..data.len() - 1 ] ).unwrap();
+    assert_eq!( open::<i32>( &path ).unwrap_err().kind(), io::ErrorKind::InvalidData );
+
+    let mut longer = data.clone();
+    longer.extend_from_slice( &[ 0; 4 ] );
+    fs::write( &path, &longer ).unwrap();
+    assert_eq!( open::<i32>( &path ).unwrap_err().kind(), io::ErrorKind::InvalidData );
+
+    // C. Files that are not in the format.
+    fs::write( &path, &data[ ..10 ] ).unwrap();
+    assert_eq!( open::<i32>( &path ).unwrap_err().kind(), io::ErrorKind::InvalidData );
+
+    fs::write( &path, "{1, 2, 3}, said the text format" ).unwrap();
+    let error = open::<i32>( &path ).unwrap_err();
+    assert_eq!( error.to_string(), "the file is not in the bag binary format" );
+
+    let mut newer = data.clone();
+    newer[ 4 ] = 2;
+    fs::write( &path, &newer ).unwrap();
+    assert!( open::<i32>( &path ).unwrap_err().to_string().contains( "version 2" ) );
+
+    // D. A file that is not there.
+    fs::remove_file( &path ).unwrap();
+    assert_eq!( open::<i32>( &path ).unwrap_err().kind(), io::ErrorKind::NotFound );
+}
+
+#[ test ]
+///   Performs tests of writing numbers from an iterator.
+fn test_write_values()
+{
+    let path = scratch_path( "bagmmap", "values" );
+
+    // A. More numbers than are written in one chunk.
+    write_values( ( 0..100_000u32 ).map( | value | value % 1000 ), File::create( &path ).unwrap() ).unwrap();
+    let test_bag: MappedBag<u32> = open( &path ).unwrap();
+
+    assert_eq!( test_bag.len(), 100_000 );
+    assert_eq!( test_bag.occurrences( 999 ), 100 );
+    assert_eq!( test_bag.sum(), 100 * 999 * 1000 / 2 );
+    assert_eq!( test_bag.mean(), Some( 499.5 ) );
+    assert_eq!( ( test_bag.min(), test_bag.max() ), ( Some( 0 ), Some( 999 ) ) );
+    assert_eq!( test_bag.iter().rev().nth( 1000 ), Some( 999 ) );
+
+    // B. The same file as written from a bag.
+    let mut from_values = Vec::new();
+    write_values( vec![ 3u8, 1, 3 ].into_iter(), &mut from_values ).unwrap();
+
+    let mut from_bag = Vec::new();
+    write_bag( &[ 3u8, 1, 3 ].iter().cloned().collect(), &mut from_bag ).unwrap();
+    assert_eq!( from_values, from_bag );
+    assert_eq!( &from_bag[ ..8 ], b"BAGN\x01B\x00\x00" );
+    assert_eq!( &from_bag[ 16.. ], &[ 3, 1, 3 ] );
+
+    drop( test_bag );
+    fs::remove_file( &path ).unwrap();
+}
+
+#[ test ]
+#[ should_panic( expected = "the sum of the bag overflows" ) ]
+///   Performs a test of a sum that overflows.
+fn test_sum_overflow()
+{
+    let path = scratch_path( "bagmmap", "overflow" );
+    let test_bag = mapped( &[ 100i8, 100 ].iter().cloned().collect(), &path );
+    fs::remove_file( &path ).unwrap();
+
+    test_bag.sum();
+}
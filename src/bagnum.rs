@@ -0,0 +1,605 @@
+// FILE: bagnum.rs
+
+//! The numbers a `bag::Bag` can hold to compute aggregates such as
+//! the sum, mean, least and greatest value, and variance of its
+//! numbers, and to count its distinct values as `==` compares them.
+//!
+//! The type used must have the trait `Numeric`, which is implemented
+//! for the primitive integer and floating point types. `bagf64::Bag`
+//! is the bag of `f64`s.
+//!
+//! The counts of `bag::Bag` need `Hash` and `Eq`, which floating point
+//! numbers do not have, so the counts of a bag of numbers are given
+//! by the trait `NumericCounts` under the same names, `counts()` and
+//! the like. For integers they are the counts of `bag::Bag`.
+//!
+//! # Note
+//! Because of the slow linear algorithms of this
+//! class, large bags will have poor performance.
+
+pub use bag::len_trait::len::{ Len, Empty, Clear };
+pub use bag::len_trait::capacity::{ Capacity, WithCapacity, CapacityMut };
+use bag::Bag;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt::{ Debug, Display };
+use std::str::FromStr;
+
+/// The numbers a bag can hold to compute its aggregates.
+///
+/// Two numbers are the same value, for counting them, when their
+/// `equality_key()`s are the same, which is when they are `==`.
+
+pub trait Numeric: Copy + PartialEq + PartialOrd + Default + Debug + Display + FromStr
+{
+    /// The bits identifying the numbers equal to this one, or None
+    /// for a number that is equal to nothing, as NaN is.
+    fn equality_key( self ) -> Option<u64>;
+
+    /// This number added to `addend`, or None if the sum overflows.
+    fn checked_add( self, addend: Self ) -> Option<Self>;
+
+    /// This number as the nearest `f64`.
+    fn to_f64( self ) -> f64;
+
+    /// Compare this number to `other` by a total order, which for
+    /// floating point numbers is the IEEE 754 `totalOrder` predicate
+    /// of `bagtotal`, with `-0.0` before `0.0` and NaNs at the ends.
+    fn total_cmp( &self, other: &Self ) -> Ordering;
+}
+
+// Implements Numeric for integers, each of whose values is a distinct key.
+macro_rules! numeric_integers
+{
+    ( $( $integer:ty ),* ) =>
+    {
+        $(
+            impl Numeric for $integer
+            {
+                fn equality_key( self ) -> Option<u64>
+                {
+                    Some( self as u64 )
+                }
+
+                fn checked_add( self, addend: Self ) -> Option<Self>
+                {
+                    <$integer>::checked_add( self, addend )
+                }
+
+                fn to_f64( self ) -> f64
+                {
+                    self as f64
+                }
+
+                fn total_cmp( &self, other: &Self ) -> Ordering
+                {
+                    self.cmp( other )
+                }
+            }
+        )*
+    };
+}
+
+// Implements Numeric for floating point numbers, with -0.0 and 0.0
+// the same key and no key for NaN.
+macro_rules! numeric_floats
+{
+    ( $( $float:ty ),* ) =>
+    {
+        $(
+            impl Numeric for $float
+            {
+                fn equality_key( self ) -> Option<u64>
+                {
+                    if self.is_nan()
+                    {
+                        None
+                    }
+                    else if self == 0.0
+                    {
+                        Some( 0 )
+                    }
+                    else
+                    {
+                        Some( self.to_bits() as u64 )
+                    }
+                }
+
+                fn checked_add( self, addend: Self ) -> Option<Self>
+                {
+                    Some( self + addend )
+                }
+
+                fn to_f64( self ) -> f64
+                {
+                    self as f64
+                }
+
+                fn total_cmp( &self, other: &Self ) -> Ordering
+                {
+                    <$float>::total_cmp( self, other )
+                }
+            }
+        )*
+    };
+}
+
+numeric_integers!( i8, i16, i32, i64, isize, u8, u16, u32, u64, usize );
+numeric_floats!( f32, f64 );
+
+impl<Type: Numeric> Bag<Type>
+{
+    /// Add up the numbers in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The sum of the numbers, or 0 for an empty bag.
+    ///
+    /// # Panics
+    /// The sum of integers overflows. checked_sum( ) does not panic.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag: Bag<f64> = Bag::new();
+    /// bag.insert( 4.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 4.0 );
+    ///
+    /// assert_eq!( bag.sum(), 10.0 );
+    /// ```
+
+    pub fn sum( &self ) -> Type
+    {
+        match self.checked_sum()
+        {
+            Some( sum ) => sum,
+            None => panic!( "the sum of the bag overflows" )
+        }
+    }
+
+    /// Add up the numbers in this bag, checking for overflow.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The sum of the numbers, 0 for an empty bag, or None if
+    /// the sum of integers overflows.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag: Bag<u8> = Bag::new();
+    /// bag.insert( 200 );
+    /// bag.insert( 100 );
+    ///
+    /// assert_eq!( bag.checked_sum(), None );
+    /// ```
+
+    pub fn checked_sum( &self ) -> Option<Type>
+    {
+        checked_sum( self.into_iter().cloned() )
+    }
+
+    /// The mean of the numbers in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The mean as an `f64`, which does not overflow for integers,
+    /// or None for an empty bag.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag: Bag<f64> = Bag::new();
+    /// bag.insert( 1.0 );
+    /// bag.insert( 2.0 );
+    ///
+    /// assert_eq!( bag.mean(), Some( 1.5 ) );
+    /// ```
+
+    pub fn mean( &self ) -> Option<f64>
+    {
+        mean( self.into_iter().cloned() )
+    }
+
+    /// The least number in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The least number, not counting NaNs, or None if the bag
+    /// holds no other number. Of `-0.0` and `0.0` the first to occur
+    /// is returned.
+
+    pub fn min( &self ) -> Option<Type>
+    {
+        extreme( self.into_iter().cloned(), | value, least | value < least )
+    }
+
+    /// The greatest number in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The greatest number, not counting NaNs, or None if the bag
+    /// holds no other number. Of `-0.0` and `0.0` the first to occur
+    /// is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag: Bag<f32> = Bag::new();
+    /// bag.insert( 1.5 );
+    /// bag.insert( f32::NAN );
+    ///
+    /// assert_eq!( bag.max(), Some( 1.5 ) );
+    /// ```
+
+    pub fn max( &self ) -> Option<Type>
+    {
+        extreme( self.into_iter().cloned(), | value, greatest | value > greatest )
+    }
+
+    /// The variance of the numbers in this bag, as a population.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The mean of the squared differences of the numbers from
+    /// their mean, or None for an empty bag.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag: Bag<u32> = Bag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 3 );
+    ///
+    /// assert_eq!( bag.variance(), Some( 1.0 ) );
+    /// ```
+
+    pub fn variance( &self ) -> Option<f64>
+    {
+        variance( self.into_iter().cloned() )
+    }
+
+    /// The standard deviation of the numbers in this bag, as a population.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The square root of variance( ), or None for an empty bag.
+
+    pub fn std_dev( &self ) -> Option<f64>
+    {
+        self.variance().map( f64::sqrt )
+    }
+}
+
+// The aggregates of the numbers of a bag, shared with the mapped bag
+// of bagmmap, which reads its numbers from a file rather than a slice.
+
+// The sum of the numbers, or None if it overflows.
+pub( crate ) fn checked_sum<Type: Numeric, Values: Iterator<Item = Type>>( values: Values ) -> Option<Type>
+{
+    let mut values = values;
+
+    values.try_fold( Type::default(), | sum, value | sum.checked_add( value ) )
+}
+
+// The mean of the numbers as an `f64`, or None if there are none.
+pub( crate ) fn mean<Type: Numeric, Values: ExactSizeIterator<Item = Type>>( values: Values ) -> Option<f64>
+{
+    let len = values.len();
+
+    if len == 0
+    {
+        return None;
+    }
+
+    let sum: f64 = values.map( | value | value.to_f64() ).sum();
+
+    Some( sum / len as f64 )
+}
+
+// The first number, not counting NaNs, that no later number is `beyond`.
+pub( crate ) fn extreme<Type, Values, Beyond>( values: Values, beyond: Beyond ) -> Option<Type>
+    where Type: Numeric, Values: Iterator<Item = Type>, Beyond: Fn( Type, Type ) -> bool
+{
+    values.filter( | value | value.equality_key().is_some() )
+          .fold( None, | extreme, value | match extreme
+              {
+                  Some( extreme ) if !beyond( value, extreme ) => Some( extreme ),
+                  _ => Some( value )
+              } )
+}
+
+// The population variance of the numbers, or None if there are none.
+// The numbers are read twice, once for their mean.
+pub( crate ) fn variance<Type, Values>( values: Values ) -> Option<f64>
+    where Type: Numeric, Values: ExactSizeIterator<Item = Type> + Clone
+{
+    let len = values.len();
+    let mean = mean( values.clone() )?;
+    let squares: f64 = values.map( | value | ( value.to_f64() - mean ) * ( value.to_f64() - mean ) )
+                             .sum();
+
+    Some( squares / len as f64 )
+}
+
+/// The counts of the values of a bag of numbers, as `==` compares
+/// them, under the names of the counts of `bag::Bag`.
+///
+/// The counts of `bag::Bag` need `Hash` and `Eq`, which floating point
+/// numbers do not have, so a bag of floats, such as `bagf64::Bag`, has
+/// these in their place. For a bag of integers the methods of
+/// `bag::Bag` are called instead, and give the same counts.
+
+pub trait NumericCounts
+{
+    /// The numbers in the bag.
+    type Value: Numeric;
+
+    /// Iterate over the distinct values in this bag.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning each value in the bag once, in the
+    /// order of their first occurrence.
+    ///
+    /// # Note
+    /// Values are distinct as compared by `==`: `-0.0` and `0.0` are
+    /// the same value, and every NaN is a value of its own.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 4.0 );
+    ///
+    /// assert_eq!( bag.distinct().collect::<Vec<&f64>>(), vec![ &4.0, &2.0 ] );
+    /// ```
+    fn distinct( &self ) -> impl Iterator<Item = &Self::Value> + '_;
+
+    /// Count the distinct values in this bag exactly.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// The number of values returned by distinct( ).
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the set of values.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 4.0 );
+    ///
+    /// assert_eq!( bag.distinct_len(), 2 );
+    /// ```
+    fn distinct_len( &self ) -> usize;
+
+    /// Iterate over the distinct values in this bag with the
+    /// number of times each occurs.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning `( value, multiplicity )` for each value
+    /// in the bag, in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Values are distinct as compared by `==`: `-0.0` and `0.0` are
+    /// counted together, as the first of them to occur, and every NaN
+    /// is counted on its own with a multiplicity of one.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 4.0 );
+    ///
+    /// assert_eq!( bag.counts().collect::<Vec<( &f64, usize )>>(), vec![ ( &4.0, 2 ), ( &2.0, 1 ) ] );
+    /// ```
+    fn counts( &self ) -> ::std::vec::IntoIter<( &Self::Value, usize )>;
+
+    /// List the `k` values that occur most often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the highest
+    /// multiplicity to the lowest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 3.0 );
+    ///
+    /// assert_eq!( bag.most_common( 2 ), vec![ ( &2.0, 2 ), ( &1.0, 1 ) ] );
+    /// ```
+    fn most_common( &self, k: usize ) -> Vec<( &Self::Value, usize )>;
+
+    /// List the `k` values that occur least often in this bag.
+    ///
+    /// # Parameter: `k`
+    /// The most values to list.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// Up to `k` `( value, multiplicity )` pairs from the lowest
+    /// multiplicity to the highest, with values of equal multiplicity
+    /// in the order of their first occurrence.
+    ///
+    /// # Note
+    /// Only `k` values are kept while the counts are read, rather
+    /// than sorting all of them.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 3.0 );
+    ///
+    /// assert_eq!( bag.least_common( 2 ), vec![ ( &1.0, 1 ), ( &3.0, 1 ) ] );
+    /// ```
+    fn least_common( &self, k: usize ) -> Vec<( &Self::Value, usize )>;
+
+    /// Iterate over the numbers in this bag in ascending order.
+    ///
+    /// # Postcondition
+    /// This method does not alter state of the bag.
+    ///
+    /// # Return
+    /// An iterator returning every number in the bag, ordered by
+    /// `Numeric::total_cmp`, with equal numbers in their stored order.
+    ///
+    /// # Note
+    /// Floating point numbers are in `totalOrder`: `-0.0` comes
+    /// before `0.0`, and NaNs are first or last by their sign.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the sorted references.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 2.5 );
+    /// bag.insert( f64::NAN );
+    /// bag.insert( -1.0 );
+    ///
+    /// assert_eq!( bag.sorted_iter().take( 2 ).collect::<Vec<&f64>>(), vec![ &-1.0, &2.5 ] );
+    /// ```
+    fn sorted_iter( &self ) -> ::std::vec::IntoIter<&Self::Value>;
+
+    /// Convert this bag into a list of each value and its multiplicity.
+    ///
+    /// # Return
+    /// The `( value, multiplicity )` pairs of counts( ) in ascending
+    /// order of the values, with any NaNs last.
+    ///
+    /// # Note
+    /// Unlike into_counts( ) a map is not returned, as floating
+    /// point numbers are neither `Hash` nor `Ord`.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the list.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 4.0 );
+    /// bag.insert( 2.0 );
+    /// bag.insert( 4.0 );
+    ///
+    /// assert_eq!( bag.into_counts(), vec![ ( 2.0, 1 ), ( 4.0, 2 ) ] );
+    /// ```
+    fn into_counts( self ) -> Vec<( Self::Value, usize )>;
+}
+
+impl<Type: Numeric> NumericCounts for Bag<Type>
+{
+    type Value = Type;
+
+    fn distinct( &self ) -> impl Iterator<Item = &Type> + '_
+    {
+        NumericCounts::counts( self ).map( | ( value, _ ) | value )
+    }
+
+    fn distinct_len( &self ) -> usize
+    {
+        NumericCounts::counts( self ).len()
+    }
+
+    fn counts( &self ) -> ::std::vec::IntoIter<( &Type, usize )>
+    {
+        let mut positions: HashMap<u64, usize> = HashMap::new();
+        let mut counts: Vec<( &Type, usize )> = Vec::new();
+
+        for value in self.into_iter().as_slice()
+        {
+            match value.equality_key().map( | key | positions.entry( key ) )
+            {
+                Some( Entry::Occupied( entry ) ) => { counts[ *entry.get() ].1 += 1; }
+                Some( Entry::Vacant( entry ) ) =>
+                    {
+                        entry.insert( counts.len() );
+                        counts.push( ( value, 1 ) );
+                    }
+                None => { counts.push( ( value, 1 ) ); }
+            }
+        }
+
+        counts.into_iter()
+    }
+
+    fn most_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( NumericCounts::counts( self ).enumerate().map( | ( position, ( value, count ) ) | ( value, ( position, count ) ) ), k, false )
+    }
+
+    fn least_common( &self, k: usize ) -> Vec<( &Type, usize )>
+    {
+        ::bag::select_common( NumericCounts::counts( self ).enumerate().map( | ( position, ( value, count ) ) | ( value, ( position, count ) ) ), k, true )
+    }
+
+    fn sorted_iter( &self ) -> ::std::vec::IntoIter<&Type>
+    {
+        let mut sorted: Vec<&Type> = self.into_iter().collect();
+
+        sorted.sort_by( | a, b | a.total_cmp( b ) );
+        sorted.into_iter()
+    }
+
+    fn into_counts( self ) -> Vec<( Type, usize )>
+    {
+        let mut counts: Vec<( Type, usize )> = NumericCounts::counts( &self ).map( | ( &value, count ) | ( value, count ) ).collect();
+
+        counts.sort_by( | a, b | a.0.partial_cmp( &b.0 )
+                                     .unwrap_or_else( || a.0.equality_key().is_none().cmp( &b.0.equality_key().is_none() ) ) );
+        counts
+    }
+}
@@ -0,0 +1,160 @@
+// FILE: bagnumexam.rs
+
+//! This module holds tests for the aggregates and counts of bagnum
+//! on bags of numbers, along with the conformance tests in
+//! bagconformance.rs for bags of `f32`s and `i64`s
+//!
+//! 1) tests of the aggregates of bags of integers
+//!
+//! 2) tests of the aggregates and counts of bags of floating point numbers
+//!
+//! 3) tests of the counts and text format of bags of integers
+//!
+//! 4) tests of bagf64::Bag as the bag of f64s
+//!
+//! Run with `cargo test`
+
+use bag::Bag;
+use bag::len_trait::Len;
+use bagnum::NumericCounts;
+
+conformance_tests!( bagnum_f32 => ::bag::Bag<f32>,
+                    bagnum_i64 => ::bag::Bag<i64> );
+
+#[ test ]
+///   Performs tests of the aggregates of bags of integers.
+fn test1()
+{
+    let mut test_bag: Bag<u32> = Bag::new();
+
+    // A. An empty bag.
+    assert_eq!( test_bag.sum(), 0 );
+    assert_eq!( test_bag.checked_sum(), Some( 0 ) );
+    assert_eq!( test_bag.mean(), None );
+    assert_eq!( test_bag.min(), None );
+    assert_eq!( test_bag.max(), None );
+    assert_eq!( test_bag.variance(), None );
+    assert_eq!( test_bag.std_dev(), None );
+
+    // B. A bag of numbers.
+    for &value in &[ 2, 4, 4, 4, 5, 5, 7, 9 ]
+    {
+        test_bag.insert( value );
+    }
+
+    assert_eq!( test_bag.sum(), 40 );
+    assert_eq!( test_bag.mean(), Some( 5.0 ) );
+    assert_eq!( test_bag.min(), Some( 2 ) );
+    assert_eq!( test_bag.max(), Some( 9 ) );
+    assert_eq!( test_bag.variance(), Some( 4.0 ) );
+    assert_eq!( test_bag.std_dev(), Some( 2.0 ) );
+
+    // C. The mean does not overflow, though the sum does.
+    let mut test_bag: Bag<i64> = Bag::new();
+    test_bag.insert( i64::MAX );
+    test_bag.insert( i64::MAX );
+    test_bag.insert( -1 );
+
+    assert_eq!( test_bag.checked_sum(), None );
+    assert!( ( test_bag.mean().unwrap() - 2.0 * i64::MAX as f64 / 3.0 ).abs() < 1e6 );
+    assert_eq!( test_bag.min(), Some( -1 ) );
+
+    let mut test_bag: Bag<u8> = Bag::new();
+    test_bag.insert( 200 );
+    test_bag.insert( 55 );
+    assert_eq!( test_bag.checked_sum(), Some( 255 ) );
+
+    test_bag.insert( 1 );
+    assert_eq!( test_bag.checked_sum(), None );
+}
+
+#[ test ]
+///   Performs tests of the aggregates and counts of bags of floating point numbers.
+fn test2()
+{
+    let mut test_bag: Bag<f32> = Bag::new();
+
+    for &value in &[ 1.5, f32::NAN, -0.0, 0.0, 1.5, -2.5 ]
+    {
+        test_bag.insert( value );
+    }
+
+    // A. NaN is not a least or greatest number, but spoils the sum.
+    assert_eq!( test_bag.min(), Some( -2.5 ) );
+    assert_eq!( test_bag.max(), Some( 1.5 ) );
+    assert!( test_bag.sum().is_nan() );
+    assert!( test_bag.mean().unwrap().is_nan() );
+
+    // B. Zeros are counted together, each NaN on its own.
+    assert_eq!( test_bag.distinct_len(), 4 );
+    assert_eq!( test_bag.occurrences( 0.0 ), 2 );
+
+    let sorted = test_bag.clone().into_counts();
+    assert_eq!( &sorted[ ..3 ], &[ ( -2.5, 1 ), ( -0.0, 2 ), ( 1.5, 2 ) ] );
+    assert!( sorted[ 3 ].0.is_nan() );
+
+    // C. Only NaNs.
+    let mut test_bag: Bag<f32> = Bag::new();
+    test_bag.insert( f32::NAN );
+    assert_eq!( test_bag.min(), None );
+    assert_eq!( test_bag.len(), 1 );
+
+    // D. Of equal zeros the first is the least.
+    let mut test_bag: Bag<f64> = Bag::new();
+    test_bag.insert( 0.0 );
+    test_bag.insert( -0.0 );
+    assert!( test_bag.min().unwrap().is_sign_positive() );
+    assert_eq!( test_bag.variance(), Some( 0.0 ) );
+}
+
+#[ test ]
+///   Performs tests of the counts and text format of bags of integers.
+fn test3()
+{
+    let mut test_bag: Bag<i64> = Bag::new();
+
+    for &value in &[ -3, 7, 7, -3, 7, 0 ]
+    {
+        test_bag.insert( value );
+    }
+
+    // A. Negative values are counted apart from the rest, as bag::Bag counts them.
+    assert_eq!( NumericCounts::counts( &test_bag ).collect::<Vec<( &i64, usize )>>(), vec![ ( &-3, 2 ), ( &7, 3 ), ( &0, 1 ) ] );
+    assert_eq!( NumericCounts::counts( &test_bag ).collect::<Vec<( &i64, usize )>>(), test_bag.counts().collect::<Vec<( &i64, usize )>>() );
+    assert_eq!( NumericCounts::most_common( &test_bag, 1 ), vec![ ( &7, 3 ) ] );
+    assert_eq!( NumericCounts::most_common( &test_bag, 2 ), test_bag.most_common( 2 ) );
+    assert_eq!( NumericCounts::into_counts( test_bag.clone() ), vec![ ( -3, 2 ), ( 0, 1 ), ( 7, 3 ) ] );
+
+    // B. The text format.
+    assert_eq!( test_bag.to_string(), "{-3, 7×2, -3, 7, 0}" );
+    assert_eq!( test_bag.to_string().parse::<Bag<i64>>(), Ok( test_bag ) );
+
+    let error = "{1, -2}".parse::<Bag<u32>>().unwrap_err();
+    assert_eq!( error.column, 5 );
+}
+
+#[ test ]
+///   Performs tests of bagf64::Bag as the bag of f64s.
+fn test4()
+{
+    let mut test_bag: Bag<f64> = ::bagf64::Bag::new();
+    test_bag.insert( 2.5 );
+
+    let alias: ::bagf64::Bag = test_bag.clone();
+    let mut iter: ::bagf64::BagIterator = alias.into_iter();
+
+    assert_eq!( iter.next(), Some( &2.5 ) );
+    assert_eq!( alias, test_bag );
+    assert_eq!( alias.sum(), 2.5 );
+}
+
+#[ test ]
+#[ should_panic ]
+///   Performs a test of a sum of integers that overflows.
+fn test5()
+{
+    let mut test_bag: Bag<u8> = Bag::new();
+    test_bag.insert( 255 );
+    test_bag.insert( 1 );
+    test_bag.sum();
+}
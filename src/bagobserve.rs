@@ -0,0 +1,348 @@
+// FILE: bagobserve.rs
+
+//! A `bag::Bag` that tells its observers of every element inserted
+//! into it or removed from it, so that caches, metrics and other state
+//! mirroring the bag can be kept in step without polling it.
+//!
+//! An observer is either a callback, given a reference to each event,
+//! or the sending half of an `mpsc` channel, sent a copy of each event.
+//! A channel whose receiver has been dropped is unsubscribed at its
+//! next event.
+//!
+//! Each change is one event, carrying the elements it inserted and
+//! removed: one for an `insert( _ )` or `erase_one( _ )`, and all of
+//! them for an `erase( _ )`, `clear()`, `+=` or `clone_from( _ )`.
+//! A change of nothing, such as an erase of an element not in the
+//! bag, is no event.
+//!
+//! The elements removed are those stored in the bag, which may differ
+//! from the target erased though they compare equal.
+
+use bag::Bag;
+use bag::len_trait::len::{ Empty, Clear };
+use std::fmt::{ self, Debug, Formatter };
+use std::ops::AddAssign;
+use std::slice;
+use std::sync::mpsc::{ self, Sender, Receiver };
+
+/// A change to an observable bag.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum BagEvent<Type>
+{
+    /// An element was inserted.
+    Inserted( Type ),
+    /// One copy of an element was erased.
+    Removed( Type ),
+    /// All copies of an element were erased.
+    Erased( Vec<Type> ),
+    /// Every element was removed.
+    Cleared( Vec<Type> ),
+    /// The elements of another bag were added with `+=`.
+    Added( Vec<Type> ),
+    /// The elements were replaced by a copy of another bag.
+    Replaced { removed: Vec<Type>, inserted: Vec<Type> }
+}
+
+impl<Type> BagEvent<Type>
+{
+    /// The elements the change put into the bag.
+
+    pub fn inserted( &self ) -> &[Type]
+    {
+        match *self
+        {
+            BagEvent::Inserted( ref value )             => slice::from_ref( value ),
+            BagEvent::Added( ref values )               => values,
+            BagEvent::Replaced { ref inserted, .. }     => inserted,
+            _                                           => &[]
+        }
+    }
+
+    /// The elements the change took out of the bag.
+
+    pub fn removed( &self ) -> &[Type]
+    {
+        match *self
+        {
+            BagEvent::Removed( ref value )              => slice::from_ref( value ),
+            BagEvent::Erased( ref values )              => values,
+            BagEvent::Cleared( ref values )             => values,
+            BagEvent::Replaced { ref removed, .. }      => removed,
+            _                                           => &[]
+        }
+    }
+}
+
+/// The handle of an observer, with which it is unsubscribed.
+
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Hash ) ]
+pub struct ObserverId( usize );
+
+// A callback subscribed to the events of a bag.
+type BoxedCallback<Type> = Box<dyn FnMut( &BagEvent<Type> )>;
+
+// Where the events for an observer go.
+enum Observer<Type>
+{
+    Callback( BoxedCallback<Type> ),
+    Channel( Sender<BagEvent<Type>> )
+}
+
+/// A bag that tells its observers of each change to it.
+///
+/// # Invariant of the ObservableBag struct:
+/// 1. Every change to `bag` since an observer subscribed has been
+/// given to it as an event, in the order the changes were made.
+///
+/// 2. The ids of `observers` are different and less than `next_id`.
+
+pub struct ObservableBag<Type: PartialEq + Clone + Default>
+{
+    bag: Bag<Type>,
+    observers: Vec<( ObserverId, Observer<Type> )>,
+    next_id: usize
+}
+
+impl<Type: PartialEq + Clone + Default> ObservableBag<Type>
+{
+    /// Initialize an empty bag with no observers.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = ObservableBag::new();
+    /// let events = bag.channel();
+    ///
+    /// bag.insert( 1 );
+    /// bag.erase_one( 1 );
+    ///
+    /// assert_eq!( events.try_recv(), Ok( BagEvent::Inserted( 1 ) ) );
+    /// assert_eq!( events.try_recv(), Ok( BagEvent::Removed( 1 ) ) );
+    /// ```
+
+    pub fn new() -> Self
+    {
+        ObservableBag::from( Bag::new() )
+    }
+
+    /// The bag as changed so far.
+
+    pub fn bag( &self ) -> &Bag<Type>
+    {
+        &self.bag
+    }
+
+    /// The bag, without its observers.
+
+    pub fn into_bag( self ) -> Bag<Type>
+    {
+        self.bag
+    }
+
+    /// The number of observers subscribed.
+
+    pub fn observer_count( &self ) -> usize
+    {
+        self.observers.len()
+    }
+
+    /// Subscribe `callback` to be called with each change from now on.
+    ///
+    /// # Return
+    /// The handle with which the callback is unsubscribed.
+
+    pub fn subscribe<Callback>( &mut self, callback: Callback ) -> ObserverId
+        where Callback: FnMut( &BagEvent<Type> ) + 'static
+    {
+        self.add_observer( Observer::Callback( Box::new( callback ) ) )
+    }
+
+    /// Subscribe `sender` to be sent a copy of each change from now on.
+    ///
+    /// # Return
+    /// The handle with which the sender is unsubscribed.
+
+    pub fn subscribe_sender( &mut self, sender: Sender<BagEvent<Type>> ) -> ObserverId
+    {
+        self.add_observer( Observer::Channel( sender ) )
+    }
+
+    /// Subscribe a new channel to be sent a copy of each change from now on.
+    ///
+    /// # Return
+    /// The receiving half of the channel. Dropping it unsubscribes the channel.
+
+    pub fn channel( &mut self ) -> Receiver<BagEvent<Type>>
+    {
+        let ( sender, receiver ) = mpsc::channel();
+
+        self.subscribe_sender( sender );
+        receiver
+    }
+
+    /// Unsubscribe an observer.
+    ///
+    /// # Return
+    /// False if no observer had the id, as when it was already unsubscribed.
+
+    pub fn unsubscribe( &mut self, id: ObserverId ) -> bool
+    {
+        let old_len = self.observers.len();
+
+        self.observers.retain( | &( observer_id, _ ) | observer_id != id );
+        self.observers.len() < old_len
+    }
+
+    /// Add a new element to the bag, as `Bag::insert( _ )` does.
+
+    pub fn insert( &mut self, new_item: Type )
+    {
+        if !self.observers.is_empty()
+        {
+            self.notify( BagEvent::Inserted( new_item.clone() ) );
+        }
+
+        self.bag.insert( new_item );
+    }
+
+    /// Remove one copy of an element from the bag, as `Bag::erase_one( _ )` does.
+    ///
+    /// # Return
+    /// True or false depending on whether `target` exists in the bag.
+
+    pub fn erase_one( &mut self, target: Type ) -> bool
+    {
+        if !self.observers.is_empty()
+        {
+            match self.bag.find( &target )
+            {
+                None                    => return false,
+                Some( ( _, value ) )    => self.notify( BagEvent::Removed( value ) )
+            }
+        }
+
+        self.bag.erase_one( target )
+    }
+
+    /// Erase all copies of an element from the bag, as `Bag::erase( _ )` does.
+    ///
+    /// # Return
+    /// The number of items erased from bag.
+
+    pub fn erase( &mut self, target: Type ) -> usize
+    {
+        if !self.observers.is_empty()
+        {
+            let erased: Vec<Type> = self.bag.into_iter().filter( | &value | *value == target ).cloned().collect();
+
+            if !erased.is_empty()
+            {
+                self.notify( BagEvent::Erased( erased ) );
+            }
+        }
+
+        self.bag.erase( target )
+    }
+
+    /// Remove all elements from the bag, as `Bag::clear()` does.
+
+    pub fn clear( &mut self )
+    {
+        if !self.observers.is_empty() && !self.bag.is_empty()
+        {
+            let removed = self.elements();
+            self.notify( BagEvent::Cleared( removed ) );
+        }
+
+        self.bag.clear();
+    }
+
+    /// Make the bag a copy of `source`, as `Bag::clone_from( _ )` does.
+
+    pub fn clone_from( &mut self, source: &Bag<Type> )
+    {
+        let changed = !self.bag.is_empty() || !source.is_empty();
+
+        if changed && !self.observers.is_empty()
+        {
+            let removed = self.elements();
+            self.notify( BagEvent::Replaced { removed, inserted: source.into_iter().cloned().collect() } );
+        }
+
+        self.bag.clone_from( source );
+    }
+
+    // A copy of the elements of the bag.
+    fn elements( &self ) -> Vec<Type>
+    {
+        self.bag.into_iter().cloned().collect()
+    }
+
+    // Gives the next id to a new observer.
+    fn add_observer( &mut self, observer: Observer<Type> ) -> ObserverId
+    {
+        let id = ObserverId( self.next_id );
+
+        self.next_id += 1;
+        self.observers.push( ( id, observer ) );
+        id
+    }
+
+    // Gives `event` to every observer, in the order they subscribed,
+    // and unsubscribes the channels that have no receiver.
+    fn notify( &mut self, event: BagEvent<Type> )
+    {
+        self.observers.retain_mut( | &mut ( _, ref mut observer ) |
+            {
+                match *observer
+                {
+                    Observer::Callback( ref mut callback )  => { callback( &event ); true }
+                    Observer::Channel( ref sender )         => sender.send( event.clone() ).is_ok()
+                }
+            } );
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> Default for ObservableBag<Type>
+{
+    fn default() -> Self
+    {
+        ObservableBag::new()
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> From<Bag<Type>> for ObservableBag<Type>
+{
+    /// Observe the changes to `bag` from now on, with no observers yet.
+
+    fn from( bag: Bag<Type> ) -> Self
+    {
+        ObservableBag { bag, observers: Vec::new(), next_id: 0 }
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> AddAssign<Bag<Type>> for ObservableBag<Type>
+{
+    /// Add the elements of `other` to the bag, as `+=` on a bag does.
+
+    fn add_assign( &mut self, other: Bag<Type> )
+    {
+        if !self.observers.is_empty() && !other.is_empty()
+        {
+            self.notify( BagEvent::Added( other.into_iter().cloned().collect() ) );
+        }
+
+        self.bag += other;
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + Debug> Debug for ObservableBag<Type>
+{
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        fmt.debug_struct( "ObservableBag" )
+           .field( "bag", &self.bag )
+           .field( "observers", &self.observers.len() )
+           .finish()
+    }
+}
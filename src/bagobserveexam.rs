@@ -0,0 +1,155 @@
+// FILE: bagobserveexam.rs
+
+//! This module holds tests for the observable bag of bagobserve
+//!
+//! 1) tests of the events of each change
+//!
+//! 2) tests of subscribing and unsubscribing observers
+//!
+//! 3) random changes mirrored into counts by a callback
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::Bag;
+use bagobserve::{ ObservableBag, BagEvent };
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[ test ]
+///   Performs tests of the events of each change.
+fn test1()
+{
+    let mut test_bag = ObservableBag::new();
+    let events = test_bag.channel();
+
+    // A. One event for each change.
+    test_bag.insert( 1 );
+    test_bag.insert( 2 );
+    test_bag.insert( 1 );
+    assert!( test_bag.erase_one( 2 ) );
+    test_bag += [ 3, 1 ].iter().cloned().collect();
+    assert_eq!( test_bag.erase( 1 ), 3 );
+    test_bag.clone_from( &[ 4, 5 ].iter().cloned().collect() );
+    test_bag.clear();
+
+    assert_eq!( events.try_iter().collect::<Vec<BagEvent<i32>>>(),
+                vec![ BagEvent::Inserted( 1 ),
+                      BagEvent::Inserted( 2 ),
+                      BagEvent::Inserted( 1 ),
+                      BagEvent::Removed( 2 ),
+                      BagEvent::Added( vec![ 3, 1 ] ),
+                      BagEvent::Erased( vec![ 1, 1, 1 ] ),
+                      BagEvent::Replaced { removed: vec![ 3 ], inserted: vec![ 4, 5 ] },
+                      BagEvent::Cleared( vec![ 4, 5 ] ) ] );
+
+    // B. Changes of nothing are no events.
+    assert!( !test_bag.erase_one( 9 ) );
+    assert_eq!( test_bag.erase( 9 ), 0 );
+    test_bag.clear();
+    test_bag += Bag::new();
+    test_bag.clone_from( &Bag::new() );
+    assert!( events.try_recv().is_err() );
+
+    // C. The elements inserted and removed by each kind of event.
+    let event = BagEvent::Replaced { removed: vec![ 1 ], inserted: vec![ 2, 3 ] };
+    assert_eq!( ( event.removed(), event.inserted() ), ( &[ 1 ][ .. ], &[ 2, 3 ][ .. ] ) );
+    assert_eq!( BagEvent::Inserted( 7 ).inserted(), &[ 7 ] );
+    assert!( BagEvent::Inserted( 7 ).removed().is_empty() );
+
+    // D. The element stored is removed, not the target.
+    let mut test_bag: ObservableBag<f64> = ObservableBag::new();
+    let events = test_bag.channel();
+    test_bag.insert( -0.0 );
+    test_bag.erase_one( 0.0 );
+    events.recv().unwrap();
+
+    match events.recv().unwrap()
+    {
+        BagEvent::Removed( value ) => assert!( value.is_sign_negative() ),
+        event                      => panic!( "unexpected {:?}", event )
+    }
+}
+
+#[ test ]
+///   Performs tests of subscribing and unsubscribing observers.
+fn test2()
+{
+    let mut test_bag = ObservableBag::from( [ 1 ].iter().cloned().collect::<Bag<i32>>() );
+    let seen = Rc::new( RefCell::new( Vec::new() ) );
+
+    // A. Callbacks are called in the order they subscribed.
+    let first = { let seen = seen.clone(); test_bag.subscribe( move | _ | seen.borrow_mut().push( "first" ) ) };
+    let second = { let seen = seen.clone(); test_bag.subscribe( move | _ | seen.borrow_mut().push( "second" ) ) };
+    assert!( first != second );
+
+    test_bag.insert( 2 );
+    assert_eq!( *seen.borrow(), vec![ "first", "second" ] );
+
+    // B. An unsubscribed callback is not called.
+    assert!( test_bag.unsubscribe( first ) );
+    assert!( !test_bag.unsubscribe( first ) );
+    test_bag.insert( 3 );
+    assert_eq!( *seen.borrow(), vec![ "first", "second", "second" ] );
+
+    // C. A channel without a receiver is unsubscribed at its next event.
+    let events = test_bag.channel();
+    assert_eq!( test_bag.observer_count(), 2 );
+    drop( events );
+    test_bag.insert( 4 );
+    assert_eq!( test_bag.observer_count(), 1 );
+
+    // D. Observers do not alter the bag.
+    assert_eq!( test_bag.into_bag(), [ 1, 2, 3, 4 ].iter().cloned().collect() );
+}
+
+#[ test ]
+///   Performs random changes mirrored into counts by a callback.
+fn test3()
+{
+    let mut rng = XorShiftRng::from_seed( [ 46, 2, 3, 4 ] );
+    let mut test_bag = ObservableBag::new();
+    let counts = Rc::new( RefCell::new( HashMap::new() ) );
+
+    {
+        let counts = counts.clone();
+
+        test_bag.subscribe( move | event: &BagEvent<i32> |
+            {
+                let mut counts = counts.borrow_mut();
+
+                for &value in event.inserted()
+                {
+                    *counts.entry( value ).or_insert( 0 ) += 1;
+                }
+
+                for value in event.removed()
+                {
+                    *counts.get_mut( value ).unwrap() -= 1;
+                }
+
+                counts.retain( | _, &mut count | count > 0 );
+            } );
+    }
+
+    for _ in 0..2000
+    {
+        let value = rng.gen_range( 0, 8 );
+
+        match rng.gen_range( 0, 20 )
+        {
+            0..=9   => test_bag.insert( value ),
+            10..=14 => { test_bag.erase_one( value ); }
+            15..=16 => { test_bag.erase( value ); }
+            17      => test_bag += [ value, value + 1 ].iter().cloned().collect(),
+            18      => test_bag.clone_from( &[ value, 1, value ].iter().cloned().collect() ),
+            _       => if rng.gen_range( 0, 10 ) == 0 { test_bag.clear() }
+        }
+
+        // A. The counts kept by the callback are those of the bag.
+        assert_eq!( *counts.borrow(), test_bag.bag().clone().into_counts() );
+    }
+}
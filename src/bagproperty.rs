@@ -0,0 +1,419 @@
+// FILE: bagproperty.rs
+
+//! Property based testing of the bags against a reference model.
+//!
+//! Random sequences of operations are applied both to a bag and to
+//! a `HashMap` from each value to its number of copies. After every
+//! operation the bag must agree with the model and keep the invariant
+//! of the Bag struct:
+//!
+//! 1. The number of elements, `len()`, is no more than `capacity()`.
+//!
+//! 2. The elements of the bag, and only those, are reached by its
+//! iterator; there are `len()` of them with the same number of each
+//! value as the model.
+//!
+//! A failing sequence is shrunk to a minimal one, by removing
+//! operations and making values smaller, before it is reported.
+//!
+//! The check is run for every bag by `conformance_tests!`.
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bagtrait::BagOps;
+use std::collections::HashMap;
+
+/// The values inserted are in `0..VALUE_RANGE`, small enough to
+/// give many duplicates.
+
+pub const VALUE_RANGE: i16 = 8;
+
+/// One operation on a bag, with the values used by it.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum Operation
+{
+    Insert( i16 ),
+    Erase( i16 ),
+    EraseOne( i16 ),
+    Reserve( usize ),
+    ShrinkToFit,
+    Clear,
+    AddAssign( Vec<i16> ),
+    Clone
+}
+
+/// Generate a random sequence of up to `max_len` operations.
+
+pub fn random_operations<R: Rng>( rng: &mut R, max_len: usize ) -> Vec<Operation>
+{
+    let len = rng.gen_range( 0, max_len + 1 );
+
+    ( 0..len ).map( | _ | random_operation( rng ) ).collect()
+}
+
+fn random_operation<R: Rng>( rng: &mut R ) -> Operation
+{
+    let value = rng.gen_range( 0, VALUE_RANGE );
+
+    // inserts are the most common so the bags grow
+    match rng.gen_range( 0, 12 )
+    {
+        0..=4 => Operation::Insert( value ),
+        5 => Operation::Erase( value ),
+        6 | 7 => Operation::EraseOne( value ),
+        8 => Operation::Reserve( rng.gen_range( 1, 64 ) ),
+        9 => Operation::ShrinkToFit,
+        10 => if rng.gen_weighted_bool( 4 )
+              {
+                  Operation::Clear
+              }
+              else
+              {
+                  Operation::Clone
+              },
+        _ =>
+            {
+                let len = rng.gen_range( 0, 6 );
+                Operation::AddAssign( ( 0..len ).map( | _ | rng.gen_range( 0, VALUE_RANGE ) ).collect() )
+            }
+    }
+}
+
+/// Apply `operations` to an empty bag and to the model, checking
+/// the bag after every operation.
+///
+/// # Return
+/// An Err describing the first operation after which the bag
+/// and model disagree.
+
+pub fn check<B: BagOps>( operations: &[Operation] ) -> Result<(), String>
+    where B::Item: From<i16>
+{
+    let mut bag = B::default();
+    let mut model: HashMap<i16, usize> = HashMap::new();
+
+    for ( step, operation ) in operations.iter().enumerate()
+    {
+        let fail = | message: String | Err( format!( "step {} ({:?}): {}", step, operation, message ) );
+
+        match *operation
+        {
+            Operation::Insert( value ) =>
+                {
+                    bag.insert( B::Item::from( value ) );
+                    *model.entry( value ).or_insert( 0 ) += 1;
+                }
+            Operation::Erase( value ) =>
+                {
+                    let expected = model.remove( &value ).unwrap_or( 0 );
+                    let erased = bag.erase( B::Item::from( value ) );
+
+                    if erased != expected
+                    {
+                        return fail( format!( "erased {} but the model had {}", erased, expected ) );
+                    }
+                }
+            Operation::EraseOne( value ) =>
+                {
+                    let expected = model.get( &value ).cloned().unwrap_or( 0 ) > 0;
+                    let erased = bag.erase_one( B::Item::from( value ) );
+
+                    if expected
+                    {
+                        *model.get_mut( &value ).unwrap() -= 1;
+                    }
+
+                    if erased != expected
+                    {
+                        return fail( format!( "returned {} but the model expected {}", erased, expected ) );
+                    }
+                }
+            Operation::Reserve( new_capacity ) =>
+                {
+                    bag.reserve( new_capacity );
+
+                    if bag.capacity() < new_capacity
+                    {
+                        return fail( format!( "capacity {} is less than reserved", bag.capacity() ) );
+                    }
+                }
+            Operation::ShrinkToFit => { bag.shrink_to_fit(); }
+            Operation::Clear =>
+                {
+                    bag.clear();
+                    model.clear();
+                }
+            Operation::AddAssign( ref values ) =>
+                {
+                    let mut other = B::default();
+
+                    for &value in values
+                    {
+                        other.insert( B::Item::from( value ) );
+                        *model.entry( value ).or_insert( 0 ) += 1;
+                    }
+
+                    bag += other;
+                }
+            Operation::Clone =>
+                {
+                    let copy = bag.clone();
+
+                    if copy != bag
+                    {
+                        return fail( "the clone is not equal to the bag".to_string() );
+                    }
+
+                    bag = copy;
+                }
+        }
+
+        if let Err( message ) = check_invariant( &bag, &model )
+        {
+            return fail( message );
+        }
+    }
+
+    Ok( () )
+}
+
+// Checks the invariant of the bag and that it holds the same values as the model.
+fn check_invariant<B: BagOps>( bag: &B, model: &HashMap<i16, usize> ) -> Result<(), String>
+    where B::Item: From<i16>
+{
+    let expected_len: usize = model.values().sum();
+
+    if bag.len() != expected_len
+    {
+        return Err( format!( "len() is {} but the model has {}", bag.len(), expected_len ) );
+    }
+
+    if bag.len() > bag.capacity()
+    {
+        return Err( format!( "len() {} is more than capacity() {}", bag.len(), bag.capacity() ) );
+    }
+
+    if bag.iter().count() != bag.len()
+    {
+        return Err( format!( "the iterator reached {} elements", bag.iter().count() ) );
+    }
+
+    let mut iterated = 0;
+
+    for value in 0..VALUE_RANGE
+    {
+        let expected = model.get( &value ).cloned().unwrap_or( 0 );
+        let item = B::Item::from( value );
+        let occurrences = bag.occurrences( item.clone() );
+        let reached = bag.iter().filter( | &element | *element == item ).count();
+
+        if occurrences != expected || reached != expected
+        {
+            return Err( format!( "{} has {} occurrences and {} iterated but the model has {}",
+                                 value, occurrences, reached, expected ) );
+        }
+
+        iterated += reached;
+    }
+
+    if iterated != bag.len()
+    {
+        return Err( format!( "the iterator reached {} elements that were never inserted",
+                             bag.len() - iterated ) );
+    }
+
+    Ok( () )
+}
+
+/// Shrink a failing sequence of operations to a minimal one.
+///
+/// # Parameter: `fails`
+/// Whether a sequence still fails. `fails( &operations )` must be true.
+///
+/// # Return
+/// A failing sequence from which no operation can be removed and
+/// no value made smaller without it passing.
+
+pub fn shrink<F: Fn( &[Operation] ) -> bool>( mut operations: Vec<Operation>, fails: F ) -> Vec<Operation>
+{
+    let mut progress = true;
+
+    while progress
+    {
+        progress = false;
+
+        // remove runs of operations, from half the sequence down to one
+        let mut chunk = ( operations.len() / 2 ).max( 1 );
+
+        while chunk > 0 && !operations.is_empty()
+        {
+            let mut start = 0;
+
+            while start < operations.len()
+            {
+                let end = ( start + chunk ).min( operations.len() );
+                let candidate: Vec<Operation> = operations[ ..start ].iter()
+                                                                     .chain( operations[ end.. ].iter() )
+                                                                     .cloned()
+                                                                     .collect();
+
+                if fails( &candidate )
+                {
+                    operations = candidate;
+                    progress = true;
+                }
+                else
+                {
+                    start += chunk;
+                }
+            }
+
+            chunk /= 2;
+        }
+
+        // make each operation simpler
+        for index in 0..operations.len()
+        {
+            for simpler in simplify( &operations[ index ] )
+            {
+                let mut candidate = operations.clone();
+                candidate[ index ] = simpler;
+
+                if fails( &candidate )
+                {
+                    operations = candidate;
+                    progress = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    operations
+}
+
+// Returns simpler versions of an operation, simplest first.
+fn simplify( operation: &Operation ) -> Vec<Operation>
+{
+    let smaller = | value: i16 | ( 0..value ).collect::<Vec<i16>>();
+
+    match *operation
+    {
+        Operation::Insert( value ) => smaller( value ).into_iter().map( Operation::Insert ).collect(),
+        Operation::Erase( value ) => smaller( value ).into_iter().map( Operation::Erase ).collect(),
+        Operation::EraseOne( value ) => smaller( value ).into_iter().map( Operation::EraseOne ).collect(),
+        Operation::Reserve( new_capacity ) =>
+            {
+                if new_capacity > 1
+                {
+                    vec![ Operation::Reserve( 1 ), Operation::Reserve( new_capacity / 2 ) ]
+                }
+                else
+                {
+                    Vec::new()
+                }
+            }
+        Operation::AddAssign( ref values ) =>
+            {
+                let mut simpler: Vec<Operation> = ( 0..values.len() ).map( | index |
+                    {
+                        let mut fewer = values.clone();
+                        fewer.remove( index );
+                        Operation::AddAssign( fewer )
+                    } ).collect();
+
+                for ( index, &value ) in values.iter().enumerate()
+                {
+                    if value > 0
+                    {
+                        let mut smaller = values.clone();
+                        smaller[ index ] = 0;
+                        simpler.push( Operation::AddAssign( smaller ) );
+                    }
+                }
+
+                simpler
+            }
+        Operation::ShrinkToFit | Operation::Clear | Operation::Clone => Vec::new()
+    }
+}
+
+/// Check `cases` random sequences of up to `max_len` operations
+/// on a bag type, from a random seed.
+///
+/// # Panics
+/// With the seed and the shrunk sequence, if a sequence fails. The
+/// failure is replayed by `check_seeded( _, _, _ )` with that seed.
+
+pub fn check_random<B: BagOps>( cases: usize, max_len: usize )
+    where B::Item: From<i16>
+{
+    let seed = [ rand::random::<u32>() | 1, rand::random(), rand::random(), rand::random() ];
+
+    check_seeded::<B>( seed, cases, max_len );
+}
+
+/// Check `cases` random sequences of up to `max_len` operations
+/// on a bag type, generated from `seed`.
+///
+/// # Parameter: `seed`
+/// The seed of the sequences, as printed by a failure of
+/// `check_random( _, _ )`. The same seed gives the same sequences.
+///
+/// # Panics
+/// With the seed and the shrunk sequence, if a sequence fails, or
+/// if `seed` is all zeros.
+
+pub fn check_seeded<B: BagOps>( seed: [u32; 4], cases: usize, max_len: usize )
+    where B::Item: From<i16>
+{
+    let mut rng = XorShiftRng::from_seed( seed );
+
+    for _ in 0..cases
+    {
+        let operations = random_operations( &mut rng, max_len );
+
+        if check::<B>( &operations ).is_err()
+        {
+            let minimal = shrink( operations, | operations | check::<B>( operations ).is_err() );
+
+            panic!( "seed {:?}: {}\nminimal failing operations: {:?}",
+                    seed, check::<B>( &minimal ).unwrap_err(), minimal );
+        }
+    }
+}
+
+#[ test ]
+///   Performs a test of shrinking a failing sequence.
+fn test_shrink()
+{
+    let fails = | operations: &[Operation] |
+        operations.iter().any( | operation | match *operation
+                                             {
+                                                 Operation::Insert( value ) => value >= 3,
+                                                 _ => false
+                                             } );
+
+    let operations = vec![ Operation::Clear,
+                           Operation::Insert( 1 ),
+                           Operation::Insert( 7 ),
+                           Operation::AddAssign( vec![ 2, 5 ] ),
+                           Operation::Insert( 6 ) ];
+
+    assert_eq!( shrink( operations, fails ), vec![ Operation::Insert( 3 ) ] );
+}
+
+#[ test ]
+///   Performs a test of replaying the sequences of a seed.
+fn test_seeded()
+{
+    let seed = [ 17, 2, 3, 4 ];
+
+    let first = random_operations( &mut XorShiftRng::from_seed( seed ), 40 );
+    let again = random_operations( &mut XorShiftRng::from_seed( seed ), 40 );
+    assert_eq!( first, again );
+
+    check_seeded::<::bag::Bag<i16>>( seed, 20, 40 );
+}
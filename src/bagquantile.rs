@@ -0,0 +1,544 @@
+// FILE: bagquantile.rs
+
+//! A streaming summary of numbers for estimating their quantiles and
+//! cumulative distribution, using memory that depends only on its
+//! compression and not on the number of values, as a t-digest.
+//!
+//! The values are kept as centroids, each a mean and the number of
+//! values merged into it. Centroids near the middle of the distribution
+//! hold many values and those near the ends hold few, so the quantiles
+//! near 0 and 1, such as the 99th percentile, are the most accurate.
+//! New values are buffered and merged into the centroids when the
+//! buffer is full.
+//!
+//! It is fed with `insert( _ )` or `extend( _ )`, made from a
+//! `bagf64::Bag` with `QuantileSketch::from( &bag )`, and sketches of
+//! different streams can be merged with `merge( _ )` or `+`.
+//!
+//! # Note
+//! NaN has no place in the order of the numbers, and is ignored. The
+//! infinities are kept apart from the finite numbers, so that they
+//! are the exact `min()` and `max()` and the quantiles next to them.
+
+use bagf64;
+use std::borrow::Cow;
+use std::f64::consts::PI;
+use std::fmt::{ self, Debug, Formatter };
+use std::ops::{ Add, AddAssign };
+
+/// The compression used by `QuantileSketch::default()` and `From`.
+
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+// The values buffered before merging, as a multiple of the compression.
+const BUFFER_FACTOR: f64 = 5.0;
+
+// A mean and the number of values merged into it.
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+struct Centroid
+{
+    mean: f64,
+    weight: f64
+}
+
+/// An estimator of the quantiles of the numbers inserted.
+///
+/// # Invariant of the QuantileSketch struct:
+/// 1. `centroids` are sorted by mean, and each has a weight of at least 1.
+///
+/// 2. `count` is the weight of the centroids plus the length of `buffer`,
+/// and `min` and `max` are the least and greatest values inserted.
+///
+/// 3. `buffer` holds fewer than `BUFFER_FACTOR * compression` values
+/// and no NaN.
+
+#[ derive( Clone ) ]
+pub struct QuantileSketch
+{
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    compression: f64,
+    count: f64,
+    min: f64,
+    max: f64
+}
+
+impl QuantileSketch
+{
+    /// Initialize a sketch that has seen no values.
+    ///
+    /// # Parameter: `compression`
+    /// A number of at least 10. The sketch keeps at most about this
+    /// many centroids, and the more it keeps the smaller the error.
+    ///
+    /// # Panics
+    /// `compression` given is less than 10 or not finite.
+    ///
+    /// # Examples
+    /// ```
+    /// let sketch = QuantileSketch::new( 200.0 );
+    ///
+    /// assert!( sketch.quantile( 0.5 ).is_nan() );
+    /// ```
+
+    pub fn new( compression: f64 ) -> Self
+    {
+        if !( compression >= 10.0 && compression.is_finite() )
+        {
+            panic!( "compression must be a finite number >= 10" );
+        }
+
+        QuantileSketch { centroids: Vec::new(),
+                         buffer: Vec::new(),
+                         compression,
+                         count: 0.0,
+                         min: f64::INFINITY,
+                         max: f64::NEG_INFINITY }
+    }
+
+    /// Initialize a sketch from the numbers of a bag.
+    ///
+    /// # Postcondition
+    /// The sketch is the same as one fed each number of `source`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = bagf64::Bag::new();
+    /// bag.insert( 1.0 );
+    /// bag.insert( 3.0 );
+    ///
+    /// assert_eq!( QuantileSketch::from_bag( &bag, 50.0 ).len(), 2 );
+    /// ```
+
+    pub fn from_bag( source: &bagf64::Bag, compression: f64 ) -> Self
+    {
+        let mut sketch = QuantileSketch::new( compression );
+
+        sketch.extend( source );
+        sketch
+    }
+
+    /// The compression the sketch was made with.
+
+    pub fn compression( &self ) -> f64
+    {
+        self.compression
+    }
+
+    /// The number of values inserted, not counting NaNs.
+
+    pub fn len( &self ) -> usize
+    {
+        self.count as usize
+    }
+
+    /// Whether no value has been inserted.
+
+    pub fn is_empty( &self ) -> bool
+    {
+        self.count == 0.0
+    }
+
+    /// The least value inserted, or NaN if there is none.
+
+    pub fn min( &self ) -> f64
+    {
+        if self.is_empty() { f64::NAN } else { self.min }
+    }
+
+    /// The greatest value inserted, or NaN if there is none.
+
+    pub fn max( &self ) -> f64
+    {
+        if self.is_empty() { f64::NAN } else { self.max }
+    }
+
+    /// The number of centroids the values have been merged into,
+    /// not counting the values still buffered.
+
+    pub fn centroid_count( &self ) -> usize
+    {
+        self.centroids.len()
+    }
+
+    /// Add a value to those summarized by the sketch.
+    ///
+    /// # Parameter: `new_item`
+    /// The value that is to be added, which is ignored if it is NaN.
+    ///
+    /// # Postcondition
+    /// The value is buffered, and the buffer is merged into the
+    /// centroids if it is full. The value itself is not kept.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut sketch = QuantileSketch::default();
+    /// sketch.insert( 2.0 );
+    /// sketch.insert( f64::NAN );
+    ///
+    /// assert_eq!( sketch.len(), 1 );
+    /// ```
+
+    pub fn insert( &mut self, new_item: f64 )
+    {
+        if new_item.is_nan()
+        {
+            return;
+        }
+
+        self.buffer.push( new_item );
+        self.count += 1.0;
+        self.min = self.min.min( new_item );
+        self.max = self.max.max( new_item );
+
+        if self.buffer.len() as f64 >= BUFFER_FACTOR * self.compression
+        {
+            self.flush();
+        }
+    }
+
+    /// Merge the values of another sketch into this one.
+    ///
+    /// # Postcondition
+    /// The sketch summarizes the values of both. `other` is not altered.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut low = QuantileSketch::default();
+    /// let mut high = QuantileSketch::default();
+    /// low.extend( vec![ 1.0, 2.0 ] );
+    /// high.extend( vec![ 3.0, 4.0 ] );
+    /// low.merge( &high );
+    ///
+    /// assert_eq!( ( low.len(), low.max() ), ( 4, 4.0 ) );
+    /// ```
+
+    pub fn merge( &mut self, other: &QuantileSketch )
+    {
+        if other.is_empty()
+        {
+            return;
+        }
+
+        let mut centroids = self.merged_centroids().into_owned();
+        centroids.extend( other.merged_centroids().iter() );
+
+        self.count += other.count;
+        self.min = self.min.min( other.min );
+        self.max = self.max.max( other.max );
+        self.centroids = compress( &mut centroids, self.count, self.compression );
+        self.buffer.clear();
+    }
+
+    /// Merge the buffered values into the centroids.
+    ///
+    /// # Postcondition
+    /// The buffer is empty. The estimates are not altered.
+
+    pub fn flush( &mut self )
+    {
+        if !self.buffer.is_empty()
+        {
+            self.centroids = self.merged_centroids().into_owned();
+            self.buffer.clear();
+        }
+    }
+
+    // The centroids with the buffered values merged into them.
+    fn merged_centroids( &self ) -> Cow<'_, [Centroid]>
+    {
+        if self.buffer.is_empty()
+        {
+            return Cow::Borrowed( &self.centroids );
+        }
+
+        let mut centroids = self.centroids.clone();
+        centroids.extend( self.buffer.iter().map( | &mean | Centroid { mean, weight: 1.0 } ) );
+
+        Cow::Owned( compress( &mut centroids, self.count, self.compression ) )
+    }
+
+    /// Estimate the value below which a fraction of the values fall.
+    ///
+    /// # Parameter: `p`
+    /// The fraction, from 0 to 1, so that 0.99 gives the 99th percentile.
+    ///
+    /// # Postcondition
+    /// This method does not alter the sketch.
+    ///
+    /// # Return
+    /// The estimate, interpolated between the means of the centroids,
+    /// `min()` for 0 and `max()` for 1, or NaN if the sketch is empty.
+    ///
+    /// # Panics
+    /// `p` given is not from 0 to 1.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut sketch = QuantileSketch::default();
+    /// sketch.extend( ( 1..=5 ).map( f64::from ) );
+    ///
+    /// assert_eq!( sketch.quantile( 0.5 ), 3.0 );
+    /// ```
+
+    pub fn quantile( &self, p: f64 ) -> f64
+    {
+        if !( 0.0..=1.0 ).contains( &p )
+        {
+            panic!( "p must be from 0 to 1" );
+        }
+
+        if self.is_empty()
+        {
+            return f64::NAN;
+        }
+
+        let centroids = self.merged_centroids();
+        let target = p * self.count;
+
+        // each centroid's values are centered on its mean, so the mean
+        // is at the centroid's cumulative weight plus half its own
+        let mut previous = ( 0.0, self.min );
+        let mut cumulative = 0.0;
+
+        for centroid in centroids.iter()
+        {
+            let center = cumulative + centroid.weight / 2.0;
+
+            if target < center
+            {
+                return interpolate( previous, ( center, centroid.mean ), target );
+            }
+
+            previous = ( center, centroid.mean );
+            cumulative += centroid.weight;
+        }
+
+        interpolate( previous, ( self.count, self.max ), target )
+    }
+
+    /// Estimate the fraction of the values that are at most `x`.
+    ///
+    /// # Postcondition
+    /// This method does not alter the sketch.
+    ///
+    /// # Return
+    /// The estimate, from 0 to 1, interpolated between the means of
+    /// the centroids, or NaN if the sketch is empty or `x` is NaN.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut sketch = QuantileSketch::default();
+    /// sketch.extend( ( 1..=4 ).map( f64::from ) );
+    ///
+    /// assert_eq!( sketch.cdf( 0.0 ), 0.0 );
+    /// assert_eq!( sketch.cdf( 4.0 ), 1.0 );
+    /// ```
+
+    pub fn cdf( &self, x: f64 ) -> f64
+    {
+        if self.is_empty() || x.is_nan()
+        {
+            return f64::NAN;
+        }
+
+        if x < self.min
+        {
+            return 0.0;
+        }
+
+        if x >= self.max
+        {
+            return 1.0;
+        }
+
+        let centroids = self.merged_centroids();
+        let mut previous = ( self.min, 0.0 );
+        let mut cumulative = 0.0;
+
+        for centroid in centroids.iter()
+        {
+            let center = cumulative + centroid.weight / 2.0;
+
+            if x < centroid.mean
+            {
+                return interpolate( previous, ( centroid.mean, center ), x ) / self.count;
+            }
+
+            previous = ( centroid.mean, center );
+            cumulative += centroid.weight;
+        }
+
+        interpolate( previous, ( self.max, self.count ), x ) / self.count
+    }
+}
+
+// The y of `x` on the line between the points `from` and `to`, or
+// the y of the nearer point if either is at an infinity.
+fn interpolate( from: ( f64, f64 ), to: ( f64, f64 ), x: f64 ) -> f64
+{
+    if to.0 <= from.0 || from.1 == to.1
+    {
+        return from.1;
+    }
+
+    if !( from.0.is_finite() && from.1.is_finite() && to.0.is_finite() && to.1.is_finite() )
+    {
+        return if x - from.0 < to.0 - x { from.1 } else { to.1 };
+    }
+
+    // weighing each y apart cannot overflow as their difference can
+    let t = ( x - from.0 ) / ( to.0 - from.0 );
+
+    from.1 * ( 1.0 - t ) + to.1 * t
+}
+
+// The scale of the quantile `q`, so that a centroid may span at most
+// one unit of scale, which is narrow near 0 and 1 and wide in the middle.
+fn scale( q: f64, compression: f64 ) -> f64
+{
+    compression / ( 2.0 * PI ) * ( 2.0 * q - 1.0 ).asin()
+}
+
+// The quantile at `k` units of scale.
+fn unscale( k: f64, compression: f64 ) -> f64
+{
+    if k >= compression / 4.0
+    {
+        1.0
+    }
+    else
+    {
+        ( ( k * 2.0 * PI / compression ).sin() + 1.0 ) / 2.0
+    }
+}
+
+// Sorts `centroids`, whose weights add up to `count`, and merges
+// neighbours while the merged centroid spans at most one unit of scale.
+// An infinity is only merged with the same infinity, so that it is
+// never averaged into a NaN.
+fn compress( centroids: &mut [Centroid], count: f64, compression: f64 ) -> Vec<Centroid>
+{
+    centroids.sort_by( | a, b | a.mean.total_cmp( &b.mean ) );
+
+    let mut merged: Vec<Centroid> = Vec::with_capacity( compression as usize );
+    let mut before = 0.0;
+    let mut limit = unscale( scale( 0.0, compression ) + 1.0, compression );
+
+    for &centroid in centroids.iter()
+    {
+        let fits = match merged.last()
+        {
+            Some( last ) => ( before + last.weight + centroid.weight ) / count <= limit
+                            && ( last.mean == centroid.mean || last.mean.is_finite() && centroid.mean.is_finite() ),
+            None => false
+        };
+
+        if fits
+        {
+            let last = merged.last_mut().unwrap();
+            let weight = last.weight + centroid.weight;
+
+            // weighing each mean apart cannot overflow as their difference can
+            if last.mean != centroid.mean
+            {
+                last.mean = last.mean * ( last.weight / weight ) + centroid.mean * ( centroid.weight / weight );
+            }
+
+            last.weight = weight;
+        }
+        else
+        {
+            if let Some( last ) = merged.last()
+            {
+                before += last.weight;
+                limit = unscale( scale( before / count, compression ) + 1.0, compression );
+            }
+
+            merged.push( centroid );
+        }
+    }
+
+    merged
+}
+
+impl Default for QuantileSketch
+{
+    /// A sketch with the default compression.
+
+    fn default() -> Self
+    {
+        QuantileSketch::new( DEFAULT_COMPRESSION )
+    }
+}
+
+impl<'a> From<&'a bagf64::Bag> for QuantileSketch
+{
+    /// A sketch with the default compression of the numbers of a bag.
+
+    fn from( source: &'a bagf64::Bag ) -> Self
+    {
+        QuantileSketch::from_bag( source, DEFAULT_COMPRESSION )
+    }
+}
+
+impl Extend<f64> for QuantileSketch
+{
+    /// Add each value of a stream to those summarized by the sketch.
+
+    fn extend<Values: IntoIterator<Item = f64>>( &mut self, values: Values )
+    {
+        for value in values
+        {
+            self.insert( value );
+        }
+    }
+}
+
+impl<'a> Extend<&'a f64> for QuantileSketch
+{
+    /// Add each value of a bag, or other stream of references, to
+    /// those summarized by the sketch.
+
+    fn extend<Values: IntoIterator<Item = &'a f64>>( &mut self, values: Values )
+    {
+        for &value in values
+        {
+            self.insert( value );
+        }
+    }
+}
+
+impl AddAssign for QuantileSketch
+{
+    /// Merge the values of another sketch into this one.
+
+    fn add_assign( &mut self, addend: QuantileSketch )
+    {
+        self.merge( &addend );
+    }
+}
+
+impl Add for QuantileSketch
+{
+    type Output = QuantileSketch;
+
+    /// Merge two sketches into a new sketch.
+
+    fn add( mut self, addend: QuantileSketch ) -> QuantileSketch
+    {
+        self.merge( &addend );
+        self
+    }
+}
+
+impl Debug for QuantileSketch
+{
+    /// Renders the number of values and centroids and the compression.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        write!( fmt, "QuantileSketch of {} values in {} centroids and {} buffered (compression {})",
+                self.len(), self.centroids.len(), self.buffer.len(), self.compression )
+    }
+}
@@ -0,0 +1,243 @@
+// FILE: bagquantileexam.rs
+
+//! This module holds tests for the quantile sketch of bagquantile
+//!
+//! 1) tests of an empty sketch and of small numbers of values
+//!
+//! 2) tests of the error of quantile( _ ) and cdf( _ ) on large streams
+//!
+//! 3) tests of the memory used
+//!
+//! 4) tests of merging with merge( _ ), += and +
+//!
+//! 5) tests of making a sketch from a bagf64::Bag
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bagquantile::{ QuantileSketch, DEFAULT_COMPRESSION };
+
+// Latencies in which most are short and a few are very long.
+fn latencies( len: usize, seed: u32 ) -> Vec<f64>
+{
+    let mut rng = XorShiftRng::from_seed( [ seed, 2, 3, 4 ] );
+
+    ( 0..len ).map( | _ | -( 1.0 - rng.gen::<f64>() ).ln() * 20.0 ).collect()
+}
+
+// The fraction of the sorted `values` less than `x`, counting equal
+// values as half.
+fn exact_rank( sorted: &[f64], x: f64 ) -> f64
+{
+    let below = sorted.iter().filter( | &&value | value < x ).count();
+    let equal = sorted.iter().filter( | &&value | value == x ).count();
+
+    ( below as f64 + equal as f64 / 2.0 ) / sorted.len() as f64
+}
+
+#[ test ]
+///   Performs tests of an empty sketch and of small numbers of values.
+fn test1()
+{
+    let mut sketch = QuantileSketch::default();
+
+    // A. An empty sketch.
+    assert!( sketch.is_empty() );
+    assert_eq!( sketch.compression(), DEFAULT_COMPRESSION );
+    assert!( sketch.quantile( 0.5 ).is_nan() );
+    assert!( sketch.cdf( 1.0 ).is_nan() );
+    assert!( sketch.min().is_nan() && sketch.max().is_nan() );
+
+    // B. NaN is ignored.
+    sketch.insert( f64::NAN );
+    assert!( sketch.is_empty() );
+
+    // C. A few values are kept exactly.
+    sketch.extend( vec![ 5.0, 1.0, 4.0, 2.0, 3.0 ] );
+
+    assert_eq!( sketch.len(), 5 );
+    assert_eq!( ( sketch.min(), sketch.max() ), ( 1.0, 5.0 ) );
+    assert_eq!( sketch.quantile( 0.0 ), 1.0 );
+    assert_eq!( sketch.quantile( 0.5 ), 3.0 );
+    assert_eq!( sketch.quantile( 1.0 ), 5.0 );
+    assert_eq!( sketch.cdf( 0.5 ), 0.0 );
+    assert_eq!( sketch.cdf( 3.0 ), 0.5 );
+    assert_eq!( sketch.cdf( 5.0 ), 1.0 );
+
+    // D. The estimates do not change when the buffer is flushed.
+    let before = ( sketch.quantile( 0.3 ), sketch.cdf( 2.5 ) );
+    sketch.flush();
+    assert_eq!( ( sketch.quantile( 0.3 ), sketch.cdf( 2.5 ) ), before );
+
+    // E. Every value the same.
+    let mut sketch = QuantileSketch::new( 20.0 );
+    sketch.extend( vec![ 7.0; 1000 ] );
+    assert_eq!( sketch.quantile( 0.01 ), 7.0 );
+    assert_eq!( sketch.quantile( 0.99 ), 7.0 );
+    assert_eq!( sketch.cdf( 7.0 ), 1.0 );
+
+    // F. The infinities are kept apart from the finite values.
+    let mut sketch = QuantileSketch::new( 20.0 );
+    sketch.extend( vec![ f64::INFINITY; 2000 ] );
+    assert_eq!( ( sketch.quantile( 0.0 ), sketch.quantile( 0.5 ) ), ( f64::INFINITY, f64::INFINITY ) );
+    assert_eq!( sketch.cdf( 1.0 ), 0.0 );
+
+    sketch.extend( ( 0..1000 ).map( f64::from ) );
+    sketch.extend( vec![ f64::NEG_INFINITY; 1000 ] );
+    sketch.extend( vec![ f64::MAX, -f64::MAX ] );
+    assert_eq!( ( sketch.min(), sketch.max() ), ( f64::NEG_INFINITY, f64::INFINITY ) );
+    assert_eq!( sketch.quantile( 0.1 ), f64::NEG_INFINITY );
+    assert!( sketch.quantile( 0.375 ).is_finite() );
+    assert_eq!( sketch.quantile( 0.9 ), f64::INFINITY );
+    assert!( ( 0.0..=1.0 ).contains( &sketch.cdf( 500.0 ) ) );
+    assert_eq!( sketch.cdf( f64::INFINITY ), 1.0 );
+}
+
+#[ test ]
+///   Performs tests of the error of quantile( _ ) and cdf( _ ) on large streams.
+fn test2()
+{
+    let values = latencies( 200_000, 1 );
+    let mut sorted = values.clone();
+    sorted.sort_by( | a, b | a.partial_cmp( b ).unwrap() );
+
+    let mut sketch = QuantileSketch::default();
+    sketch.extend( values );
+
+    for &p in &[ 0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999 ]
+    {
+        // A. The rank of the estimate is close to p, and closest near 0 and 1.
+        let estimate = sketch.quantile( p );
+        let allowed = if ( p - 0.5 ).abs() > 0.45 { 0.001 } else { 0.01 };
+
+        assert!( ( exact_rank( &sorted, estimate ) - p ).abs() < allowed,
+                 "quantile( {} ) = {} has rank {}", p, estimate, exact_rank( &sorted, estimate ) );
+
+        // B. The cdf of the exact quantile is close to p.
+        let exact = sorted[ ( p * sorted.len() as f64 ) as usize ];
+        assert!( ( sketch.cdf( exact ) - p ).abs() < allowed,
+                 "cdf( {} ) = {} for p = {}", exact, sketch.cdf( exact ), p );
+    }
+
+    assert_eq!( sketch.quantile( 0.0 ), sorted[ 0 ] );
+    assert_eq!( sketch.quantile( 1.0 ), sorted[ sorted.len() - 1 ] );
+}
+
+#[ test ]
+///   Performs tests of the memory used.
+fn test3()
+{
+    for &compression in &[ 20.0, 100.0, 500.0 ]
+    {
+        let mut sketch = QuantileSketch::new( compression );
+
+        for value in latencies( 300_000, 2 )
+        {
+            sketch.insert( value );
+            assert!( sketch.centroid_count() as f64 <= compression );
+        }
+
+        assert_eq!( sketch.len(), 300_000 );
+    }
+}
+
+#[ test ]
+///   Performs tests of merging with merge( _ ), += and +.
+fn test4()
+{
+    let values = latencies( 100_000, 3 );
+    let mut sorted = values.clone();
+    sorted.sort_by( | a, b | a.partial_cmp( b ).unwrap() );
+
+    // A. Sketches of parts of the stream merged into one.
+    let mut parts: Vec<QuantileSketch> = values.chunks( 7_000 ).map( | chunk |
+        {
+            let mut part = QuantileSketch::default();
+            part.extend( chunk );
+            part
+        } ).collect();
+
+    let mut merged = parts.pop().unwrap();
+
+    for part in &parts
+    {
+        merged.merge( part );
+    }
+
+    assert_eq!( merged.len(), 100_000 );
+    assert_eq!( ( merged.min(), merged.max() ), ( sorted[ 0 ], sorted[ sorted.len() - 1 ] ) );
+    assert!( merged.centroid_count() as f64 <= DEFAULT_COMPRESSION );
+
+    for &p in &[ 0.01, 0.5, 0.99 ]
+    {
+        assert!( ( exact_rank( &sorted, merged.quantile( p ) ) - p ).abs() < 0.01 );
+    }
+
+    // B. Merging with an empty sketch changes nothing.
+    let before = merged.quantile( 0.9 );
+    merged += QuantileSketch::default();
+    assert_eq!( merged.quantile( 0.9 ), before );
+
+    // C. + gives the values of both.
+    let mut low = QuantileSketch::default();
+    let mut high = QuantileSketch::default();
+    low.extend( ( 0..500 ).map( f64::from ) );
+    high.extend( ( 500..1000 ).map( f64::from ) );
+
+    let both = low + high;
+    assert_eq!( both.len(), 1000 );
+    assert!( ( both.quantile( 0.5 ) - 500.0 ).abs() < 10.0 );
+}
+
+#[ test ]
+///   Performs tests of making a sketch from a bagf64::Bag.
+fn test5()
+{
+    let mut test_bag = ::bagf64::Bag::new();
+
+    for value in latencies( 10_000, 4 )
+    {
+        test_bag.insert( value );
+    }
+
+    test_bag.insert( f64::NAN );
+
+    // A. From gives the same sketch as inserting each number, without the NaN.
+    let sketch = QuantileSketch::from( &test_bag );
+    let mut inserted = QuantileSketch::default();
+
+    for &value in &test_bag
+    {
+        inserted.insert( value );
+    }
+
+    assert_eq!( sketch.len(), 10_000 );
+    assert_eq!( sketch.quantile( 0.99 ), inserted.quantile( 0.99 ) );
+
+    // B. The compression given is used.
+    let sketch = QuantileSketch::from_bag( &test_bag, 30.0 );
+    assert_eq!( sketch.compression(), 30.0 );
+    assert!( sketch.centroid_count() <= 30 );
+
+    // C. A bag holding the infinities.
+    for _ in 0..1000
+    {
+        test_bag.insert( f64::INFINITY );
+        test_bag.insert( f64::NEG_INFINITY );
+    }
+
+    let sketch = QuantileSketch::from_bag( &test_bag, 30.0 );
+    assert_eq!( sketch.len(), 12_000 );
+    assert_eq!( ( sketch.quantile( 0.05 ), sketch.quantile( 0.95 ) ), ( f64::NEG_INFINITY, f64::INFINITY ) );
+    assert!( sketch.quantile( 0.5 ).is_finite() );
+}
+
+#[ test ]
+#[ should_panic ]
+///   Performs a test of a quantile outside from 0 to 1.
+fn test6()
+{
+    QuantileSketch::default().quantile( 1.5 );
+}
@@ -0,0 +1,37 @@
+// FILE: bagscratch.rs
+
+//! Scratch paths in the temporary directory, for the tests of the
+//! bags kept in files.
+//!
+//! A path is named after the module, the process and the test, so
+//! that tests run at once, by one program or by several, never share
+//! a file. Whatever an earlier run left at the path is removed.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+/// A path for the test `name` of `module`, with nothing at it.
+
+pub fn scratch_path( module: &str, name: &str ) -> PathBuf
+{
+    let path = env::temp_dir().join( format!( "{}-{}-{}", module, process::id(), name ) );
+
+    let _ = fs::remove_file( &path );
+    let _ = fs::remove_dir_all( &path );
+    path
+}
+
+/// An empty directory for the test `name` of `module`.
+///
+/// # Panics
+/// The directory could not be created.
+
+pub fn scratch_directory( module: &str, name: &str ) -> PathBuf
+{
+    let path = scratch_path( module, name );
+
+    fs::create_dir( &path ).unwrap();
+    path
+}
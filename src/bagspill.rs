@@ -0,0 +1,660 @@
+// FILE: bagspill.rs
+
+//! A bag for more elements than fit in memory, which holds at most a
+//! budget of elements in a `bag::Bag` and spills the rest to disk.
+//!
+//! When the bag in memory reaches the budget its elements are sorted,
+//! counted, and written to a temporary file as a sorted run of each
+//! distinct value with its count, and the bag in memory is emptied.
+//! `occurrences( _ )`, `counts()` and `iter()` merge the runs with
+//! the bag in memory, reading each run from start to end, so a bag of
+//! billions of elements is counted in the memory of its budget and
+//! the buffers of its runs.
+//!
+//! The runs are merged level by level. A spilled run is of level 0,
+//! and when there are `MERGE_FAN_IN` runs of one level they are merged
+//! into one run of the next level. Each element is so rewritten once
+//! for each level, of which there are about the logarithm to the base
+//! `MERGE_FAN_IN` of the number of spills, and there are fewer than
+//! `MERGE_FAN_IN` runs of each level.
+//!
+//! The budget is a number of elements, not of bytes. The array in
+//! memory grows to at most the budget, and a spill writes each value
+//! of the sorted array to the run as it goes, then drops the elements
+//! and the array. The memory used is so the budget times the size of
+//! `Type`, and what each element holds on the heap besides, as a
+//! `String` does; the budget of a bag of elements of different sizes
+//! is best chosen for the largest. `counts()` and `iter()` copy each
+//! distinct value in memory besides.
+//!
+//! # The runs
+//! A run is a sequence of records in ascending order of their values,
+//! with no value twice, each written as
+//!
+//! ```text
+//! count: u64 LE | length: u32 LE | text
+//! ```
+//!
+//! where `text` is the value as written by `Display`, of `length`
+//! bytes. The files are made in the temporary directory, or the one
+//! given, and removed when they are merged or the bag is dropped.
+//!
+//! The type used must have the traits `Ord`, `Clone`, `Default`,
+//! `Display` and `FromStr`, and each element must be read back from
+//! its text as an equal element.
+
+use bag::{ Bag, CapacityMut };
+use bag::len_trait::len::{ Len, Empty, Clear };
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::{ self, Debug, Display, Formatter };
+use std::fs::{ self, File, OpenOptions };
+use std::io::{ self, BufRead, BufReader, BufWriter, Read, Write };
+use std::iter;
+use std::marker::PhantomData;
+use std::path::{ Path, PathBuf };
+use std::str::FromStr;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::vec;
+
+/// The number of runs of one level that are merged into one run of
+/// the next level.
+pub const MERGE_FAN_IN: usize = 16;
+
+// The budget of a bag made by new( ).
+const DEFAULT_BUDGET: usize = 1 << 20;
+
+// The bytes of a record before its text.
+const HEADER_LEN: usize = 12;
+
+// The number of the next run made by this process, naming its file.
+static NEXT_RUN: AtomicUsize = AtomicUsize::new( 0 );
+
+/// A bag keeping at most a budget of its elements in memory and the
+/// rest in sorted runs on disk.
+///
+/// # Invariant of the SpillBag struct:
+/// 1. The elements of the bag are those of `bag` and of each run in
+/// `runs`, and `len` is their number.
+///
+/// 2. `bag` holds at most `budget` elements, and each run is a file
+/// in `directory`.
+///
+/// 3. The levels of `runs` never increase from first to last, and
+/// fewer than `MERGE_FAN_IN` runs are of each level.
+
+pub struct SpillBag<Type: Ord + Clone + Default + Display + FromStr>
+{
+    bag: Bag<Type>,
+    runs: Vec<Run>,
+    len: usize,
+    budget: usize,
+    directory: PathBuf
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> SpillBag<Type>
+    where Type::Err: Display
+{
+    /// Initialize an empty bag with a budget of 1048576 elements,
+    /// spilling to the temporary directory.
+
+    pub fn new() -> Self
+    {
+        SpillBag::with_budget( DEFAULT_BUDGET )
+    }
+
+    /// Initialize an empty bag spilling to the temporary directory.
+    ///
+    /// # Parameter: `budget`
+    /// The most elements held in memory.
+    ///
+    /// # Panics
+    /// If `budget` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = SpillBag::with_budget( 2 );
+    /// bag.insert( "b".to_string() ).unwrap();
+    /// bag.insert( "a".to_string() ).unwrap();
+    /// bag.insert( "b".to_string() ).unwrap();
+    ///
+    /// assert_eq!( bag.runs(), 1 );
+    /// assert_eq!( bag.occurrences( "b".to_string() ).unwrap(), 2 );
+    /// ```
+
+    pub fn with_budget( budget: usize ) -> Self
+    {
+        SpillBag::with_budget_in( budget, ::std::env::temp_dir() )
+    }
+
+    /// Initialize an empty bag spilling to `directory`.
+    ///
+    /// # Parameter: `budget`
+    /// The most elements held in memory, counted as elements whatever
+    /// their size.
+    ///
+    /// # Parameter: `directory`
+    /// The directory the runs are made in, which must exist when the
+    /// bag first spills.
+    ///
+    /// # Panics
+    /// If `budget` is 0.
+
+    pub fn with_budget_in<P: AsRef<Path>>( budget: usize, directory: P ) -> Self
+    {
+        assert!( budget > 0, "the budget of a spilling bag must be at least 1" );
+
+        SpillBag
+        {
+            bag: Bag::new(),
+            runs: Vec::new(),
+            len: 0,
+            budget,
+            directory: directory.as_ref().to_path_buf()
+        }
+    }
+
+    /// The most elements held in memory, a number of elements rather
+    /// than of bytes.
+
+    pub fn budget( &self ) -> usize
+    {
+        self.budget
+    }
+
+    /// The directory the runs are made in.
+
+    pub fn directory( &self ) -> &Path
+    {
+        &self.directory
+    }
+
+    /// The number of runs on disk, of which fewer than `MERGE_FAN_IN`
+    /// are of each level.
+
+    pub fn runs( &self ) -> usize
+    {
+        self.runs.len()
+    }
+
+    /// The number of elements held in memory rather than in runs.
+
+    pub fn in_memory( &self ) -> usize
+    {
+        self.bag.size()
+    }
+
+    /// Add a new element to this bag, first spilling the elements in
+    /// memory to a run if there are as many as the budget.
+    ///
+    /// # Parameter: `new_item`
+    /// The element to insert.
+    ///
+    /// # Postcondition
+    /// If the elements could be spilled, the bag holds `new_item`.
+    /// Otherwise the bag is not altered.
+    ///
+    /// # Return
+    /// An Err if a run could not be written or merged.
+
+    pub fn insert( &mut self, new_item: Type ) -> io::Result<()>
+    {
+        if self.bag.size() >= self.budget
+        {
+            self.spill()?;
+        }
+
+        // grow by doubling, but to no more than the budget
+        if self.bag.size() == self.bag.get_capacity()
+        {
+            let capacity = self.bag.size().saturating_mul( 2 ).min( self.budget );
+            self.bag.reserve( capacity );
+        }
+
+        self.bag.insert( new_item );
+        self.len += 1;
+        Ok( () )
+    }
+
+    /// Determine the number of times `target` is in this bag.
+    ///
+    /// # Postcondition
+    /// Each run has been read up to the values after `target`. The
+    /// bag is not altered.
+    ///
+    /// # Return
+    /// The number of times `target` occurs, or an Err if a run could
+    /// not be read.
+
+    pub fn occurrences( &self, target: Type ) -> io::Result<usize>
+    {
+        let mut occurrences = self.bag.binary_search_count( &target );
+
+        for run in &self.runs
+        {
+            for record in run.open::<Type>()?
+            {
+                let ( value, count ) = record?;
+
+                if value > target
+                {
+                    break;
+                }
+
+                if value == target
+                {
+                    occurrences += count;
+                }
+            }
+        }
+
+        Ok( occurrences )
+    }
+
+    /// List each distinct value with its multiplicity, merging the runs
+    /// with the elements in memory.
+    ///
+    /// # Postcondition
+    /// The elements in memory are sorted. The elements of the bag are
+    /// not altered.
+    ///
+    /// # Return
+    /// An iterator over the `( value, multiplicity )` pairs in ascending
+    /// order of the values, or an Err if a run could not be opened. An
+    /// Err returned by the iterator ends it.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating the counts in memory.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = SpillBag::with_budget( 1 );
+    /// bag.insert( 4 ).unwrap();
+    /// bag.insert( 2 ).unwrap();
+    /// bag.insert( 4 ).unwrap();
+    ///
+    /// let counts: io::Result<Vec<( i32, usize )>> = bag.counts().unwrap().collect();
+    /// assert_eq!( counts.unwrap(), vec![ ( 2, 1 ), ( 4, 2 ) ] );
+    /// ```
+
+    pub fn counts( &mut self ) -> io::Result<Counts<Type>>
+    {
+        let mut sources = Vec::with_capacity( self.runs.len() + 1 );
+
+        for run in &self.runs
+        {
+            sources.push( Source::Run( run.open()? ) );
+        }
+
+        sources.push( Source::Memory( self.bag.dedup_counts().into_iter() ) );
+
+        Ok( Counts::new( sources ) )
+    }
+
+    /// Iterate over the elements of this bag in ascending order.
+    ///
+    /// # Postcondition
+    /// The elements in memory are sorted, as by counts( ).
+    ///
+    /// # Return
+    /// An iterator returning every element, or an Err if a run could
+    /// not be opened. An Err returned by the iterator ends it.
+
+    pub fn iter( &mut self ) -> io::Result<SpillIter<Type>>
+    {
+        Ok( SpillIter { counts: self.counts()?, current: None } )
+    }
+
+    // Write the elements in memory to a new run of level 0, then merge
+    // the newest MERGE_FAN_IN runs into one of the next level for as
+    // long as they are of one level.
+    fn spill( &mut self ) -> io::Result<()>
+    {
+        self.bag.sort();
+        let run = Run::write( &self.directory, 0, sorted_counts( &self.bag ).map( Ok ) )?;
+
+        self.runs.push( run );
+        // dropping the elements, which a clear would keep until written over
+        self.bag = Bag::new();
+
+        while self.runs.len() >= MERGE_FAN_IN
+        {
+            let first = self.runs.len() - MERGE_FAN_IN;
+            let level = self.runs[ first ].level;
+
+            if self.runs[ first.. ].iter().any( | run | run.level != level )
+            {
+                break;
+            }
+
+            let mut sources: Vec<Source<Type>> = Vec::with_capacity( MERGE_FAN_IN );
+
+            for run in &self.runs[ first.. ]
+            {
+                sources.push( Source::Run( run.open()? ) );
+            }
+
+            let merged = Run::write( &self.directory, level + 1, Counts::new( sources ) )?;
+
+            // the runs merged are removed as they are dropped
+            self.runs.truncate( first );
+            self.runs.push( merged );
+        }
+
+        Ok( () )
+    }
+}
+
+// Each distinct value of the sorted `bag` with its count, copying one
+// value at a time as it is asked for.
+fn sorted_counts<'a, Type: PartialEq + Clone + Default>( bag: &'a Bag<Type> ) -> impl Iterator<Item = ( Type, usize )> + 'a
+{
+    let mut values = bag.into_iter().peekable();
+
+    iter::from_fn( move ||
+        {
+            let value = values.next()?;
+            let mut count = 1;
+
+            while values.next_if_eq( &value ).is_some()
+            {
+                count += 1;
+            }
+
+            Some( ( value.clone(), count ) )
+        } )
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> Len for SpillBag<Type>
+    where Type::Err: Display
+{
+    /// Determine the number of elements in this bag, in memory and in
+    /// the runs.
+
+    fn len( &self ) -> usize
+    {
+        self.len
+    }
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> Empty for SpillBag<Type>
+    where Type::Err: Display
+{
+    /// Determine if the bag is empty.
+
+    fn is_empty( &self ) -> bool
+    {
+        self.len == 0
+    }
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> Clear for SpillBag<Type>
+    where Type::Err: Display
+{
+    /// Remove every element from this bag, removing the files of
+    /// its runs.
+
+    fn clear( &mut self )
+    {
+        self.bag = Bag::new();
+        self.runs.clear();
+        self.len = 0;
+    }
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> Default for SpillBag<Type>
+    where Type::Err: Display
+{
+    fn default() -> Self
+    {
+        SpillBag::new()
+    }
+}
+
+impl<Type: Ord + Clone + Default + Display + FromStr> Debug for SpillBag<Type>
+{
+    /// Shows where the elements are, without reading the runs.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        fmt.debug_struct( "SpillBag" )
+           .field( "len", &self.len )
+           .field( "in_memory", &self.bag.size() )
+           .field( "runs", &self.runs.len() )
+           .field( "budget", &self.budget )
+           .finish()
+    }
+}
+
+/// An iterator over the `( value, multiplicity )` pairs of a
+/// `SpillBag`, merging its runs.
+
+pub struct Counts<Type: Ord + FromStr>
+{
+    sources: Vec<Source<Type>>,
+    heads: BinaryHeap<Reverse<( Type, usize, usize )>>,
+    error: Option<io::Error>
+}
+
+impl<Type: Ord + FromStr> Counts<Type>
+    where Type::Err: Display
+{
+    // Merge `sources`, each in ascending order of its values.
+    fn new( sources: Vec<Source<Type>> ) -> Self
+    {
+        let mut counts = Counts { heads: BinaryHeap::with_capacity( sources.len() ), sources, error: None };
+
+        for source in 0..counts.sources.len()
+        {
+            counts.advance( source );
+        }
+
+        counts
+    }
+
+    // Read the next pair of `source` onto the heap of the least pairs.
+    fn advance( &mut self, source: usize )
+    {
+        match self.sources[ source ].next()
+        {
+            Some( Ok( ( value, count ) ) ) => self.heads.push( Reverse( ( value, source, count ) ) ),
+            Some( Err( error ) ) => self.error = Some( error ),
+            None => {}
+        }
+    }
+}
+
+impl<Type: Ord + FromStr> Iterator for Counts<Type>
+    where Type::Err: Display
+{
+    type Item = io::Result<( Type, usize )>;
+
+    fn next( &mut self ) -> Option<Self::Item>
+    {
+        if let Some( error ) = self.error.take()
+        {
+            self.heads.clear();
+            return Some( Err( error ) );
+        }
+
+        let Reverse( ( value, source, mut count ) ) = self.heads.pop()?;
+        self.advance( source );
+
+        while self.heads.peek().is_some_and( | &Reverse( ( ref next, _, _ ) ) | *next == value )
+        {
+            let Reverse( ( _, source, more ) ) = self.heads.pop().unwrap();
+
+            count += more;
+            self.advance( source );
+        }
+
+        // a source that failed may have held more of the value
+        if let Some( error ) = self.error.take()
+        {
+            self.heads.clear();
+            return Some( Err( error ) );
+        }
+
+        Some( Ok( ( value, count ) ) )
+    }
+}
+
+/// An iterator over the elements of a `SpillBag` in ascending order,
+/// merging its runs.
+
+pub struct SpillIter<Type: Ord + FromStr>
+{
+    counts: Counts<Type>,
+    current: Option<( Type, usize )>
+}
+
+impl<Type: Ord + Clone + FromStr> Iterator for SpillIter<Type>
+    where Type::Err: Display
+{
+    type Item = io::Result<Type>;
+
+    fn next( &mut self ) -> Option<Self::Item>
+    {
+        loop
+        {
+            if let Some( ( ref value, ref mut count ) ) = self.current
+            {
+                if *count > 0
+                {
+                    *count -= 1;
+                    return Some( Ok( value.clone() ) );
+                }
+            }
+
+            match self.counts.next()?
+            {
+                Ok( pair ) => self.current = Some( pair ),
+                Err( error ) => return Some( Err( error ) )
+            }
+        }
+    }
+}
+
+// A sorted sequence of pairs merged by Counts.
+enum Source<Type>
+{
+    Run( RunReader<Type> ),
+    Memory( vec::IntoIter<( Type, usize )> )
+}
+
+impl<Type: FromStr> Iterator for Source<Type>
+    where Type::Err: Display
+{
+    type Item = io::Result<( Type, usize )>;
+
+    fn next( &mut self ) -> Option<Self::Item>
+    {
+        match *self
+        {
+            Source::Run( ref mut reader ) => reader.next(),
+            Source::Memory( ref mut pairs ) => pairs.next().map( Ok )
+        }
+    }
+}
+
+// A file of a run, removed when it is dropped, and the level of the
+// merges that made it.
+struct Run
+{
+    path: PathBuf,
+    level: usize
+}
+
+impl Run
+{
+    // Write the pairs of `counts`, in ascending order of their values,
+    // to a new file of a run of `level` in `directory`, removing it if
+    // they cannot all be written.
+    fn write<Type, Pairs>( directory: &Path, level: usize, counts: Pairs ) -> io::Result<Run>
+        where Type: Display, Pairs: Iterator<Item = io::Result<( Type, usize )>>
+    {
+        let name = format!( "bagspill-{}-{}.run", ::std::process::id(), NEXT_RUN.fetch_add( 1, Ordering::Relaxed ) );
+        let run = Run { path: directory.join( name ), level };
+        let mut out = BufWriter::new( OpenOptions::new().write( true ).create_new( true ).open( &run.path )? );
+
+        for pair in counts
+        {
+            let ( value, count ) = pair?;
+            let text = value.to_string();
+
+            if text.len() > u32::MAX as usize
+            {
+                return Err( io::Error::new( io::ErrorKind::InvalidInput, "a value is too long to spill" ) );
+            }
+
+            out.write_all( &( count as u64 ).to_le_bytes() )?;
+            out.write_all( &( text.len() as u32 ).to_le_bytes() )?;
+            out.write_all( text.as_bytes() )?;
+        }
+
+        out.flush()?;
+        Ok( run )
+    }
+
+    // Open the run to read its pairs.
+    fn open<Type>( &self ) -> io::Result<RunReader<Type>>
+    {
+        Ok( RunReader { input: BufReader::new( File::open( &self.path )? ), marker: PhantomData } )
+    }
+}
+
+impl Drop for Run
+{
+    fn drop( &mut self )
+    {
+        let _ = fs::remove_file( &self.path );
+    }
+}
+
+// The pairs of a run, read in order.
+struct RunReader<Type>
+{
+    input: BufReader<File>,
+    marker: PhantomData<Type>
+}
+
+impl<Type: FromStr> RunReader<Type>
+    where Type::Err: Display
+{
+    // The next pair, or None at the end of the run.
+    fn read_pair( &mut self ) -> io::Result<Option<( Type, usize )>>
+    {
+        if self.input.fill_buf()?.is_empty()
+        {
+            return Ok( None );
+        }
+
+        let mut header = [ 0; HEADER_LEN ];
+        self.input.read_exact( &mut header )?;
+
+        let mut count = [ 0; 8 ];
+        let mut len = [ 0; 4 ];
+        count.copy_from_slice( &header[ ..8 ] );
+        len.copy_from_slice( &header[ 8.. ] );
+
+        let mut text = vec![ 0; u32::from_le_bytes( len ) as usize ];
+        self.input.read_exact( &mut text )?;
+
+        let text = String::from_utf8( text ).map_err( | error | io::Error::new( io::ErrorKind::InvalidData, error ) )?;
+        let value = text.parse::<Type>().map_err( | error |
+            io::Error::new( io::ErrorKind::InvalidData, format!( "a run holds `{}`, which cannot be read: {}", text, error ) ) )?;
+
+        Ok( Some( ( value, u64::from_le_bytes( count ) as usize ) ) )
+    }
+}
+
+impl<Type: FromStr> Iterator for RunReader<Type>
+    where Type::Err: Display
+{
+    type Item = io::Result<( Type, usize )>;
+
+    fn next( &mut self ) -> Option<Self::Item>
+    {
+        self.read_pair().transpose()
+    }
+}
@@ -0,0 +1,229 @@
+// FILE: bagspillexam.rs
+
+//! This module holds tests for the spilling bag of bagspill
+//!
+//! 1) tests of spilling elements past the budget
+//!
+//! 2) tests of merging the runs level by level
+//!
+//! 3) tests of elements whose text has the characters of the runs
+//!
+//! 4) random bags counted against bag::Bag
+//!
+//! 5) tests of a budget of zero
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::Bag;
+use bag::len_trait::len::{ Len, Empty, Clear };
+use bagscratch::scratch_directory;
+use bagspill::{ SpillBag, MERGE_FAN_IN };
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// The runs after `spills` spills, one for each unit of each digit of
+// `spills` written in base MERGE_FAN_IN.
+fn runs_after( mut spills: usize ) -> usize
+{
+    let mut runs = 0;
+
+    while spills > 0
+    {
+        runs += spills % MERGE_FAN_IN;
+        spills /= MERGE_FAN_IN;
+    }
+
+    runs
+}
+
+// The number of files in `directory`.
+fn file_count( directory: &PathBuf ) -> usize
+{
+    fs::read_dir( directory ).unwrap().count()
+}
+
+// The counts of a spilling bag, in ascending order of their values.
+fn counts_of<Type>( test_bag: &mut SpillBag<Type> ) -> Vec<( Type, usize )>
+    where Type: Ord + Clone + Default + ::std::fmt::Display + ::std::str::FromStr,
+          Type::Err: ::std::fmt::Display
+{
+    test_bag.counts().unwrap().collect::<io::Result<Vec<( Type, usize )>>>().unwrap()
+}
+
+#[ test ]
+///   Performs tests of spilling elements past the budget.
+fn test_spill()
+{
+    let directory = scratch_directory( "bagspill", "spill" );
+    let mut test_bag = SpillBag::with_budget_in( 3, &directory );
+
+    // A. Nothing is spilled up to the budget.
+    for &value in &[ 5, 1, 5 ]
+    {
+        test_bag.insert( value ).unwrap();
+    }
+
+    assert_eq!( ( test_bag.runs(), test_bag.in_memory() ), ( 0, 3 ) );
+    assert_eq!( file_count( &directory ), 0 );
+
+    // B. The elements in memory are spilled to a run past it.
+    for &value in &[ 3, 5, 1, 9 ]
+    {
+        test_bag.insert( value ).unwrap();
+    }
+
+    assert_eq!( ( test_bag.runs(), test_bag.in_memory() ), ( 2, 1 ) );
+    assert_eq!( file_count( &directory ), 2 );
+    assert_eq!( test_bag.len(), 7 );
+
+    // C. Counting across the runs and the elements in memory.
+    assert_eq!( test_bag.occurrences( 5 ).unwrap(), 3 );
+    assert_eq!( test_bag.occurrences( 1 ).unwrap(), 2 );
+    assert_eq!( test_bag.occurrences( 9 ).unwrap(), 1 );
+    assert_eq!( test_bag.occurrences( 4 ).unwrap(), 0 );
+    assert_eq!( counts_of( &mut test_bag ), vec![ ( 1, 2 ), ( 3, 1 ), ( 5, 3 ), ( 9, 1 ) ] );
+
+    let values: io::Result<Vec<i32>> = test_bag.iter().unwrap().collect();
+    assert_eq!( values.unwrap(), vec![ 1, 1, 3, 5, 5, 5, 9 ] );
+
+    // D. Clearing and dropping remove the runs.
+    test_bag.clear();
+    assert!( test_bag.is_empty() );
+    assert_eq!( file_count( &directory ), 0 );
+    assert_eq!( test_bag.iter().unwrap().count(), 0 );
+
+    test_bag.insert( 2 ).unwrap();
+    test_bag.insert( 2 ).unwrap();
+    test_bag.insert( 2 ).unwrap();
+    test_bag.insert( 2 ).unwrap();
+    assert_eq!( file_count( &directory ), 1 );
+    drop( test_bag );
+
+    assert_eq!( file_count( &directory ), 0 );
+    fs::remove_dir( &directory ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of merging the runs level by level.
+fn test_merge()
+{
+    let directory = scratch_directory( "bagspill", "merge" );
+    let mut test_bag = SpillBag::with_budget_in( 10, &directory );
+    let inserted = 10 * ( MERGE_FAN_IN * MERGE_FAN_IN + 3 );
+
+    // A. Only runs of one level are merged, past two levels of merges.
+    for value in 0..inserted
+    {
+        test_bag.insert( value % 37 ).unwrap();
+        assert_eq!( test_bag.runs(), runs_after( value / 10 ) );
+        assert_eq!( file_count( &directory ), test_bag.runs() );
+    }
+
+    assert_eq!( test_bag.runs(), 3 );
+
+    // B. Nothing is lost in the merges.
+    assert_eq!( test_bag.len(), inserted );
+
+    let counts = counts_of( &mut test_bag );
+    assert_eq!( counts.len(), 37 );
+    assert_eq!( counts.iter().map( | &( _, count ) | count ).sum::<usize>(), test_bag.len() );
+    assert_eq!( test_bag.occurrences( 36 ).unwrap(), ( 0..inserted ).filter( | value | value % 37 == 36 ).count() );
+
+    drop( test_bag );
+    fs::remove_dir( &directory ).unwrap();
+}
+
+#[ test ]
+///   Performs tests of elements whose text has the characters of the runs.
+fn test_element_text()
+{
+    let directory = scratch_directory( "bagspill", "strings" );
+    let mut test_bag = SpillBag::with_budget_in( 2, &directory );
+    let mut values = vec![ "", "line\nbreak", "a, b", "×", "", "\u{0}", "line\nbreak", " " ];
+
+    for value in &values
+    {
+        test_bag.insert( value.to_string() ).unwrap();
+    }
+
+    // A. Each value is read back as written.
+    values.sort();
+    let read: io::Result<Vec<String>> = test_bag.iter().unwrap().collect();
+    assert_eq!( read.unwrap(), values );
+    assert_eq!( test_bag.occurrences( String::new() ).unwrap(), 2 );
+
+    // B. A run that cannot be read as the type of the bag.
+    drop( test_bag );
+    let mut test_bag: SpillBag<String> = SpillBag::with_budget_in( 1, &directory );
+    test_bag.insert( "7".to_string() ).unwrap();
+    test_bag.insert( "x".to_string() ).unwrap();
+
+    let run = fs::read_dir( &directory ).unwrap().next().unwrap().unwrap().path();
+    let mut data = fs::read( &run ).unwrap();
+    data.truncate( data.len() - 1 );
+    fs::write( &run, &data ).unwrap();
+
+    let error = test_bag.occurrences( "7".to_string() ).unwrap_err();
+    assert_eq!( error.kind(), io::ErrorKind::UnexpectedEof );
+
+    let mut counts = test_bag.counts().unwrap();
+    assert!( counts.next().unwrap().is_err() );
+    assert!( counts.next().is_none() );
+
+    drop( counts );
+    drop( test_bag );
+    fs::remove_dir( &directory ).unwrap();
+}
+
+#[ test ]
+///   Performs random bags counted against bag::Bag.
+fn test_random()
+{
+    let mut rng = XorShiftRng::from_seed( [ 50, 2, 3, 4 ] );
+    let directory = scratch_directory( "bagspill", "random" );
+
+    for _ in 0..100
+    {
+        let budget = rng.gen_range( 1, 20 );
+        let mut test_bag = SpillBag::with_budget_in( budget, &directory );
+        let mut bag = Bag::new();
+
+        for _ in 0..rng.gen_range( 0, 400 )
+        {
+            let value = rng.gen_range( -20, 20 );
+
+            test_bag.insert( value ).unwrap();
+            bag.insert( value );
+        }
+
+        // A. The same counts, in order.
+        assert_eq!( test_bag.len(), bag.size() );
+        assert!( test_bag.in_memory() <= budget );
+        assert_eq!( counts_of( &mut test_bag ), bag.dedup_counts() );
+
+        // B. The same occurrences of each value.
+        for value in -21..21
+        {
+            assert_eq!( test_bag.occurrences( value ).unwrap(), bag.occurrences( value ) );
+        }
+
+        // C. The same elements, sorted.
+        let values: io::Result<Vec<i32>> = test_bag.iter().unwrap().collect();
+        assert_eq!( values.unwrap(), bag.sorted_iter().cloned().collect::<Vec<i32>>() );
+    }
+
+    assert_eq!( file_count( &directory ), 0 );
+    fs::remove_dir( &directory ).unwrap();
+}
+
+#[ test ]
+#[ should_panic( expected = "the budget of a spilling bag must be at least 1" ) ]
+///   Performs a test of a budget of zero.
+fn test_zero_budget()
+{
+    let _test_bag: SpillBag<i32> = SpillBag::with_budget( 0 );
+}
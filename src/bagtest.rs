@@ -2,7 +2,12 @@
 
 //! An interactive test program for the bag generic structure.
 //!
-//! Run with `cargo run`
+//! Run with `cargo run` or `cargo run -- interactive`.
+//!
+//! The same commands can be read from a script file with
+//! `cargo run -- run <script>`, one command per line. In a script
+//! the value for a command follows on the next line, blank lines are
+//! skipped and lines starting with `#` are comments.
 //!
 //! # The following commands are available with 2 bags
 //!
@@ -26,6 +31,16 @@
 //!
 //! * `x` Erase one of item from bag2
 //!
+//! * `U` Undo the newest operation on bag1
+//!
+//! * `u` Undo the newest operation on bag2
+//!
+//! * `Y` Redo the operation most recently undone on bag1
+//!
+//! * `y` Redo the operation most recently undone on bag2
+//!
+//! * `H` Display the history of operations on both bags
+//!
 //! * `O` Display both bags
 //!
 //! * `S` Print the result from the len( ) functions
@@ -33,28 +48,80 @@
 //! * `Q` Quit this test program
 
 use bag::Bag;
+use bagundo::UndoableBag;
 
 #[ allow( unused_imports ) ]
 use bag::len_trait::{Len, Capacity, CapacityMut};
-use std::io::Write;
-use std::ascii::AsciiExt;
+use std::io::{ BufRead, Write };
+
+/// The source of commands for a test session, either the
+/// terminal or a script file.
 
-macro_rules! read_input
+struct Session<'a, Input: 'a + BufRead>
 {
-    () =>
-    {{
-        ::std::io::stdout().flush().unwrap();
-        let mut input = String::new();
-        ::std::io::stdin().read_line( &mut input ).expect( "Error: could not read input" );
-        input
-    }};
+    input: &'a mut Input,
+    line: usize,
+    interactive: bool
 }
 
+impl<'a, Input: BufRead> Session<'a, Input>
+{
+    // Reads the next line of input, returning None at the end of input.
+    // Scripts skip blank lines and comments.
+    fn read_input( &mut self ) -> Result<Option<String>, String>
+    {
+        loop
+        {
+            ::std::io::stdout().flush().map_err( | error | error.to_string() )?;
+
+            let mut input = String::new();
+
+            if self.input.read_line( &mut input ).map_err( | error | error.to_string() )? == 0
+            {
+                return Ok( None );
+            }
+
+            self.line += 1;
+            let trimmed = input.trim();
+
+            if self.interactive || !( trimmed.is_empty() || trimmed.starts_with( '#' ) )
+            {
+                return Ok( Some( trimmed.to_string() ) );
+            }
+        }
+    }
+}
+
+/// Run the test program interactively on the terminal.
+
 pub fn bagtest()
 {
-    // bags that we'll perform tests on
-    let mut bag1: Bag<f32> = Bag::new();
-    let mut bag2: Bag<f32> = Bag::new();
+    let stdin = ::std::io::stdin();
+    let mut input = stdin.lock();
+
+    if let Err( message ) = run_session( &mut Session { input: &mut input, line: 0, interactive: true } )
+    {
+        println!( "Error: {}", message );
+    }
+}
+
+/// Run the test program with commands read from `input`.
+///
+/// The menu and prompts are not printed.
+///
+/// # Return
+/// An Err describing the line of the first value that could not be read.
+
+pub fn bagscript<Input: BufRead>( input: &mut Input ) -> Result<(), String>
+{
+    run_session( &mut Session { input, line: 0, interactive: false } )
+}
+
+fn run_session<Input: BufRead>( session: &mut Session<Input> ) -> Result<(), String>
+{
+    // bags that we'll perform tests on, whose operations can be undone
+    let mut bag1: UndoableBag<f32> = UndoableBag::new();
+    let mut bag2: UndoableBag<f32> = UndoableBag::new();
 
     let mut choice; // A command character entered by the user
 
@@ -63,30 +130,42 @@ pub fn bagtest()
 
     while
     {
-        print_menu();
-        choice = get_user_command();
+        if session.interactive
+        {
+            print_menu();
+        }
+
+        choice = get_user_command( session )?;
 
         match choice
         {
-            'A' => { bag1 = copybag( &bag2 ); }
-            'a' => { bag2 = copybag( &bag1 ); }
-            'C' => { bag1.clone_from( &bag2 ); }
-            'c' => { bag2.clone_from( &bag1 ); }
+            'A' => { bag1.assign( copybag( bag2.bag() ) ); }
+            'a' => { bag2.assign( copybag( bag1.bag() ) ); }
+            'C' => { bag1.clone_from( bag2.bag() ); }
+            'c' => { bag2.clone_from( bag1.bag() ); }
             'S' | 's' => { println!( "The bags' sizes are {} and {}",
-                                        bag1.len(), bag2.len() ); }
-            'I' => { bag1.insert( get_number() ); }
-            'i' => { bag2.insert( get_number() ); }
-            'R' => { bag1.erase( get_number() ); }
-            'r' => { bag2.erase( get_number() ); }
-            'X' => { bag1.erase_one( get_number() ); }
-            'x' => { bag2.erase_one( get_number() ); }
-            'O' | 'o' => { show_bags( &bag1, &bag2 ); }
+                                        bag1.bag().len(), bag2.bag().len() ); }
+            'I' => { bag1.insert( get_number( session )? ); }
+            'i' => { bag2.insert( get_number( session )? ); }
+            'R' => { bag1.erase( get_number( session )? ); }
+            'r' => { bag2.erase( get_number( session )? ); }
+            'X' => { bag1.erase_one( get_number( session )? ); }
+            'x' => { bag2.erase_one( get_number( session )? ); }
+            'U' => { undo( &mut bag1, "bag1" ); }
+            'u' => { undo( &mut bag2, "bag2" ); }
+            'Y' => { redo( &mut bag1, "bag1" ); }
+            'y' => { redo( &mut bag2, "bag2" ); }
+            'H' | 'h' => { show_history( &bag1, "bag1" );
+                           show_history( &bag2, "bag2" ); }
+            'O' | 'o' => { show_bags( bag1.bag(), bag2.bag() ); }
             'q' | 'Q' => { println!( "Ridicule is the best test of truth." ); }
             val => { println!( "{} is invalid. Sorry.", val ); }
         }
 
         !choice.eq_ignore_ascii_case( &'Q' ) // post check
     }{}
+
+    Ok( () )
 }
 
 // A menu of choices for this program has been written to cout.
@@ -103,6 +182,11 @@ fn print_menu()
     println!( "\t\t r  Erase all of item from bag2" );
     println!( "\t\t X  Erase one of item from bag1" );
     println!( "\t\t x  Erase one of item from bag2" );
+    println!( "\t\t U  Undo the newest operation on bag1" );
+    println!( "\t\t u  Undo the newest operation on bag2" );
+    println!( "\t\t Y  Redo the operation most recently undone on bag1" );
+    println!( "\t\t y  Redo the operation most recently undone on bag2" );
+    println!( "\t\t H  Display the history of operations on both bags" );
     println!( "\t\t O  Display both bags" );
     println!( "\t\t S  Print the result from the len( ) functions" );
     println!( "\t\t Q  Quit this test program" );
@@ -111,11 +195,25 @@ fn print_menu()
 // The user has been prompted to enter a one character command.
 // A line of input (with at least one character) has been read,
 // and the first character of the input line is returned.
-fn get_user_command() -> char
+// The end of the input is treated as the quit command.
+fn get_user_command<Input: BufRead>( session: &mut Session<Input> ) -> Result<char, String>
 {
-    print!( "\t\tEnter choice: " );
+    loop
+    {
+        if session.interactive
+        {
+            print!( "\t\tEnter choice: " );
+        }
 
-    read_input!().chars().next().expect( "Error: could not parse character" )
+        match session.read_input()?
+        {
+            None => return Ok( 'Q' ),
+            Some( line ) => if let Some( choice ) = line.chars().next()
+                            {
+                                return Ok( choice );
+                            }
+        }
+    }
 }
 
 // The function has tested whether the numbers 0..9 are in
@@ -126,6 +224,43 @@ fn show_bags( bag1: &Bag<f32>, bag2: &Bag<f32> )
                 bag1, bag2 );
 }
 
+// The newest operation on the bag has been undone, or a message
+// printed that there was none.
+fn undo( bag: &mut UndoableBag<f32>, name: &str )
+{
+    if !bag.undo()
+    {
+        println!( "\t\tThere is nothing to undo in {}.", name );
+    }
+}
+
+// The operation most recently undone on the bag has been redone, or
+// a message printed that there was none.
+fn redo( bag: &mut UndoableBag<f32>, name: &str )
+{
+    if !bag.redo()
+    {
+        println!( "\t\tThere is nothing to redo in {}.", name );
+    }
+}
+
+// The operations that can be undone and redone on the bag have been
+// printed, the oldest first.
+fn show_history( bag: &UndoableBag<f32>, name: &str )
+{
+    println!( "\t\t{} history:", name );
+
+    for operation in bag.history()
+    {
+        println!( "\t\t\t{}", operation );
+    }
+
+    for operation in bag.redo_history()
+    {
+        println!( "\t\t\t{} (undone)", operation );
+    }
+}
+
 // The return value is a copy of b.
 fn copybag( input_bag: &Bag<f32> ) -> Bag<f32>
 {
@@ -134,10 +269,17 @@ fn copybag( input_bag: &Bag<f32> ) -> Bag<f32>
 
 // The user has been prompted to enter bag::value_type  number. The number
 // has been read, echoed to the screen, and returned by the function.
-fn get_number() -> f32
+fn get_number<Input: BufRead>( session: &mut Session<Input> ) -> Result<f32, String>
 {
-    print!( "\t\tPlease enter a bag::value_type number for the bag: ");
-    let result: f32 = read_input!().trim_right_matches( '\n' ).parse::<f32>().expect( "Error: could not parse f32" );
+    if session.interactive
+    {
+        print!( "\t\tPlease enter a bag::value_type number for the bag: ");
+    }
+
+    let input = session.read_input()?.ok_or( "expected a number but the input ended" )?;
+    let result: f32 = input.parse::<f32>()
+                           .map_err( | _ | format!( "line {}: could not parse f32 from `{}`",
+                                                    session.line, input ) )?;
     println!( "\t\t{} has been read.", result );
-    result
+    Ok( result )
 }
@@ -0,0 +1,276 @@
+// FILE: bagtotal.rs
+
+//! Keys for floating point numbers that are totally ordered, so that
+//! bags of them can be hashed, sorted and counted in maps.
+//!
+//! `TotalF64` and `TotalF32` are ordered by the IEEE 754 `totalOrder`
+//! predicate, as `f64::total_cmp` orders numbers:
+//!
+//! ```text
+//! -NaN < -inf < ... < -0.0 < 0.0 < ... < inf < NaN
+//! ```
+//!
+//! and two keys are equal only when their bits are, so the keys have
+//! `Eq`, `Ord` and `Hash`. A `bag::Bag` of keys has the `Hash`,
+//! `counts()`, `into_counts()` and `into_sorted_counts()` of any
+//! bag of such a type.
+//!
+//! A number is canonicalized by a `KeyPolicy` as it is made into a key.
+//! The default policy makes `-0.0` and `0.0` the same key, as `==`
+//! does, and every NaN the same key, sorted last.
+//! `KeyPolicy::TOTAL_ORDER` keeps every number as it is.
+//!
+//! A `BagDelta` of bagdelta needs values with `Hash` and `Eq`, so the
+//! delta between two bags of numbers is the delta between their keys,
+//! which `apply_keys( _, _ )` applies to a bag of the numbers.
+
+use bag::Bag;
+use bagdelta::{ ApplyDeltaError, BagDelta };
+use std::cmp::Ordering;
+use std::fmt::{ self, Display, Formatter };
+use std::hash::{ Hash, Hasher };
+use std::num::ParseFloatError;
+use std::str::FromStr;
+
+/// What becomes of a NaN made into a key.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum NanPolicy
+{
+    /// Every NaN is the same key, the positive quiet NaN, sorted last.
+    Canonical,
+    /// Each NaN keeps its sign and payload, with negative NaNs sorted
+    /// first and positive NaNs last.
+    Keep
+}
+
+/// What becomes of `-0.0` made into a key.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub enum ZeroPolicy
+{
+    /// `-0.0` is the same key as `0.0`.
+    Canonical,
+    /// `-0.0` is a key of its own, sorted just before `0.0`.
+    Keep
+}
+
+/// How numbers are canonicalized as they are made into keys.
+
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+pub struct KeyPolicy
+{
+    pub nan: NanPolicy,
+    pub zero: ZeroPolicy
+}
+
+impl KeyPolicy
+{
+    /// Keep every number as it is, so the keys are ordered and
+    /// compared exactly as by `totalOrder`.
+    pub const TOTAL_ORDER: KeyPolicy = KeyPolicy { nan: NanPolicy::Keep, zero: ZeroPolicy::Keep };
+}
+
+impl Default for KeyPolicy
+{
+    /// One key for both zeros and one key for every NaN.
+
+    fn default() -> Self
+    {
+        KeyPolicy { nan: NanPolicy::Canonical, zero: ZeroPolicy::Canonical }
+    }
+}
+
+// Defines a key type for each floating point type, with the bits of its
+// canonical NaN, and the conversion of a bag of the numbers into keys.
+macro_rules! total_floats
+{
+    ( $( $key:ident => $float:ident, $nan_bits:expr );* ) =>
+    {
+        $(
+            /// A totally ordered key for a floating point number.
+            ///
+            /// # Invariant of the key struct:
+            /// 1. The number has been canonicalized by the policy the key was made with.
+
+            #[ derive( Debug, Clone, Copy, Default ) ]
+            pub struct $key( $float );
+
+            impl $key
+            {
+                /// Make a key of `value` with the default policy.
+
+                pub fn new( value: $float ) -> Self
+                {
+                    $key::with_policy( value, KeyPolicy::default() )
+                }
+
+                /// Make a key of `value`, canonicalized by `policy`.
+
+                pub fn with_policy( value: $float, policy: KeyPolicy ) -> Self
+                {
+                    if value.is_nan() && policy.nan == NanPolicy::Canonical
+                    {
+                        $key( $float::from_bits( $nan_bits ) )
+                    }
+                    else if value == 0.0 && policy.zero == ZeroPolicy::Canonical
+                    {
+                        $key( 0.0 )
+                    }
+                    else
+                    {
+                        $key( value )
+                    }
+                }
+
+                /// The number of the key, as canonicalized.
+
+                pub fn value( self ) -> $float
+                {
+                    self.0
+                }
+            }
+
+            impl From<$float> for $key
+            {
+                /// Make a key with the default policy.
+
+                fn from( value: $float ) -> Self
+                {
+                    $key::new( value )
+                }
+            }
+
+            impl From<$key> for $float
+            {
+                /// The number of the key, as canonicalized.
+
+                fn from( key: $key ) -> Self
+                {
+                    key.0
+                }
+            }
+
+            impl PartialEq for $key
+            {
+                /// Whether the keys have the same bits.
+
+                fn eq( &self, other: &$key ) -> bool
+                {
+                    self.0.to_bits() == other.0.to_bits()
+                }
+            }
+
+            impl Eq for $key {}
+
+            impl PartialOrd for $key
+            {
+                fn partial_cmp( &self, other: &$key ) -> Option<Ordering>
+                {
+                    Some( self.cmp( other ) )
+                }
+            }
+
+            impl Ord for $key
+            {
+                /// Compare the keys by the IEEE 754 `totalOrder` predicate.
+
+                fn cmp( &self, other: &$key ) -> Ordering
+                {
+                    self.0.total_cmp( &other.0 )
+                }
+            }
+
+            impl Hash for $key
+            {
+                /// Hash the bits of the key, so equal keys hash alike.
+
+                fn hash<HashType: Hasher>( &self, state: &mut HashType )
+                {
+                    self.0.to_bits().hash( state );
+                }
+            }
+
+            impl Display for $key
+            {
+                /// Renders the number of the key.
+
+                fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+                {
+                    Display::fmt( &self.0, fmt )
+                }
+            }
+
+            impl FromStr for $key
+            {
+                type Err = ParseFloatError;
+
+                /// Reads a number and makes it a key with the default policy.
+
+                fn from_str( text: &str ) -> Result<Self, ParseFloatError>
+                {
+                    text.parse::<$float>().map( $key::new )
+                }
+            }
+
+            impl Bag<$float>
+            {
+                /// Make a bag of the keys of the numbers in this bag.
+                ///
+                /// # Parameter: `policy`
+                /// How the numbers are canonicalized as they are made into keys.
+                ///
+                /// # Postcondition
+                /// This bag is not altered.
+                ///
+                /// # Return
+                /// A `bag::Bag` holding the key of each number in the same order,
+                /// which can be hashed, sorted and counted in maps.
+                ///
+                /// # Aborts
+                /// OOM: Insufficient memory for allocating the new bag.
+
+                pub fn to_keys( &self, policy: KeyPolicy ) -> Bag<$key>
+                {
+                    let mut keys = Bag::new();
+
+                    for &value in self
+                    {
+                        keys.insert( $key::with_policy( value, policy ) );
+                    }
+
+                    keys
+                }
+
+                /// Apply a delta between bags of keys to this bag of numbers,
+                /// as `apply( _ )` applies one to a bag of keys.
+                ///
+                /// # Parameter: `delta`
+                /// A delta, such as one made by `diff( _, _ )` of bagdelta
+                /// between the keys of two bags.
+                ///
+                /// # Parameter: `policy`
+                /// How the numbers are canonicalized as they are made into keys,
+                /// which should be the policy the delta's keys were made with.
+                ///
+                /// # Postcondition
+                /// If the bag holds enough numbers of each key removed, the
+                /// count of each key has changed by its change, with the numbers
+                /// inserted being those of the keys. Otherwise the bag is not altered.
+                ///
+                /// # Return
+                /// An Err naming a key of which the delta removes more numbers
+                /// than the bag holds.
+
+                pub fn apply_keys( &mut self, delta: &BagDelta<$key>, policy: KeyPolicy )
+                    -> Result<(), ApplyDeltaError<$key>>
+                {
+                    delta.apply_by( self, | &value | $key::with_policy( value, policy ) )
+                }
+            }
+        )*
+    };
+}
+
+total_floats!( TotalF64 => f64, 0x7ff8_0000_0000_0000;
+               TotalF32 => f32, 0x7fc0_0000 );
@@ -0,0 +1,159 @@
+// FILE: bagtotalexam.rs
+
+//! This module holds tests for the totally ordered float keys of
+//! bagtotal and the hashing and sorting of the numeric bags
+//!
+//! 1) tests of the order and equality of keys under each policy
+//!
+//! 2) tests of counting and hashing bags of keys
+//!
+//! 3) tests of the sorted iteration and hashing of bags of numbers
+//!
+//! 4) tests of the text format of bags of keys
+//!
+//! Run with `cargo test`
+
+use bag::Bag;
+use bagf64::NumericCounts;
+use bagtotal::{ TotalF64, TotalF32, KeyPolicy, NanPolicy, ZeroPolicy };
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+
+// The hash of a value with the default hasher.
+fn hash_of<Value: Hash>( value: &Value ) -> u64
+{
+    let mut hasher = DefaultHasher::new();
+
+    value.hash( &mut hasher );
+    hasher.finish()
+}
+
+#[ test ]
+///   Performs tests of the order and equality of keys under each policy.
+fn test1()
+{
+    let negative_nan = -f64::NAN;
+
+    // A. The default policy: one zero, one NaN sorted last.
+    assert_eq!( TotalF64::new( -0.0 ), TotalF64::new( 0.0 ) );
+    assert_eq!( TotalF64::new( f64::NAN ), TotalF64::new( negative_nan ) );
+    assert_eq!( hash_of( &TotalF64::new( -0.0 ) ), hash_of( &TotalF64::new( 0.0 ) ) );
+    assert!( TotalF64::new( negative_nan ) > TotalF64::new( f64::INFINITY ) );
+    assert!( TotalF64::new( -0.0 ).value().is_sign_positive() );
+
+    // B. The total order: the zeros and NaNs by their sign.
+    let total = | value | TotalF64::with_policy( value, KeyPolicy::TOTAL_ORDER );
+    let mut keys = [ total( f64::NAN ), total( 0.0 ), total( f64::INFINITY ), total( -0.0 ),
+                     total( negative_nan ), total( -1.5 ), total( f64::NEG_INFINITY ) ];
+    keys.sort();
+
+    let sorted: Vec<u64> = keys.iter().map( | key | key.value().to_bits() ).collect();
+    let expected: Vec<u64> = [ negative_nan, f64::NEG_INFINITY, -1.5, -0.0, 0.0, f64::INFINITY, f64::NAN ]
+                                 .iter().map( | value | value.to_bits() ).collect();
+    assert_eq!( sorted, expected );
+    assert!( total( -0.0 ) != total( 0.0 ) );
+
+    // C. The policies each on their own.
+    let zeros_kept = KeyPolicy { nan: NanPolicy::Canonical, zero: ZeroPolicy::Keep };
+    assert!( TotalF64::with_policy( -0.0, zeros_kept ) < TotalF64::with_policy( 0.0, zeros_kept ) );
+    assert_eq!( TotalF64::with_policy( negative_nan, zeros_kept ), TotalF64::new( f64::NAN ) );
+
+    let nans_kept = KeyPolicy { nan: NanPolicy::Keep, zero: ZeroPolicy::Canonical };
+    assert_eq!( TotalF64::with_policy( -0.0, nans_kept ), TotalF64::new( 0.0 ) );
+    assert!( TotalF64::with_policy( negative_nan, nans_kept ) < TotalF64::new( f64::NEG_INFINITY ) );
+
+    // D. Keys of f32s.
+    assert_eq!( TotalF32::new( -f32::NAN ), TotalF32::from( f32::NAN ) );
+    assert!( TotalF32::new( 1.5 ) < TotalF32::new( 2.5 ) );
+    assert_eq!( f32::from( TotalF32::new( 1.5 ) ), 1.5 );
+}
+
+#[ test ]
+///   Performs tests of counting and hashing bags of keys.
+fn test2()
+{
+    let mut numbers = ::bagf64::Bag::new();
+
+    for &value in &[ 2.5, f64::NAN, -0.0, 0.0, 2.5, -f64::NAN, -1.0 ]
+    {
+        numbers.insert( value );
+    }
+
+    // A. With the default policy the zeros and the NaNs are each counted together.
+    let keys = numbers.to_keys( KeyPolicy::default() );
+    let sorted: Vec<( f64, usize )> = keys.clone().into_sorted_counts().into_iter()
+                                          .map( | ( key, count ) | ( key.value(), count ) )
+                                          .collect();
+
+    assert_eq!( &sorted[ ..3 ], &[ ( -1.0, 1 ), ( 0.0, 2 ), ( 2.5, 2 ) ] );
+    assert!( sorted[ 3 ].0.is_nan() && sorted[ 3 ].1 == 2 );
+    assert_eq!( keys.distinct_len(), 4 );
+    assert_eq!( keys.into_counts()[ &TotalF64::new( f64::NAN ) ], 2 );
+
+    // B. With the total order every zero and NaN is its own value.
+    let keys = numbers.to_keys( KeyPolicy::TOTAL_ORDER );
+    assert_eq!( keys.distinct_len(), 6 );
+    assert_eq!( keys.most_common( 1 ), vec![ ( &TotalF64::new( 2.5 ), 2 ) ] );
+
+    // C. Bags of keys can be put in a set.
+    let mut set: HashSet<Bag<TotalF64>> = HashSet::new();
+    set.insert( numbers.to_keys( KeyPolicy::default() ) );
+    set.insert( numbers.to_keys( KeyPolicy::default() ) );
+    set.insert( numbers.to_keys( KeyPolicy::TOTAL_ORDER ) );
+    assert_eq!( set.len(), 2 );
+}
+
+#[ test ]
+///   Performs tests of the sorted iteration and hashing of bags of numbers.
+fn test3()
+{
+    let mut numbers = ::bagf64::Bag::new();
+
+    for &value in &[ 0.0, 3.5, -f64::NAN, -0.0, f64::NAN, -2.0, 3.5 ]
+    {
+        numbers.insert( value );
+    }
+
+    // A. Sorted in total order, NaNs at the ends by their sign.
+    let sorted: Vec<u64> = numbers.sorted_iter().map( | value | value.to_bits() ).collect();
+    let expected: Vec<u64> = [ -f64::NAN, -2.0, -0.0, 0.0, 3.5, 3.5, f64::NAN ]
+                                 .iter().map( | value | value.to_bits() ).collect();
+    assert_eq!( sorted, expected );
+
+    // B. Integers are sorted as usual.
+    let mut integers: Bag<i64> = Bag::new();
+    integers.insert( 3 );
+    integers.insert( -7 );
+    integers.insert( 3 );
+    assert_eq!( NumericCounts::sorted_iter( &integers ).collect::<Vec<&i64>>(), vec![ &-7, &3, &3 ] );
+    assert_eq!( NumericCounts::sorted_iter( &integers ).collect::<Vec<&i64>>(), integers.sorted_iter().collect::<Vec<&i64>>() );
+
+    // C. Equal bags hash alike by their keys, even with -0.0 and 0.0.
+    let mut left = ::bagf64::Bag::new();
+    let mut right = ::bagf64::Bag::new();
+    left.insert( -0.0 );
+    left.insert( 1.5 );
+    right.insert( 0.0 );
+    right.insert( 1.5 );
+
+    assert_eq!( left, right );
+    assert_eq!( hash_of( &left.to_keys( KeyPolicy::default() ) ), hash_of( &right.to_keys( KeyPolicy::default() ) ) );
+
+    right.insert( 1.5 );
+    assert!( hash_of( &left.to_keys( KeyPolicy::default() ) ) != hash_of( &right.to_keys( KeyPolicy::default() ) ) );
+}
+
+#[ test ]
+///   Performs tests of the text format of bags of keys.
+fn test4()
+{
+    let keys: Bag<TotalF64> = "{-0, 1.5×2, NaN, inf}".parse().unwrap();
+
+    assert_eq!( keys.occurrences( TotalF64::new( 0.0 ) ), 1 );
+    assert_eq!( keys.occurrences( TotalF64::new( -f64::NAN ) ), 1 );
+    assert_eq!( keys.to_string(), "{0, 1.5×2, NaN, inf}" );
+    assert_eq!( keys.to_string().parse::<Bag<TotalF64>>(), Ok( keys ) );
+
+    assert!( "{1.5, x}".parse::<Bag<TotalF64>>().is_err() );
+}
@@ -0,0 +1,120 @@
+// FILE: bagtrait.rs
+
+//! A trait for the operations shared by every bag implementation,
+//! so code such as the conformance tests can be written once for
+//! all of them.
+//!
+//! The methods have the same names and meanings as the methods of
+//! `bag::Bag`, which each bag implements in its own module.
+
+use bag;
+use bagVec;
+use bagdelta::{ BagDelta, ApplyDeltaError };
+
+pub use bag::len_trait::len::{ Len, Empty, Clear };
+pub use bag::len_trait::capacity::{ Capacity, WithCapacity, CapacityMut };
+pub use std::ops::AddAssign;
+use std::iter::FusedIterator;
+use std::hash::Hash;
+
+/// The operations of a bag of `Item`s.
+///
+/// Along with the methods below every bag can be created empty
+/// (`Default` and `WithCapacity`), copied (`Clone`), compared
+/// (`PartialEq`), measured (`Len` and `Capacity`), resized
+/// (`CapacityMut`), emptied (`Clear`) and added to (`AddAssign`).
+
+pub trait BagOps: Clone + PartialEq + AddAssign + Clear + CapacityMut
+{
+    /// The type of the elements in the bag.
+    type Item: Clone + PartialEq;
+
+    /// The iterator over a bag's elements, from either end.
+    type Iter<'a>: DoubleEndedIterator<Item = &'a Self::Item> + ExactSizeIterator + FusedIterator where Self: 'a;
+
+    /// Add a new element to this bag.
+    fn insert( &mut self, new_item: Self::Item );
+
+    /// Erase all copies of `target`, returning the number erased.
+    fn erase( &mut self, target: Self::Item ) -> usize;
+
+    /// Remove one copy of `target`, returning whether one was found.
+    fn erase_one( &mut self, target: Self::Item ) -> bool;
+
+    /// Count the number of occurrences of `target`.
+    fn occurrences( &self, target: Self::Item ) -> usize;
+
+    /// Iterate over the elements of the bag in their stored order.
+    fn iter( &self ) -> Self::Iter<'_>;
+
+    /// Change the count of each value by its change in `delta`, or
+    /// return an Err leaving the bag unaltered if the delta removes
+    /// more copies of a value than the bag holds.
+    fn apply( &mut self, delta: &BagDelta<Self::Item> ) -> Result<(), ApplyDeltaError<Self::Item>>
+        where Self: Sized, Self::Item: Hash + Eq
+    {
+        delta.apply_to( self )
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> BagOps for bag::Bag<Type>
+{
+    type Item = Type;
+    type Iter<'a> = bag::BagIterator<'a, Type> where Self: 'a;
+
+    fn insert( &mut self, new_item: Type )
+    {
+        bag::Bag::insert( self, new_item )
+    }
+
+    fn erase( &mut self, target: Type ) -> usize
+    {
+        bag::Bag::erase( self, target )
+    }
+
+    fn erase_one( &mut self, target: Type ) -> bool
+    {
+        bag::Bag::erase_one( self, target )
+    }
+
+    fn occurrences( &self, target: Type ) -> usize
+    {
+        bag::Bag::occurrences( self, target )
+    }
+
+    fn iter( &self ) -> Self::Iter<'_>
+    {
+        self.into_iter()
+    }
+}
+
+impl<Type: PartialEq + Clone> BagOps for bagVec::Bag<Type>
+{
+    type Item = Type;
+    type Iter<'a> = bagVec::BagIterator<'a, Type> where Self: 'a;
+
+    fn insert( &mut self, new_item: Type )
+    {
+        bagVec::Bag::insert( self, new_item )
+    }
+
+    fn erase( &mut self, target: Type ) -> usize
+    {
+        bagVec::Bag::erase( self, target )
+    }
+
+    fn erase_one( &mut self, target: Type ) -> bool
+    {
+        bagVec::Bag::erase_one( self, target )
+    }
+
+    fn occurrences( &self, target: Type ) -> usize
+    {
+        bagVec::Bag::occurrences( self, target )
+    }
+
+    fn iter( &self ) -> Self::Iter<'_>
+    {
+        self.into_iter()
+    }
+}
@@ -0,0 +1,323 @@
+// FILE: bagtransaction.rs
+
+//! Transactions on a `bag::Bag`, whose changes can be kept together
+//! or undone together.
+//!
+//! `begin()` borrows the bag and returns a `Transaction`, through
+//! which the bag is changed with `insert( _ )`, `erase_one( _ )`,
+//! `erase( _ )`, `clear()`, `+=`, `assign( _ )` and `clone_from( _ )`.
+//! Each change is recorded in an undo log holding only what is needed
+//! to reverse it: the length and capacity before an insert or `+=`,
+//! the erased elements and where they were, or the bag replaced by a
+//! clear, an assign or a `clone_from( _ )`, which is moved, not copied.
+//!
+//! `commit()` keeps the changes and `rollback()` undoes them, leaving
+//! the bag equal to, and in the same order as, before `begin()`,
+//! with the same capacity. A transaction dropped without either is
+//! rolled back, unless its `DropAction` was set to commit.
+
+use bag::Bag;
+use bag::len_trait::Len;
+use std::fmt::{ self, Debug, Formatter };
+use std::mem;
+use std::ops::AddAssign;
+
+/// What a transaction dropped without `commit()` or `rollback()` does.
+
+#[ derive( Debug, Clone, Copy, PartialEq, Default ) ]
+pub enum DropAction
+{
+    /// Undo the changes, as `rollback()` does.
+    #[ default ]
+    Rollback,
+    /// Keep the changes, as `commit()` does.
+    Commit
+}
+
+// One change made through a transaction, with what is needed to undo it.
+pub( crate ) enum Undo<Type: PartialEq + Clone + Default>
+{
+    // An insert or +=, with the length, capacity and sorted flag of the bag before it.
+    Append { used: usize, capacity: usize, sorted: bool },
+    // An erase_one( _ ) of `value` from `index`.
+    EraseOne { index: usize, value: Type },
+    // An erase( _ ), with each element erased and its index, and the
+    // last elements of an unsorted bag, which were moved into their places.
+    Erase { erased: Vec<( usize, Type )>, tail: Vec<Type> },
+    // A clear( ), assign( _ ) or clone_from( _ ), with the bag as it was before.
+    Clear( Bag<Type> )
+}
+
+/// Changes to a bag that are kept or undone together.
+///
+/// # Invariant of the Transaction struct:
+/// 1. Undoing the entries of `log` from the last to the first returns
+/// the bag to its state when the transaction began.
+
+pub struct Transaction<'a, Type: 'a + PartialEq + Clone + Default>
+{
+    bag: &'a mut Bag<Type>,
+    log: Vec<Undo<Type>>,
+    on_drop: DropAction
+}
+
+impl<Type: PartialEq + Clone + Default> Bag<Type>
+{
+    /// Begin a transaction on this bag.
+    ///
+    /// # Return
+    /// A transaction that borrows the bag until it is committed,
+    /// rolled back or dropped, and rolls back when dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = Bag::new();
+    /// bag.insert( 1 );
+    ///
+    /// {
+    ///     let mut transaction = bag.begin();
+    ///     transaction.insert( 2 );
+    ///     transaction.erase_one( 1 );
+    /// }
+    ///
+    /// assert_eq!( bag.occurrences( 1 ), 1 );
+    /// assert_eq!( bag.occurrences( 2 ), 0 );
+    /// ```
+
+    pub fn begin<'a>( &'a mut self ) -> Transaction<'a, Type>
+    {
+        Transaction { bag: self, log: Vec::new(), on_drop: DropAction::default() }
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> Transaction<'a, Type>
+{
+    /// The bag as changed so far by the transaction.
+
+    pub fn bag( &self ) -> &Bag<Type>
+    {
+        self.bag
+    }
+
+    /// The number of changes made so far by the transaction.
+
+    pub fn changes( &self ) -> usize
+    {
+        self.log.len()
+    }
+
+    /// What the transaction does if dropped without `commit()` or `rollback()`.
+
+    pub fn on_drop( &self ) -> DropAction
+    {
+        self.on_drop
+    }
+
+    /// Change what the transaction does if dropped without
+    /// `commit()` or `rollback()`.
+
+    pub fn set_on_drop( &mut self, action: DropAction )
+    {
+        self.on_drop = action;
+    }
+
+    /// Add a new element to the bag, as `Bag::insert( _ )` does.
+    ///
+    /// # Parameter: `new_item`
+    /// The new element that is being inserted.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for growing the bag or the undo log.
+
+    pub fn insert( &mut self, new_item: Type )
+    {
+        self.log_append();
+        self.bag.insert( new_item );
+    }
+
+    /// Remove one copy of a specified element from the bag,
+    /// as `Bag::erase_one( _ )` does.
+    ///
+    /// # Parameter: `target`
+    /// The element to remove from the bag.
+    ///
+    /// # Return
+    /// True or false depending on whether `target` exists in the bag.
+
+    pub fn erase_one( &mut self, target: Type ) -> bool
+    {
+        match self.bag.find( &target )
+        {
+            None                    => false,
+            Some( ( index, value ) ) =>
+                {
+                    self.log.push( Undo::EraseOne { index, value } );
+                    self.bag.erase_one( target )
+                }
+        }
+    }
+
+    /// Erase all copies of a specified element from the bag,
+    /// as `Bag::erase( _ )` does.
+    ///
+    /// # Parameter: `target`
+    /// The element(s) to remove from the bag.
+    ///
+    /// # Return
+    /// An unsigned integer value representing the number of items erased from bag.
+    ///
+    /// # Note
+    /// The erase is one change in the undo log, holding the copies
+    /// erased and where they were, found in one pass over the bag.
+
+    pub fn erase( &mut self, target: Type ) -> usize
+    {
+        let ( erased, tail ) = self.bag.find_all( &target );
+
+        if erased.is_empty()
+        {
+            return 0;
+        }
+
+        self.log.push( Undo::Erase { erased, tail } );
+        self.bag.erase( target )
+    }
+
+    /// Remove all elements from the bag, as `Bag::clear()` does.
+    ///
+    /// # Postcondition
+    /// The bag is empty with the same capacity.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Note
+    /// The elements are moved into the undo log, and the bag is given
+    /// a new array of the same capacity.
+
+    pub fn clear( &mut self )
+    {
+        let capacity = self.bag.get_capacity();
+        let old = mem::replace( self.bag, Bag::with_capacity( capacity ) );
+
+        self.log.push( Undo::Clear( old ) );
+    }
+
+    /// Replace the bag by `other`, as assigning `other` to it does.
+    ///
+    /// # Postcondition
+    /// The bag is `other`, with its elements, order and capacity.
+    ///
+    /// # Note
+    /// The old bag is moved into the undo log.
+
+    pub fn assign( &mut self, other: Bag<Type> )
+    {
+        let old = mem::replace( self.bag, other );
+
+        self.log.push( Undo::Clear( old ) );
+    }
+
+    /// Make the bag a copy of `source`, as `Bag::clone_from( _ )` does.
+    ///
+    /// # Postcondition
+    /// The bag holds the elements of `source` in the same order, with
+    /// the capacity `Bag::clone_from( _ )` gives it.
+    ///
+    /// # Aborts
+    /// OOM: Insufficient memory for allocating a new array.
+    ///
+    /// # Note
+    /// The old bag is moved into the undo log, so only `source` is copied.
+
+    pub fn clone_from( &mut self, source: &Bag<Type> )
+    {
+        let old = mem::replace( self.bag, source.clone() );
+
+        self.log.push( Undo::Clear( old ) );
+    }
+
+    /// Keep the changes made by the transaction.
+
+    pub fn commit( mut self )
+    {
+        self.log.clear();
+    }
+
+    /// Undo the changes made by the transaction.
+    ///
+    /// # Postcondition
+    /// The bag is equal to, in the same order as and with the same
+    /// capacity as it was when the transaction began.
+
+    pub fn rollback( mut self )
+    {
+        undo_log( self.bag, mem::take( &mut self.log ) );
+    }
+
+    // Keeps the changes, handing over the log that undoes them.
+    pub( crate ) fn into_log( mut self ) -> Vec<Undo<Type>>
+    {
+        mem::take( &mut self.log )
+    }
+
+    // Records the state of the bag before elements are appended.
+    fn log_append( &mut self )
+    {
+        self.log.push( Undo::Append { used: self.bag.len(),
+                                      capacity: self.bag.get_capacity(),
+                                      sorted: self.bag.is_sorted() } );
+    }
+}
+
+// Undoes the changes of `log` to `bag` from the newest to the oldest.
+pub( crate ) fn undo_log<Type: PartialEq + Clone + Default>( bag: &mut Bag<Type>, mut log: Vec<Undo<Type>> )
+{
+    while let Some( undo ) = log.pop()
+    {
+        match undo
+        {
+            Undo::Append { used, capacity, sorted } => bag.restore_len( used, capacity, sorted ),
+            Undo::EraseOne { index, value }         => bag.unerase( index, value ),
+            Undo::Erase { erased, tail }            => bag.unerase_all( erased, tail ),
+            Undo::Clear( old )                      => *bag = old
+        }
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> AddAssign<Bag<Type>> for Transaction<'a, Type>
+{
+    /// Add the elements of `other` to the bag, as `+=` on a bag does.
+
+    fn add_assign( &mut self, other: Bag<Type> )
+    {
+        self.log_append();
+        *self.bag += other;
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> Drop for Transaction<'a, Type>
+{
+    /// Rolls back the changes not yet committed, unless the
+    /// `DropAction` is `Commit`.
+
+    fn drop( &mut self )
+    {
+        if self.on_drop == DropAction::Rollback
+        {
+            undo_log( self.bag, mem::take( &mut self.log ) );
+        }
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default + Debug> Debug for Transaction<'a, Type>
+{
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        fmt.debug_struct( "Transaction" )
+           .field( "bag", &self.bag )
+           .field( "changes", &self.log.len() )
+           .field( "on_drop", &self.on_drop )
+           .finish()
+    }
+}
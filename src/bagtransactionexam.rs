@@ -0,0 +1,228 @@
+// FILE: bagtransactionexam.rs
+
+//! This module holds tests for the transactions of bagtransaction
+//!
+//! 1) tests of rolling back inserts, erases, clears and +=
+//!
+//! 2) tests of rolling back changes to a sorted bag
+//!
+//! 3) tests of commit( ) and of dropping a transaction
+//!
+//! 4) random transactions checked against a copy of the bag
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::Bag;
+use bag::len_trait::len::{ Len, Empty, Clear };
+use bag::len_trait::capacity::Capacity;
+use bagtransaction::DropAction;
+
+// The elements of the bag in their order.
+fn elements<Type: PartialEq + Clone + Default>( bag: &Bag<Type> ) -> Vec<Type>
+{
+    bag.into_iter().cloned().collect()
+}
+
+#[ test ]
+///   Performs tests of rolling back inserts, erases, clears and +=.
+fn test1()
+{
+    let mut test_bag = [ 4, 8, 15, 16, 23, 42, 8 ].iter().cloned().collect();
+    let before = elements( &test_bag );
+    let capacity = test_bag.capacity();
+
+    // A. The changes are seen through the transaction.
+    let mut transaction = test_bag.begin();
+    assert!( transaction.erase_one( 4 ) );
+    assert!( !transaction.erase_one( 99 ) );
+    assert_eq!( transaction.erase( 8 ), 2 );
+    transaction.insert( 7 );
+    transaction.insert( 7 );
+    transaction.insert( 7 );
+
+    assert_eq!( transaction.bag().len(), 7 );
+    assert_eq!( transaction.bag().occurrences( 7 ), 3 );
+    assert_eq!( transaction.changes(), 5 );
+
+    // B. Rolling back gives the same elements in the same order and capacity.
+    transaction.rollback();
+    assert_eq!( elements( &test_bag ), before );
+    assert_eq!( test_bag.capacity(), capacity );
+
+    // C. A transaction on an empty bag.
+    let mut test_bag: Bag<i32> = Bag::new();
+    {
+        let mut transaction = test_bag.begin();
+        transaction.insert( 1 );
+        transaction.insert( 2 );
+        transaction.erase_one( 1 );
+        transaction.rollback();
+    }
+    assert!( test_bag.is_empty() );
+    assert_eq!( test_bag.capacity(), 1 );
+
+    // D. Rolling back a clear and a +=.
+    let mut test_bag: Bag<i32> = [ 3, 1, 2 ].iter().cloned().collect();
+    {
+        let mut transaction = test_bag.begin();
+        transaction.clear();
+        assert!( transaction.bag().is_empty() );
+        transaction.insert( 9 );
+        transaction += [ 5, 6, 7, 8 ].iter().cloned().collect();
+        assert_eq!( elements( transaction.bag() ), vec![ 9, 5, 6, 7, 8 ] );
+    }
+    assert_eq!( elements( &test_bag ), vec![ 3, 1, 2 ] );
+    assert_eq!( test_bag.capacity(), 4 );
+
+    // E. An erase whose copies include the last elements, written over
+    // by later inserts, is rolled back as one change.
+    let mut test_bag: Bag<i32> = [ 1, 2, 1, 3, 1, 1 ].iter().cloned().collect();
+    let before = elements( &test_bag );
+    {
+        let mut transaction = test_bag.begin();
+        assert_eq!( transaction.erase( 1 ), 4 );
+        assert_eq!( transaction.changes(), 1 );
+
+        for value in 5..9
+        {
+            transaction.insert( value );
+        }
+
+        transaction.rollback();
+    }
+    assert_eq!( elements( &test_bag ), before );
+}
+
+#[ test ]
+///   Performs tests of rolling back changes to a sorted bag.
+fn test2()
+{
+    let mut test_bag: Bag<i32> = [ 9, 3, 5, 3, 1, 7 ].iter().cloned().collect();
+    test_bag.sort();
+    let before = elements( &test_bag );
+
+    // A. Erases keep the order and are undone in it.
+    let mut transaction = test_bag.begin();
+    assert_eq!( transaction.erase( 3 ), 2 );
+    assert!( transaction.erase_one( 1 ) );
+    assert!( transaction.bag().is_sorted() );
+    assert_eq!( elements( transaction.bag() ), vec![ 5, 7, 9 ] );
+    transaction.rollback();
+
+    assert_eq!( elements( &test_bag ), before );
+    assert!( test_bag.is_sorted() );
+
+    // B. An insert after an erase, over the place of the erased element.
+    {
+        let mut transaction = test_bag.begin();
+        transaction.erase_one( 1 );
+        transaction.insert( 4 );
+        assert!( !transaction.bag().is_sorted() );
+    }
+
+    assert_eq!( elements( &test_bag ), before );
+    assert!( test_bag.is_sorted() );
+    assert_eq!( test_bag.binary_search_count( &3 ), 2 );
+}
+
+#[ test ]
+///   Performs tests of commit( ) and of dropping a transaction.
+fn test3()
+{
+    let mut test_bag: Bag<i32> = [ 1, 2, 3 ].iter().cloned().collect();
+
+    // A. Commit keeps the changes.
+    let mut transaction = test_bag.begin();
+    transaction.erase_one( 2 );
+    transaction.insert( 4 );
+    transaction.commit();
+    assert_eq!( elements( &test_bag ), vec![ 1, 3, 4 ] );
+
+    // B. A dropped transaction is rolled back.
+    {
+        let mut transaction = test_bag.begin();
+        assert_eq!( transaction.on_drop(), DropAction::Rollback );
+        transaction.erase( 1 );
+    }
+    assert_eq!( elements( &test_bag ), vec![ 1, 3, 4 ] );
+
+    // C. Unless it is set to commit.
+    {
+        let mut transaction = test_bag.begin();
+        transaction.set_on_drop( DropAction::Commit );
+        transaction.erase( 1 );
+    }
+    assert_eq!( elements( &test_bag ), vec![ 4, 3 ] );
+
+    // D. The erased element itself is put back, not the target.
+    let mut test_bag: Bag<f64> = Bag::new();
+    test_bag.insert( -0.0 );
+    test_bag.begin().erase_one( 0.0 );
+    assert!( elements( &test_bag )[ 0 ].is_sign_negative() );
+}
+
+#[ test ]
+///   Performs random transactions checked against a copy of the bag.
+fn test4()
+{
+    let mut rng = XorShiftRng::from_seed( [ 44, 2, 3, 4 ] );
+
+    for round in 0..200
+    {
+        let mut test_bag = Bag::new();
+
+        for _ in 0..rng.gen_range( 0, 20 )
+        {
+            test_bag.insert( rng.gen_range( 0, 6 ) );
+        }
+
+        if round % 2 == 0
+        {
+            test_bag.sort();
+        }
+
+        let before = elements( &test_bag );
+        let capacity = test_bag.capacity();
+        let sorted = test_bag.is_sorted();
+        let mut copy = test_bag.clone();
+
+        {
+            let mut transaction = test_bag.begin();
+
+            // A. Each change matches the same change to a copy.
+            for _ in 0..rng.gen_range( 0, 30 )
+            {
+                let value = rng.gen_range( 0, 6 );
+
+                match rng.gen_range( 0, 10 )
+                {
+                    0..=3 => { transaction.insert( value ); copy.insert( value ); }
+                    4 | 5 => assert_eq!( transaction.erase_one( value ), copy.erase_one( value ) ),
+                    6 | 7 => assert_eq!( transaction.erase( value ), copy.erase( value ) ),
+                    8     => { transaction.clear(); copy.clear(); }
+                    _     =>
+                        {
+                            let other: Bag<i32> = [ value, value + 1 ].iter().cloned().collect();
+                            transaction += other.clone();
+                            copy += other;
+                        }
+                }
+
+                assert_eq!( elements( transaction.bag() ), elements( &copy ) );
+            }
+
+            if round % 3 == 0
+            {
+                transaction.rollback();
+            }
+        }
+
+        // B. The bag is as it was.
+        assert_eq!( elements( &test_bag ), before );
+        assert_eq!( test_bag.capacity(), capacity );
+        assert_eq!( test_bag.is_sorted(), sorted );
+    }
+}
@@ -0,0 +1,414 @@
+// FILE: bagundo.rs
+
+//! A `bag::Bag` with a history of the operations on it, any number of
+//! which can be undone and redone.
+//!
+//! Each `insert( _ )`, `erase( _ )`, `erase_one( _ )`, `clear()`, `+=`,
+//! `assign( _ )` and `clone_from( _ )` is made through a transaction of bagtransaction,
+//! and its undo log is kept in the history with the operation. `undo()`
+//! reverses the newest operation, leaving the bag as it was before it,
+//! in the same order, and `redo()` makes it again. A new operation
+//! after an undo discards the operations that could have been redone.
+//!
+//! The history can be limited to a number of operations, past which
+//! the oldest are forgotten.
+
+use bag::Bag;
+use bagtransaction::{ self, Undo };
+use std::collections::VecDeque;
+use std::collections::vec_deque;
+use std::fmt::{ self, Debug, Display, Formatter };
+use std::ops::AddAssign;
+use std::slice;
+
+/// An operation recorded in the history of an `UndoableBag`.
+
+#[ derive( Debug, Clone, PartialEq ) ]
+pub enum Operation<Type: PartialEq + Clone + Default>
+{
+    /// An insert of the element.
+    Insert( Type ),
+    /// An erase of all copies of the element.
+    Erase( Type ),
+    /// An erase of one copy of the element.
+    EraseOne( Type ),
+    /// A removal of all elements.
+    Clear,
+    /// A `+=` of the bag.
+    AddAssign( Bag<Type> ),
+    /// A replacement of the bag by the bag, as by assignment.
+    Assign( Bag<Type> ),
+    /// A copy of the bag made by `Bag::clone_from( _ )`.
+    CloneFrom( Bag<Type> )
+}
+
+impl<Type: PartialEq + Clone + Default + Display> Display for Operation<Type>
+{
+    /// Renders the operation as a command and its argument,
+    /// such as `insert 2` or `+= {1, 2}`.
+
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        match *self
+        {
+            Operation::Insert( ref value )      => write!( fmt, "insert {}", value ),
+            Operation::Erase( ref value )       => write!( fmt, "erase {}", value ),
+            Operation::EraseOne( ref value )    => write!( fmt, "erase_one {}", value ),
+            Operation::Clear                    => write!( fmt, "clear" ),
+            Operation::AddAssign( ref other )   => write!( fmt, "+= {}", other ),
+            Operation::Assign( ref other )      => write!( fmt, "assign {}", other ),
+            Operation::CloneFrom( ref source )  => write!( fmt, "clone_from {}", source )
+        }
+    }
+}
+
+// An operation that can be undone, with the log that undoes it.
+struct Step<Type: PartialEq + Clone + Default>
+{
+    operation: Operation<Type>,
+    log: Vec<Undo<Type>>
+}
+
+/// A bag whose operations can be undone and redone.
+///
+/// # Invariant of the UndoableBag struct:
+/// 1. Undoing the logs of `undo` from the last to the first returns
+/// `bag` to its state at the oldest operation remembered.
+///
+/// 2. The last operation of `redo` is the next to be redone, and is
+/// the operation most recently undone.
+///
+/// 3. If `limit` is Some, `undo` holds no more operations than it.
+
+pub struct UndoableBag<Type: PartialEq + Clone + Default>
+{
+    bag: Bag<Type>,
+    undo: VecDeque<Step<Type>>,
+    redo: Vec<Operation<Type>>,
+    limit: Option<usize>
+}
+
+impl<Type: PartialEq + Clone + Default> UndoableBag<Type>
+{
+    /// Initialize an empty bag with an unlimited history.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut bag = UndoableBag::new();
+    /// bag.insert( 1 );
+    /// bag.insert( 2 );
+    ///
+    /// assert!( bag.undo() );
+    /// assert_eq!( bag.bag().occurrences( 2 ), 0 );
+    ///
+    /// assert!( bag.redo() );
+    /// assert_eq!( bag.bag().occurrences( 2 ), 1 );
+    /// ```
+
+    pub fn new() -> Self
+    {
+        UndoableBag::from( Bag::new() )
+    }
+
+    /// Initialize an empty bag remembering at most `limit` operations.
+
+    pub fn with_limit( limit: usize ) -> Self
+    {
+        let mut bag = UndoableBag::new();
+        bag.limit = Some( limit );
+        bag
+    }
+
+    /// The bag as changed by the operations not undone.
+
+    pub fn bag( &self ) -> &Bag<Type>
+    {
+        &self.bag
+    }
+
+    /// The bag, forgetting the history.
+
+    pub fn into_bag( self ) -> Bag<Type>
+    {
+        self.bag
+    }
+
+    /// The most operations the history remembers, or None if unlimited.
+
+    pub fn limit( &self ) -> Option<usize>
+    {
+        self.limit
+    }
+
+    /// Change the most operations the history remembers.
+    ///
+    /// # Postcondition
+    /// If more operations can be undone than `limit` allows,
+    /// the oldest are forgotten. The bag is not altered.
+
+    pub fn set_limit( &mut self, limit: Option<usize> )
+    {
+        self.limit = limit;
+        self.trim_history();
+    }
+
+    /// The operations that can be undone, from the oldest to the newest.
+
+    pub fn history( &self ) -> History<'_, Type>
+    {
+        History { steps: self.undo.iter() }
+    }
+
+    /// The operations that can be redone, from the next to be redone.
+
+    pub fn redo_history( &self ) -> ::std::iter::Rev<slice::Iter<'_, Operation<Type>>>
+    {
+        self.redo.iter().rev()
+    }
+
+    /// Forget every operation, so that none can be undone or redone.
+    ///
+    /// # Postcondition
+    /// The bag is not altered.
+
+    pub fn clear_history( &mut self )
+    {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Add a new element to the bag, as `Bag::insert( _ )` does.
+
+    pub fn insert( &mut self, new_item: Type )
+    {
+        self.record( Operation::Insert( new_item ) );
+    }
+
+    /// Erase all copies of an element from the bag, as `Bag::erase( _ )` does.
+    ///
+    /// # Return
+    /// The number of items erased from bag. An erase of no items is
+    /// not recorded.
+
+    pub fn erase( &mut self, target: Type ) -> usize
+    {
+        self.record( Operation::Erase( target ) )
+    }
+
+    /// Remove one copy of an element from the bag, as `Bag::erase_one( _ )` does.
+    ///
+    /// # Return
+    /// True or false depending on whether `target` exists in the bag.
+    /// An erase of no item is not recorded.
+
+    pub fn erase_one( &mut self, target: Type ) -> bool
+    {
+        self.record( Operation::EraseOne( target ) ) == 1
+    }
+
+    /// Remove all elements from the bag, as `Bag::clear()` does.
+
+    pub fn clear( &mut self )
+    {
+        self.record( Operation::Clear );
+    }
+
+    /// Replace the bag by `other`, as assigning `other` to it does.
+    ///
+    /// # Postcondition
+    /// The bag is `other`, with its elements, order and capacity, and
+    /// the change is undone as one operation.
+
+    pub fn assign( &mut self, other: Bag<Type> )
+    {
+        self.record( Operation::Assign( other ) );
+    }
+
+    /// Make the bag a copy of `source`, as `Bag::clone_from( _ )` does.
+    ///
+    /// # Postcondition
+    /// The bag holds the elements of `source` in the same order, and
+    /// the change is undone as one operation.
+    ///
+    /// # Note
+    /// `source` is copied twice: once into the bag and once into the
+    /// history, for redo. The old bag is moved, not copied.
+
+    pub fn clone_from( &mut self, source: &Bag<Type> )
+    {
+        self.record( Operation::CloneFrom( source.clone() ) );
+    }
+
+    /// Undo the newest operation not undone.
+    ///
+    /// # Postcondition
+    /// The bag is as it was before the operation, in the same order and
+    /// with the same capacity, and the operation can be redone.
+    ///
+    /// # Return
+    /// False if there was no operation to undo.
+
+    pub fn undo( &mut self ) -> bool
+    {
+        match self.undo.pop_back()
+        {
+            None            => false,
+            Some( step )    =>
+                {
+                    bagtransaction::undo_log( &mut self.bag, step.log );
+                    self.redo.push( step.operation );
+                    true
+                }
+        }
+    }
+
+    /// Redo the operation most recently undone.
+    ///
+    /// # Return
+    /// False if there was no operation to redo.
+
+    pub fn redo( &mut self ) -> bool
+    {
+        match self.redo.pop()
+        {
+            None                => false,
+            Some( operation )   =>
+                {
+                    let log = apply( &mut self.bag, &operation ).1;
+                    self.push_step( Step { operation, log } );
+                    true
+                }
+        }
+    }
+
+    // Makes a new operation and records it, forgetting those that
+    // could be redone, and returns the number of elements it changed.
+    fn record( &mut self, operation: Operation<Type> ) -> usize
+    {
+        let ( changed, log ) = apply( &mut self.bag, &operation );
+
+        if !log.is_empty()
+        {
+            self.redo.clear();
+            self.push_step( Step { operation, log } );
+        }
+
+        changed
+    }
+
+    // Remembers a step that can be undone, within the limit.
+    fn push_step( &mut self, step: Step<Type> )
+    {
+        self.undo.push_back( step );
+        self.trim_history();
+    }
+
+    // Forgets the oldest operations past the limit.
+    fn trim_history( &mut self )
+    {
+        if let Some( limit ) = self.limit
+        {
+            while self.undo.len() > limit
+            {
+                self.undo.pop_front();
+            }
+        }
+    }
+}
+
+// Makes `operation` on `bag` through a transaction, returning the
+// number of elements erased, or 0 for other operations, and the log
+// that undoes it.
+fn apply<Type: PartialEq + Clone + Default>( bag: &mut Bag<Type>, operation: &Operation<Type> )
+    -> ( usize, Vec<Undo<Type>> )
+{
+    let mut transaction = bag.begin();
+    let mut changed = 0;
+
+    match *operation
+    {
+        Operation::Insert( ref value )      => transaction.insert( value.clone() ),
+        Operation::Erase( ref value )       => changed = transaction.erase( value.clone() ),
+        Operation::EraseOne( ref value )    => changed = transaction.erase_one( value.clone() ) as usize,
+        Operation::Clear                    => transaction.clear(),
+        Operation::AddAssign( ref other )   => transaction += other.clone(),
+        Operation::Assign( ref other )      => transaction.assign( other.clone() ),
+        Operation::CloneFrom( ref source )  => transaction.clone_from( source )
+    }
+
+    ( changed, transaction.into_log() )
+}
+
+impl<Type: PartialEq + Clone + Default> Default for UndoableBag<Type>
+{
+    fn default() -> Self
+    {
+        UndoableBag::new()
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> From<Bag<Type>> for UndoableBag<Type>
+{
+    /// Start a history of the operations on `bag`, with no limit.
+
+    fn from( bag: Bag<Type> ) -> Self
+    {
+        UndoableBag { bag, undo: VecDeque::new(), redo: Vec::new(), limit: None }
+    }
+}
+
+impl<Type: PartialEq + Clone + Default> AddAssign<Bag<Type>> for UndoableBag<Type>
+{
+    /// Add the elements of `other` to the bag, as `+=` on a bag does.
+
+    fn add_assign( &mut self, other: Bag<Type> )
+    {
+        self.record( Operation::AddAssign( other ) );
+    }
+}
+
+impl<Type: PartialEq + Clone + Default + Debug> Debug for UndoableBag<Type>
+{
+    fn fmt( &self, fmt: &mut Formatter ) -> fmt::Result
+    {
+        fmt.debug_struct( "UndoableBag" )
+           .field( "bag", &self.bag )
+           .field( "history", &self.history().collect::<Vec<&Operation<Type>>>() )
+           .field( "redo_history", &self.redo_history().collect::<Vec<&Operation<Type>>>() )
+           .field( "limit", &self.limit )
+           .finish()
+    }
+}
+
+/// An iterator over the operations that can be undone,
+/// from the oldest to the newest.
+
+pub struct History<'a, Type: 'a + PartialEq + Clone + Default>
+{
+    steps: vec_deque::Iter<'a, Step<Type>>
+}
+
+impl<'a, Type: PartialEq + Clone + Default> Iterator for History<'a, Type>
+{
+    type Item = &'a Operation<Type>;
+
+    fn next( &mut self ) -> Option<&'a Operation<Type>>
+    {
+        self.steps.next().map( | step | &step.operation )
+    }
+
+    fn size_hint( &self ) -> ( usize, Option<usize> )
+    {
+        self.steps.size_hint()
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> DoubleEndedIterator for History<'a, Type>
+{
+    fn next_back( &mut self ) -> Option<&'a Operation<Type>>
+    {
+        self.steps.next_back().map( | step | &step.operation )
+    }
+}
+
+impl<'a, Type: PartialEq + Clone + Default> ExactSizeIterator for History<'a, Type> {}
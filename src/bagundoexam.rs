@@ -0,0 +1,230 @@
+// FILE: bagundoexam.rs
+
+//! This module holds tests for the undoable bag of bagundo
+//!
+//! 1) tests of undoing and redoing each operation
+//!
+//! 2) tests of the history and of operations after an undo
+//!
+//! 3) tests of the history limit
+//!
+//! 4) random operations undone and redone, checked against copies of the bag
+//!
+//! 5) tests of assign and clone_from, and of the capacity they leave
+//!
+//! Run with `cargo test`
+
+extern crate rand;
+
+use self::rand::{ Rng, SeedableRng, XorShiftRng };
+use bag::Bag;
+use bag::len_trait::capacity::Capacity;
+use bagundo::{ UndoableBag, Operation };
+
+// The elements of the bag in their order.
+fn elements( bag: &UndoableBag<i32> ) -> Vec<i32>
+{
+    bag.bag().into_iter().cloned().collect()
+}
+
+#[ test ]
+///   Performs tests of undoing and redoing each operation.
+fn test1()
+{
+    let mut test_bag = UndoableBag::from( [ 5, 1, 4, 1, 3 ].iter().cloned().collect::<Bag<i32>>() );
+
+    // A. Each operation, undone, leaves the bag as it was.
+    let mut states = vec![ elements( &test_bag ) ];
+
+    test_bag.insert( 9 );
+    states.push( elements( &test_bag ) );
+    assert_eq!( test_bag.erase( 1 ), 2 );
+    states.push( elements( &test_bag ) );
+    assert!( test_bag.erase_one( 5 ) );
+    states.push( elements( &test_bag ) );
+    test_bag += [ 7, 7 ].iter().cloned().collect();
+    states.push( elements( &test_bag ) );
+    test_bag.clear();
+    states.push( elements( &test_bag ) );
+    test_bag.assign( [ 2, 6 ].iter().cloned().collect() );
+    states.push( elements( &test_bag ) );
+
+    assert_eq!( states[ 5 ], vec![] );
+    assert_eq!( states[ 6 ], vec![ 2, 6 ] );
+
+    for state in states.iter().rev().skip( 1 )
+    {
+        assert!( test_bag.undo() );
+        assert_eq!( &elements( &test_bag ), state );
+    }
+
+    assert!( !test_bag.undo() );
+    assert_eq!( test_bag.bag().capacity(), 8 );
+
+    // B. Each operation, redone, is made again.
+    for state in &states[ 1.. ]
+    {
+        assert!( test_bag.redo() );
+        assert_eq!( &elements( &test_bag ), state );
+    }
+
+    assert!( !test_bag.redo() );
+}
+
+#[ test ]
+///   Performs tests of the history and of operations after an undo.
+fn test2()
+{
+    let mut test_bag = UndoableBag::new();
+
+    // A. Erases of nothing are not recorded.
+    test_bag.insert( 1 );
+    test_bag.insert( 2 );
+    assert_eq!( test_bag.erase( 3 ), 0 );
+    assert!( !test_bag.erase_one( 3 ) );
+    test_bag += [ 3 ].iter().cloned().collect();
+
+    assert_eq!( test_bag.history().collect::<Vec<&Operation<i32>>>(),
+                vec![ &Operation::Insert( 1 ), &Operation::Insert( 2 ), &Operation::AddAssign( [ 3 ].iter().cloned().collect() ) ] );
+
+    // B. Undone operations move to the redo history, the next first.
+    test_bag.undo();
+    test_bag.undo();
+    assert_eq!( test_bag.history().len(), 1 );
+    assert_eq!( test_bag.redo_history().map( | operation | operation.to_string() ).collect::<Vec<String>>(),
+                vec![ "insert 2", "+= {3}" ] );
+
+    // C. A new operation discards the redo history.
+    test_bag.erase_one( 1 );
+    assert_eq!( test_bag.redo_history().count(), 0 );
+    assert!( !test_bag.redo() );
+    assert_eq!( test_bag.history().next_back(), Some( &Operation::EraseOne( 1 ) ) );
+
+    // D. Forgetting the history keeps the bag.
+    test_bag.insert( 4 );
+    test_bag.clear_history();
+    assert!( !test_bag.undo() );
+    assert_eq!( elements( &test_bag ), vec![ 4 ] );
+}
+
+#[ test ]
+///   Performs tests of the history limit.
+fn test3()
+{
+    let mut test_bag = UndoableBag::with_limit( 3 );
+    assert_eq!( test_bag.limit(), Some( 3 ) );
+
+    // A. Only the newest operations are remembered.
+    for value in 0..5
+    {
+        test_bag.insert( value );
+    }
+
+    assert_eq!( test_bag.history().len(), 3 );
+    while test_bag.undo() {}
+    assert_eq!( elements( &test_bag ), vec![ 0, 1 ] );
+
+    // B. Redone operations are kept within the limit.
+    while test_bag.redo() {}
+    assert_eq!( test_bag.history().len(), 3 );
+
+    // C. Lowering the limit forgets the oldest.
+    test_bag.set_limit( Some( 1 ) );
+    assert_eq!( test_bag.history().collect::<Vec<&Operation<i32>>>(), vec![ &Operation::Insert( 4 ) ] );
+
+    // D. A limit of zero remembers nothing.
+    test_bag.set_limit( Some( 0 ) );
+    test_bag.clear();
+    assert!( !test_bag.undo() );
+
+    test_bag.set_limit( None );
+    assert_eq!( test_bag.limit(), None );
+}
+
+#[ test ]
+///   Performs random operations undone and redone, checked against copies of the bag.
+fn test4()
+{
+    let mut rng = XorShiftRng::from_seed( [ 45, 2, 3, 4 ] );
+
+    for _ in 0..50
+    {
+        let mut test_bag = UndoableBag::new();
+        let mut states = vec![ elements( &test_bag ) ];
+
+        // A. Random operations, remembering the bag after each.
+        for _ in 0..rng.gen_range( 0, 40 )
+        {
+            let value = rng.gen_range( 0, 5 );
+
+            let changed = match rng.gen_range( 0, 10 )
+            {
+                0..=4 => { test_bag.insert( value ); true }
+                5 | 6 => test_bag.erase_one( value ),
+                7     => test_bag.erase( value ) > 0,
+                8     => { test_bag.clear(); true }
+                _     => { test_bag += [ value ].iter().cloned().collect(); true }
+            };
+
+            if changed
+            {
+                states.push( elements( &test_bag ) );
+            }
+        }
+
+        // B. Undoing goes back through every state.
+        for state in states.iter().rev().skip( 1 )
+        {
+            assert!( test_bag.undo() );
+            assert_eq!( &elements( &test_bag ), state );
+        }
+
+        // C. Redoing comes forward through them again.
+        for state in &states[ 1.. ]
+        {
+            assert!( test_bag.redo() );
+            assert_eq!( &elements( &test_bag ), state );
+        }
+    }
+}
+
+#[ test ]
+///   Performs tests of assign and clone_from, and of the capacity they leave.
+fn test5()
+{
+    let mut source = Bag::with_capacity( 20 );
+    source.insert( 3 );
+    source.insert( 8 );
+
+    let mut test_bag = UndoableBag::from( Bag::with_capacity( 5 ) );
+    test_bag.insert( 1 );
+
+    // A. Assigning a bag replaces it, capacity and all.
+    test_bag.assign( source.clone() );
+    assert_eq!( elements( &test_bag ), vec![ 3, 8 ] );
+    assert_eq!( test_bag.bag().capacity(), 20 );
+    assert_eq!( test_bag.history().next_back().map( | operation | operation.to_string() ),
+                Some( "assign {3, 8}".to_string() ) );
+
+    assert!( test_bag.undo() );
+    assert_eq!( elements( &test_bag ), vec![ 1 ] );
+    assert_eq!( test_bag.bag().capacity(), 5 );
+
+    // B. clone_from copies the bag as Bag::clone_from does.
+    let mut expected = test_bag.bag().clone();
+    expected.clone_from( &source );
+
+    test_bag.clone_from( &source );
+    assert_eq!( elements( &test_bag ), vec![ 3, 8 ] );
+    assert_eq!( test_bag.bag().capacity(), expected.capacity() );
+    assert_eq!( test_bag.history().next_back(), Some( &Operation::CloneFrom( source.clone() ) ) );
+
+    // C. Undone, the bag and its capacity are as before, and redone, copied again.
+    assert!( test_bag.undo() );
+    assert_eq!( elements( &test_bag ), vec![ 1 ] );
+    assert_eq!( test_bag.bag().capacity(), 5 );
+
+    assert!( test_bag.redo() );
+    assert_eq!( elements( &test_bag ), vec![ 3, 8 ] );
+    assert_eq!( test_bag.bag().capacity(), expected.capacity() );
+}
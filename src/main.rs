@@ -6,21 +6,72 @@
 //! For the non-interactive test use `cargo test`
 //!
 //! For the interactive test use `cargo run`
+//!
+//! For the other commands use `cargo run -- --help`
 
 #![ allow( dead_code ) ]
 #![ allow( unused_imports ) ]
 
+// The documentation is laid out as it was written.
+#![ allow( clippy::empty_line_after_doc_comments, clippy::doc_lazy_continuation ) ]
+
 pub mod bag;
+pub mod bagtrait;
+pub mod bagformat;
+
+#[ macro_use ]
+pub mod bagconformance;
+pub mod bagproperty;
+pub mod bagscratch;
 pub mod bagexam;
+pub mod bagtransaction;
+pub mod bagtransactionexam;
+pub mod bagundo;
+pub mod bagundoexam;
+pub mod bagobserve;
+pub mod bagobserveexam;
+pub mod bagdelta;
+pub mod bagdeltaexam;
+pub mod bagdurable;
+pub mod bagdurableexam;
+pub mod bagspill;
+pub mod bagspillexam;
 
+pub mod bagnum;
+pub mod bagnumexam;
+pub mod bagtotal;
+pub mod bagtotalexam;
 pub mod bagf64;
 pub mod bagexamf64;
+pub mod bagmmap;
+pub mod bagmmapexam;
 
 #[ allow( non_snake_case ) ]
 pub mod bagVec;
 
 pub mod bagtest;
+pub mod bagcli;
+pub mod bagcount;
+pub mod bagcountexam;
+pub mod bagcsv;
+pub mod bagcsvexam;
+pub mod bagbounded;
+pub mod bagboundedexam;
+pub mod bagcountmin;
+pub mod bagcountminexam;
+pub mod baghyperloglog;
+pub mod baghyperloglogexam;
+pub mod bagquantile;
+pub mod bagquantileexam;
+pub mod bagbench;
+pub mod bagfuzz;
 
 fn main() {
-    ::bagtest::bagtest();
+    let args: Vec<String> = ::std::env::args().skip( 1 ).collect();
+
+    if let Err( message ) = ::bagcli::run( &args )
+    {
+        eprintln!( "error: {}", message );
+        ::std::process::exit( 2 );
+    }
 }